@@ -0,0 +1,35 @@
+use {
+    clap::Parser,
+    grug_app::App,
+    grug_db_disk::DiskDb,
+    grug_vm_wasm::WasmVm,
+    std::{fs, path::PathBuf},
+};
+
+#[derive(Parser)]
+pub struct ExportCmd {
+    /// Block height at which to export the state; zero for the latest
+    #[arg(long, default_value = "0")]
+    height: u64,
+
+    /// File to write the genesis state to; print to stdout if not provided
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+impl ExportCmd {
+    pub fn run(self, data_dir: PathBuf) -> anyhow::Result<()> {
+        let db = DiskDb::open(data_dir)?;
+        let genesis_state = App::<DiskDb, WasmVm>::new(db).do_export(self.height)?;
+        let json = serde_json::to_string_pretty(&genesis_state)?;
+
+        if let Some(output) = self.output {
+            fs::write(&output, json)?;
+            println!("Genesis state written to {output:?}");
+        } else {
+            println!("{json}");
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+mod export;
 mod keys;
 mod prompt;
 mod query;
@@ -5,11 +6,12 @@ mod reset;
 mod start;
 mod tendermint;
 mod tx;
+mod upgrades;
 
 use {
     crate::{
-        keys::KeysCmd, query::QueryCmd, reset::ResetCmd, start::StartCmd, tendermint::StatusCmd,
-        tx::TxCmd,
+        export::ExportCmd, keys::KeysCmd, query::QueryCmd, reset::ResetCmd, start::StartCmd,
+        tendermint::StatusCmd, tx::TxCmd,
     },
     anyhow::anyhow,
     clap::Parser,
@@ -38,6 +40,9 @@ struct Cli {
 
 #[derive(Parser)]
 enum Command {
+    /// Export the state as a genesis state, to start a new chain from
+    Export(ExportCmd),
+
     /// Manage keys [alias: k]
     #[command(subcommand, next_display_order = None, alias = "k")]
     Keys(KeysCmd),
@@ -73,11 +78,14 @@ async fn main() -> anyhow::Result<()> {
     };
     let data_dir = app_dir.join("data");
     let keys_dir = app_dir.join("keys");
+    let wasm_dir = app_dir.join("wasm");
+    let snapshot_dir = app_dir.join("snapshots");
 
     match cli.command {
+        Command::Export(cmd) => cmd.run(data_dir),
         Command::Keys(cmd) => cmd.run(keys_dir),
         Command::Query(cmd) => cmd.run().await,
-        Command::Start(cmd) => cmd.run(data_dir).await,
+        Command::Start(cmd) => cmd.run(data_dir, wasm_dir, snapshot_dir).await,
         Command::Status(cmd) => cmd.run().await,
         Command::Tx(cmd) => cmd.run(keys_dir).await,
         Command::UnsafeResetAll(cmd) => cmd.run(data_dir),
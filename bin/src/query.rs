@@ -60,12 +60,15 @@ enum SubCmd {
     Code {
         hash: Hash,
     },
-    /// Enumerate hashes of all Wasm byte codes
+    /// Enumerate metadata of all Wasm byte codes
     Codes {
         /// Start after this hash
         start_after: Option<Hash>,
         /// Maximum number of items to display
         limit: Option<u32>,
+        /// Only display codes uploaded by this account
+        #[arg(long)]
+        uploader: Option<Addr>,
     },
     /// Query metadata of a single account by address
     Account {
@@ -79,6 +82,13 @@ enum SubCmd {
         /// Maximum number of items to display
         limit: Option<u32>,
     },
+    /// Enumerate cronjobs and the time at which each of them is next due
+    Cronjobs {
+        /// Start after this address
+        start_after: Option<Addr>,
+        /// Maximum number of items to display
+        limit: Option<u32>,
+    },
     /// Query the raw value in a contract store by raw key
     WasmRaw {
         /// Contract address
@@ -140,7 +150,11 @@ impl QueryCmd {
             SubCmd::Codes {
                 start_after,
                 limit,
-            } => print_json_pretty(client.query_codes(start_after, limit, self.height).await?),
+                uploader,
+            } => {
+                let res = client.query_code_infos(start_after, limit, uploader, self.height).await?;
+                print_json_pretty(res)
+            },
             SubCmd::Account {
                 address,
             } => print_json_pretty(client.query_account(address, self.height).await?),
@@ -148,6 +162,10 @@ impl QueryCmd {
                 start_after,
                 limit,
             } => print_json_pretty(client.query_accounts(start_after, limit, self.height).await?),
+            SubCmd::Cronjobs {
+                start_after,
+                limit,
+            } => print_json_pretty(client.query_next_cronjobs(start_after, limit, self.height).await?),
             SubCmd::WasmRaw {
                 contract,
                 key_hex,
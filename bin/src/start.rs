@@ -1,6 +1,15 @@
 use {
-    clap::Parser, grug_app::App, grug_db_disk::DiskDb, grug_vm_wasm::WasmVm,
+    crate::upgrades::register_upgrade_handlers,
+    clap::Parser,
+    grug_app::{
+        App, Db, PruningStrategy, SnapshotStore, CODES, DEFAULT_QUERY_GAS_LIMIT,
+        DEFAULT_SIMULATE_GAS_LIMIT,
+    },
+    grug_db_disk::DiskDb,
+    grug_types::{from_borsh_slice, Order},
+    grug_vm_wasm::{init_module_cache, WasmVm, DEFAULT_MODULE_CACHE_CAPACITY},
     std::path::PathBuf,
+    tracing::info,
 };
 
 #[derive(Parser)]
@@ -12,14 +21,105 @@ pub struct StartCmd {
     /// Buffer size for reading chunks of incoming data from client
     #[arg(long, default_value = "1048576")]
     read_buf_size: usize,
+
+    /// Number of compiled Wasm modules to keep in memory
+    #[arg(long, default_value_t = DEFAULT_MODULE_CACHE_CAPACITY)]
+    wasm_cache_capacity: usize,
+
+    /// Don't save compiled Wasm modules to disk
+    #[arg(long)]
+    no_wasm_disk_cache: bool,
+
+    /// Create a state sync snapshot every this many blocks; zero to disable
+    #[arg(long, default_value = "0")]
+    snapshot_interval: u64,
+
+    /// Number of most recent snapshots to keep; zero to keep all
+    #[arg(long, default_value = "2")]
+    snapshot_keep_recent: usize,
+
+    /// Which historical states to keep: "nothing" (i.e. prune nothing),
+    /// "keep-recent=<n>" (the most recent n heights), or "keep-every=<i>,<n>"
+    /// (every i-th height, and the most recent n heights)
+    #[arg(long, default_value = "nothing", value_parser = parse_pruning_strategy)]
+    pruning: PruningStrategy,
+
+    /// Maximum amount of gas a query may consume
+    #[arg(long, default_value_t = DEFAULT_QUERY_GAS_LIMIT)]
+    query_gas_limit: u64,
+
+    /// Maximum amount of gas a transaction may consume when simulated
+    #[arg(long, default_value_t = DEFAULT_SIMULATE_GAS_LIMIT)]
+    simulate_gas_limit: u64,
 }
 
 impl StartCmd {
-    pub async fn run(self, data_dir: PathBuf) -> anyhow::Result<()> {
+    pub async fn run(
+        self,
+        data_dir: PathBuf,
+        wasm_dir: PathBuf,
+        snapshot_dir: PathBuf,
+    ) -> anyhow::Result<()> {
         // create DB backend
-        let db = DiskDb::open(data_dir)?;
+        // states kept at an interval are read from the DB's archive
+        let db = match self.pruning.keep_every() {
+            Some(interval) => DiskDb::open_with_archive(data_dir, interval)?,
+            None => DiskDb::open(data_dir)?,
+        };
+
+        // create the compiled module cache, and fill it up with the codes that
+        // have been uploaded so far, so that they don't need to be compiled
+        // during block processing
+        let wasm_dir = (!self.no_wasm_disk_cache).then_some(wasm_dir);
+        let cache = init_module_cache(self.wasm_cache_capacity, wasm_dir)?;
+        if db.latest_version().is_some() {
+            let storage = db.state_storage(None);
+            let programs = CODES
+                .range(&storage, None, None, Order::Ascending)
+                .map(|res| Ok(from_borsh_slice::<Vec<u8>>(res?.1)?))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let count = cache.warm_up(programs)?;
+            info!(count, "Warmed up Wasm module cache");
+        }
+
+        let mut app = App::<DiskDb, WasmVm>::new(db)
+            .with_pruning(self.pruning)
+            .with_query_gas_limit(self.query_gas_limit)
+            .with_simulate_gas_limit(self.simulate_gas_limit);
+        app = register_upgrade_handlers(app);
+
+        if self.snapshot_interval > 0 {
+            let interval = self.snapshot_interval;
+            let keep_recent = self.snapshot_keep_recent;
+            app = app.with_snapshot_store(SnapshotStore::new(snapshot_dir, interval, keep_recent)?);
+        }
 
         // start the ABCI server
-        Ok(App::<DiskDb, WasmVm>::new(db).start_abci_server(self.read_buf_size, self.abci_addr)?)
+        Ok(app.start_abci_server(self.read_buf_size, self.abci_addr)?)
+    }
+}
+
+fn parse_pruning_strategy(s: &str) -> anyhow::Result<PruningStrategy> {
+    if s == "nothing" {
+        return Ok(PruningStrategy::Nothing);
+    }
+
+    let (strategy, args) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("invalid pruning strategy: `{s}`"))?;
+    let args = args.split(',').map(str::parse::<u64>).collect::<Result<Vec<_>, _>>()?;
+
+    anyhow::ensure!(
+        args.iter().all(|n| *n > 0),
+        "pruning strategy `{strategy}` requires positive numbers"
+    );
+
+    match (strategy, args.as_slice()) {
+        ("keep-recent", [n]) => Ok(PruningStrategy::KeepRecent(*n)),
+        ("keep-every", [interval, keep_recent]) => Ok(PruningStrategy::KeepEvery {
+            interval:    *interval,
+            keep_recent: *keep_recent,
+        }),
+        _ => anyhow::bail!("invalid pruning strategy: `{s}`"),
     }
 }
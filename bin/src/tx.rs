@@ -4,7 +4,10 @@ use {
     clap::{Parser, Subcommand},
     colored::Colorize,
     grug_sdk::{Client, SigningKey, SigningOptions},
-    grug_types::{from_json_slice, hash, Addr, Binary, Coins, Config, Hash, Message},
+    grug_types::{
+        from_json_slice, hash, Addr, Binary, Coins, Config, ConsensusParams, Hash, Message,
+        Timestamp, Uint64,
+    },
     serde::Serialize,
     std::{fs::File, io::Read, path::PathBuf, str::FromStr},
     tendermint_rpc::endpoint::broadcast::tx_sync,
@@ -32,6 +35,19 @@ pub struct TxCmd {
     #[arg(long)]
     sequence: Option<u32>,
 
+    /// Maximum amount of gas the transaction is allowed to consume
+    #[arg(long, default_value = "1000000")]
+    gas_limit: u64,
+
+    /// The last block height at which the transaction can be included
+    #[arg(long)]
+    expiry_height: Option<u64>,
+
+    /// The last block timestamp, in UNIX seconds, at which the transaction can
+    /// be included
+    #[arg(long)]
+    expiry_timestamp: Option<u64>,
+
     #[command(subcommand)]
     subcmd: SubCmd,
 }
@@ -43,6 +59,11 @@ enum SubCmd {
         /// New configurations as a JSON string
         new_cfg: String,
     },
+    /// Schedule changes to CometBFT's consensus parameters
+    UpdateConsensusParams {
+        /// Consensus parameter updates as a JSON string
+        updates: String,
+    },
     /// Send coins to the given recipient address
     Transfer {
         /// Recipient address
@@ -54,6 +75,10 @@ enum SubCmd {
     Store {
         /// Path to the Wasm file
         path: PathBuf,
+        /// Who is allowed to instantiate contracts using this code, as a JSON
+        /// string [default: everybody]
+        #[arg(long)]
+        instantiate_permission: Option<String>,
     },
     /// Instantiate a new contract
     Instantiate {
@@ -69,6 +94,9 @@ enum SubCmd {
         /// Administrator address for the contract
         #[arg(long)]
         admin: Option<Addr>,
+        /// Refuse calls to the contract while it's already on the call stack
+        #[arg(long)]
+        non_reentrant: bool,
     },
     /// Upload code and instantiate a contract in one go
     StoreAndInstantiate {
@@ -84,6 +112,9 @@ enum SubCmd {
         /// Administrator address for the contract
         #[arg(long)]
         admin: Option<Addr>,
+        /// Refuse calls to the contract while it's already on the call stack
+        #[arg(long)]
+        non_reentrant: bool,
     },
     /// Execute a contract
     Execute {
@@ -104,6 +135,18 @@ enum SubCmd {
         /// Migrate message as a JSON string
         msg: String,
     },
+    /// Update the administrator address of a contract
+    UpdateAdmin {
+        /// Contract address
+        contract: Addr,
+        /// New administrator address
+        new_admin: Addr,
+    },
+    /// Remove the administrator of a contract, making it no longer migratable
+    ClearAdmin {
+        /// Contract address
+        contract: Addr,
+    },
     /// Create an IBC light client
     CreateClient {
         /// Hash of the Wasm byte code to be associated with the contract
@@ -141,7 +184,13 @@ impl TxCmd {
             SubCmd::SetConfig { new_cfg } => {
                 let new_cfg: Config = from_json_slice(new_cfg.as_bytes())?;
                 vec![Message::SetConfig {
-                    new_cfg,
+                    new_cfg: Box::new(new_cfg),
+                }]
+            },
+            SubCmd::UpdateConsensusParams { updates } => {
+                let updates: ConsensusParams = from_json_slice(updates.as_bytes())?;
+                vec![Message::UpdateConsensusParams {
+                    updates,
                 }]
             },
             SubCmd::Transfer { to, coins } => {
@@ -151,24 +200,29 @@ impl TxCmd {
                     coins,
                 }]
             },
-            SubCmd::Store { path } => {
+            SubCmd::Store { path, instantiate_permission } => {
                 let mut file = File::open(path)?;
                 let mut code = vec![];
                 file.read_to_end(&mut code)?;
+                let instantiate_permission = instantiate_permission
+                    .map(|permission| from_json_slice(permission.as_bytes()))
+                    .transpose()?;
                 vec![Message::Upload {
                     code: code.into(),
+                    instantiate_permission,
                 }]
             },
-            SubCmd::Instantiate { code_hash, msg, salt, funds, admin } => {
+            SubCmd::Instantiate { code_hash, msg, salt, funds, admin, non_reentrant } => {
                 vec![Message::Instantiate {
                     msg:   msg.into_bytes().into(),
                     salt:  salt.into_bytes().into(),
                     funds: Coins::from_str(&funds.unwrap_or_default())?,
                     code_hash,
                     admin,
+                    non_reentrant,
                 }]
             },
-            SubCmd::StoreAndInstantiate { path, msg, salt, funds, admin } => {
+            SubCmd::StoreAndInstantiate { path, msg, salt, funds, admin, non_reentrant } => {
                 let mut file = File::open(path)?;
                 let mut code = vec![];
                 file.read_to_end(&mut code)?;
@@ -176,6 +230,7 @@ impl TxCmd {
                 vec![
                     Message::Upload {
                         code: code.into(),
+                        instantiate_permission: None,
                     },
                     Message::Instantiate {
                         msg:   msg.into_bytes().into(),
@@ -183,6 +238,7 @@ impl TxCmd {
                         funds: Coins::from_str(funds.as_deref().unwrap_or(Coins::EMPTY_COINS_STR))?,
                         code_hash,
                         admin,
+                        non_reentrant,
                     },
                 ]
             },
@@ -200,6 +256,17 @@ impl TxCmd {
                     contract,
                 }]
             },
+            SubCmd::UpdateAdmin { contract, new_admin } => {
+                vec![Message::UpdateAdmin {
+                    contract,
+                    new_admin,
+                }]
+            },
+            SubCmd::ClearAdmin { contract } => {
+                vec![Message::ClearAdmin {
+                    contract,
+                }]
+            },
             SubCmd::CreateClient { code_hash, client_state, consensus_state, salt } => {
                 vec![Message::CreateClient {
                     code_hash,
@@ -231,6 +298,9 @@ impl TxCmd {
             sender,
             chain_id: self.chain_id,
             sequence: self.sequence,
+            gas_limit: Uint64::new(self.gas_limit),
+            expiry_height: self.expiry_height.map(Uint64::new),
+            expiry_timestamp: self.expiry_timestamp.map(Timestamp::from_seconds),
         };
 
         // broadcast transaction
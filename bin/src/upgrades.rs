@@ -0,0 +1,19 @@
+use {grug_app::App, grug_db_disk::DiskDb, grug_vm_wasm::WasmVm};
+
+/// Register the handlers of the upgrades this binary knows how to apply.
+///
+/// Once the owner schedules an upgrade, a release of the binary that registers
+/// its handler here, under the upgrade's name, must be rolled out before the
+/// upgrade height. Nodes that run a binary without it halt at that height.
+/// Handlers of past upgrades should be kept, so that nodes can still sync the
+/// chain from genesis. For example:
+///
+/// ```ignore
+/// app.with_upgrade_handler("v2", |store, block| {
+///     // migrate the state
+///     Ok(())
+/// })
+/// ```
+pub fn register_upgrade_handlers(app: App<DiskDb, WasmVm>) -> App<DiskDb, WasmVm> {
+    app
+}
@@ -4,7 +4,8 @@ use {
     anyhow::bail,
     grug::{
         grug_derive, split_one_key, to_json_value, to_json_vec, Addr, AuthCtx, Binary, ImmutableCtx,
-        Item, Json, MapKey, Message, MutableCtx, RawKey, Response, StdError, StdResult, Tx,
+        Item, Json, MapKey, Message, MutableCtx, RawKey, Response, StdError, StdResult, Timestamp,
+        Tx, Uint64,
     },
     sha2::{Digest, Sha256},
 };
@@ -98,13 +99,22 @@ impl<'a> MapKey for &'a PublicKey {
 /// The bytes are defined as:
 ///
 /// ```plain
-/// bytes := blake3(json(msgs) | sender_addr | chain_id | sequence)
+/// bytes := sha256(
+///   json(msgs) | sender_addr | chain_id | sequence | gas_limit |
+///   expiry_height | expiry_timestamp
+/// )
 /// ```
 ///
 /// where:
 /// - `sender_addr` is a 32 bytes address of the sender;
 /// - `chain_id` is the chain ID in UTF-8 encoding;
-/// - `sequence` is the sender account's sequence in 32-bit big endian encoding.
+/// - `sequence` is the sender account's sequence in 32-bit big endian encoding;
+/// - `gas_limit` is the transaction's gas limit in 64-bit big endian encoding;
+/// - `expiry_height` is a single zero byte if the transaction doesn't expire by
+///   height, or a single one byte followed by the expiry height in 64-bit big
+///   endian encoding if it does;
+/// - `expiry_timestamp` is encoded the same way, with the timestamp in
+///   nanoseconds.
 ///
 /// TODO: json here is ambiguous, i.e. what padding and linebreak character to
 /// use, the order of fields... elaborate it.
@@ -116,12 +126,28 @@ pub fn sign_bytes(
     sender: &Addr,
     chain_id: &str,
     sequence: u32,
+    gas_limit: Uint64,
+    expiry_height: Option<Uint64>,
+    expiry_timestamp: Option<Timestamp>,
 ) -> anyhow::Result<[u8; 32]> {
     let mut hasher = Sha256::new();
     hasher.update(&to_json_vec(&msgs)?);
     hasher.update(sender);
     hasher.update(chain_id.as_bytes());
     hasher.update(sequence.to_be_bytes());
+    hasher.update(gas_limit.to_be_bytes());
+    for expiry in [
+        expiry_height.map(|height| height.u64()),
+        expiry_timestamp.map(|timestamp| timestamp.nanos()),
+    ] {
+        match expiry {
+            Some(value) => {
+                hasher.update([1]);
+                hasher.update(value.to_be_bytes());
+            },
+            None => hasher.update([0]),
+        }
+    }
     Ok(hasher.finalize().into())
 }
 
@@ -148,7 +174,15 @@ pub fn before_tx(ctx: AuthCtx, tx: Tx) -> anyhow::Result<Response> {
     let mut sequence = SEQUENCE.load(ctx.store)?;
 
     // prepare the hash that is expected to have been signed
-    let msg_hash = sign_bytes(&tx.msgs, &tx.sender, &ctx.chain_id, sequence)?;
+    let msg_hash = sign_bytes(
+        &tx.msgs,
+        &tx.sender,
+        &ctx.chain_id,
+        sequence,
+        tx.gas_limit,
+        tx.expiry_height,
+        tx.expiry_timestamp,
+    )?;
 
     // verify the signature
     // skip if we are in simulate mode
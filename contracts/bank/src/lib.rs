@@ -1,7 +1,7 @@
 #[cfg(not(feature = "library"))]
 use grug::entry_point;
 use {
-    anyhow::bail,
+    anyhow::{bail, ensure},
     grug::{
         grug_derive, Addr, BankQueryMsg, BankQueryResponse, Bound, Coin, Coins, ImmutableCtx, Map,
         MutableCtx, Order, Response, StdResult, Storage, SudoCtx, TransferMsg, Uint128,
@@ -35,6 +35,14 @@ pub enum ExecuteMsg {
         denom:  String,
         amount: Uint128,
     },
+    /// Transfer coins from an account without its approval. Only the chain's
+    /// taxman can do this, for the purpose of collecting transaction fees.
+    ForceTransfer {
+        from:   Addr,
+        to:     Addr,
+        denom:  String,
+        amount: Uint128,
+    },
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -109,6 +117,12 @@ pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
             denom,
             amount,
         } => burn(ctx, from, denom, amount),
+        ExecuteMsg::ForceTransfer {
+            from,
+            to,
+            denom,
+            amount,
+        } => force_transfer(ctx, from, to, denom, amount),
     }
 }
 
@@ -148,6 +162,27 @@ pub fn burn(
         .add_attribute("amount", amount))
 }
 
+pub fn force_transfer(
+    ctx:    MutableCtx,
+    from:   Addr,
+    to:     Addr,
+    denom:  String,
+    amount: Uint128,
+) -> anyhow::Result<Response> {
+    let cfg = ctx.query_info()?.config;
+    ensure!(ctx.sender == cfg.taxman, "only the taxman can force transfer");
+
+    decrease_balance(ctx.store, &from, &denom, amount)?;
+    increase_balance(ctx.store, &to, &denom, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "force_transfer")
+        .add_attribute("from", from)
+        .add_attribute("to", to)
+        .add_attribute("denom", denom)
+        .add_attribute("amount", amount))
+}
+
 /// Increase the total supply of a token by the given amount.
 /// Return the total supply value after the increase.
 fn increase_supply(
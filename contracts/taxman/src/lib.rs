@@ -0,0 +1,84 @@
+//! A reference implementation of the tax man, the core contract that levies
+//! transaction fees.
+//!
+//! It charges a flat price per unit of gas consumed. The fee is collected from
+//! the transaction's sender into this contract, using the bank contract's
+//! `ForceTransfer` method.
+
+#[cfg(not(feature = "library"))]
+use grug::entry_point;
+use {
+    grug::{
+        grug_derive, to_json_value, Coins, GasReport, ImmutableCtx, Item, Json, Message,
+        MutableCtx, Response, StdResult, SudoCtx, Uint128,
+    },
+    grug_bank::ExecuteMsg as BankExecuteMsg,
+};
+
+const CONFIG: Item<Config> = Item::new("config");
+
+#[grug_derive(serde, borsh)]
+pub struct Config {
+    /// The denomination in which fees are charged.
+    pub fee_denom: String,
+    /// The amount of `fee_denom` charged per unit of gas.
+    pub fee_rate:  Uint128,
+}
+
+#[grug_derive(serde)]
+pub struct InstantiateMsg {
+    pub config: Config,
+}
+
+#[grug_derive(serde)]
+pub enum QueryMsg {
+    /// Query the fee configuration.
+    /// Returns: Config
+    Config {},
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> StdResult<Response> {
+    CONFIG.save(ctx.store, &msg.config)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn handle_fee(ctx: SudoCtx, report: GasReport) -> StdResult<Response> {
+    let cfg = CONFIG.load(ctx.store)?;
+
+    // if the transaction ran out of gas, the amount of gas used may exceed the
+    // limit. we charge no more than what the sender has agreed to pay.
+    let gas_charged = report.used.min(report.limit);
+    let fee = cfg.fee_rate.checked_mul(gas_charged.into())?;
+
+    if fee.is_zero() {
+        return Ok(Response::new());
+    }
+
+    let bank = ctx.query_info()?.config.bank;
+
+    Ok(Response::new()
+        .add_message(Message::Execute {
+            contract: bank,
+            msg: to_json_value(&BankExecuteMsg::ForceTransfer {
+                from:   report.sender.clone(),
+                to:     ctx.contract,
+                denom:  cfg.fee_denom.clone(),
+                amount: fee,
+            })?,
+            funds: Coins::new_empty(),
+        })
+        .add_attribute("sender", report.sender)
+        .add_attribute("gas_charged", gas_charged)
+        .add_attribute("denom", cfg.fee_denom)
+        .add_attribute("amount", fee))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
+    match msg {
+        QueryMsg::Config {} => to_json_value(&CONFIG.load(ctx.store)?),
+    }
+}
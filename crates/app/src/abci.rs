@@ -1,17 +1,38 @@
 use {
-    crate::{App, AppError, AppResult, Db, Vm},
-    grug_types::{Attribute, BlockInfo, Event, Hash, Timestamp, Uint64, GENESIS_BLOCK_HASH},
+    crate::{App, AppError, AppResult, BlockOutcome, Db, Snapshot, TxOutcome, Vm},
+    grug_types::{
+        from_json_slice, to_json_vec, AbciParams, Attribute, BlockIdFlag, BlockInfo, BlockParams,
+        ConsensusParams, Duration, Event, EvidenceParams, ExtendedCommitInfo, ExtendedVote, Hash,
+        Timestamp, Uint64, ValidatorParams, ValidatorPubKey, ValidatorUpdate,
+        VerifyVoteExtensionMsg, GENESIS_BLOCK_HASH,
+    },
     prost::bytes::Bytes,
     std::{any::type_name, net::ToSocketAddrs},
     tendermint_abci::{Application, Error as ABCIError, ServerBuilder},
     tendermint_proto::{
         abci::{
-            Event as TmEvent, EventAttribute as TmAttribute, ExecTxResult, RequestCheckTx,
-            RequestFinalizeBlock, RequestInfo, RequestInitChain, RequestQuery, ResponseCheckTx,
-            ResponseCommit, ResponseFinalizeBlock, ResponseInfo, ResponseInitChain, ResponseQuery,
+            response_apply_snapshot_chunk::Result as ApplySnapshotChunkResult,
+            response_offer_snapshot::Result as OfferSnapshotResult,
+            response_process_proposal::ProposalStatus, response_verify_vote_extension::VerifyStatus,
+            Event as TmEvent, EventAttribute as TmAttribute, ExecTxResult,
+            ExtendedCommitInfo as TmExtendedCommitInfo, ExtendedVoteInfo,
+            RequestApplySnapshotChunk, RequestCheckTx, RequestExtendVote, RequestFinalizeBlock,
+            RequestInfo, RequestInitChain, RequestLoadSnapshotChunk, RequestOfferSnapshot,
+            RequestPrepareProposal, RequestProcessProposal, RequestQuery,
+            RequestVerifyVoteExtension, ResponseApplySnapshotChunk, ResponseCheckTx, ResponseCommit,
+            ResponseExtendVote, ResponseFinalizeBlock, ResponseInfo, ResponseInitChain,
+            ResponseListSnapshots, ResponseLoadSnapshotChunk, ResponseOfferSnapshot,
+            ResponsePrepareProposal, ResponseProcessProposal, ResponseQuery,
+            ResponseVerifyVoteExtension, Snapshot as TmSnapshot,
+            ValidatorUpdate as TmValidatorUpdate,
+        },
+        crypto::{public_key::Sum, ProofOp, ProofOps, PublicKey},
+        google::protobuf::{Duration as TmDuration, Timestamp as TmTimestamp},
+        types::{
+            AbciParams as TmAbciParams, BlockIdFlag as TmBlockIdFlag, BlockParams as TmBlockParams,
+            ConsensusParams as TmConsensusParams, EvidenceParams as TmEvidenceParams,
+            ValidatorParams as TmValidatorParams,
         },
-        crypto::{ProofOp, ProofOps},
-        google::protobuf::Timestamp as TmTimestamp,
     },
     tracing::Value,
 };
@@ -47,7 +68,7 @@ where
                     version:             env!("CARGO_PKG_VERSION").into(),
                     app_version:         1,
                     last_block_app_hash: last_block_version.into_vec().into(),
-                    last_block_height:   last_block_height as i64,
+                    last_block_height:   i64::try_from(last_block_height).unwrap_or(i64::MAX),
                 }
             },
             Err(err) => panic!("failed to get info: {err}"),
@@ -60,7 +81,24 @@ where
         // always matches block height.
         let block = from_tm_block(0, req.time, None);
 
-        match self.do_init_chain_raw(req.chain_id, block, &req.app_state_bytes) {
+        let consensus_params = match req.consensus_params.clone().map(from_tm_consensus_params) {
+            Some(Ok(consensus_params)) => consensus_params,
+            Some(Err(err)) => panic!("failed to init chain: {err}"),
+            None => ConsensusParams::default(),
+        };
+        let validators = req.validators.iter().cloned().map(from_tm_validator_update);
+        let validators = match validators.collect::<AppResult<Vec<_>>>() {
+            Ok(validators) => validators,
+            Err(err) => panic!("failed to init chain: {err}"),
+        };
+
+        match self.do_init_chain_raw(
+            req.chain_id,
+            block,
+            &req.app_state_bytes,
+            consensus_params,
+            validators,
+        ) {
             Ok(app_hash) => {
                 ResponseInitChain {
                     consensus_params: req.consensus_params,
@@ -72,16 +110,96 @@ where
         }
     }
 
+    fn prepare_proposal(&self, req: RequestPrepareProposal) -> ResponsePrepareProposal {
+        // the block hash isn't known until the block is proposed, so the
+        // proposal handler sees the placeholder hash
+        let block = from_tm_block(req.height, req.time, None);
+
+        // the votes for the previous height, along with their extensions and
+        // signatures. these are only given to the proposer, which passes them
+        // on to others in the proposal.
+        let extended_commit = match req.local_last_commit.map(from_tm_extended_commit) {
+            Some(Ok(extended_commit)) => extended_commit,
+            Some(Err(err)) => panic!("failed to prepare proposal: {err}"),
+            None => ExtendedCommitInfo::default(),
+        };
+
+        match self.do_prepare_proposal_raw(
+            block,
+            req.txs,
+            req.max_tx_bytes as u64,
+            extended_commit,
+        ) {
+            Ok(txs) => {
+                ResponsePrepareProposal {
+                    txs: txs.into_iter().map(|tx| Vec::from(tx).into()).collect(),
+                }
+            },
+            Err(err) => panic!("failed to prepare proposal: {err}"),
+        }
+    }
+
+    fn process_proposal(&self, req: RequestProcessProposal) -> ResponseProcessProposal {
+        let block = from_tm_block(req.height, req.time, Some(req.hash));
+
+        let status = match self.do_process_proposal_raw(block, req.txs) {
+            Ok(()) => ProposalStatus::Accept,
+            Err(_) => ProposalStatus::Reject,
+        };
+
+        ResponseProcessProposal {
+            status: status as i32,
+        }
+    }
+
+    fn extend_vote(&self, _req: RequestExtendVote) -> ResponseExtendVote {
+        // if the handler fails, we vote without an extension rather than not
+        // voting at all. other validators may reject it.
+        let vote_extension = self.do_extend_vote().unwrap_or_default();
+
+        ResponseExtendVote {
+            vote_extension: Vec::from(vote_extension).into(),
+        }
+    }
+
+    fn verify_vote_extension(
+        &self,
+        req: RequestVerifyVoteExtension,
+    ) -> ResponseVerifyVoteExtension {
+        let msg = VerifyVoteExtensionMsg {
+            validator: req.validator_address.to_vec().into(),
+            extension: req.vote_extension.to_vec().into(),
+        };
+
+        let status = match self.do_verify_vote_extension(msg) {
+            Ok(()) => VerifyStatus::Accept,
+            Err(_) => VerifyStatus::Reject,
+        };
+
+        ResponseVerifyVoteExtension {
+            status: status as i32,
+        }
+    }
+
     fn finalize_block(&self, req: RequestFinalizeBlock) -> ResponseFinalizeBlock {
         let block = from_tm_block(req.height, req.time, Some(req.hash));
 
         match self.do_finalize_block_raw(block, req.txs) {
-            Ok((app_hash, events, tx_results)) => {
+            Ok(BlockOutcome {
+                app_hash,
+                events,
+                tx_results,
+                validator_updates,
+                consensus_param_updates,
+            }) => {
                 ResponseFinalizeBlock {
                     events:                  events.into_iter().map(to_tm_event).collect(),
                     tx_results:              tx_results.into_iter().map(to_tm_tx_result).collect(),
-                    validator_updates:       vec![],
-                    consensus_param_updates: None,
+                    validator_updates:       validator_updates
+                        .into_iter()
+                        .map(to_tm_validator_update)
+                        .collect(),
+                    consensus_param_updates: consensus_param_updates.map(to_tm_consensus_params),
                     app_hash:                app_hash.into_vec().into(),
                 }
             },
@@ -91,19 +209,26 @@ where
 
     fn commit(&self) -> ResponseCommit {
         match self.do_commit() {
-            Ok(()) => {
-                ResponseCommit {
-                    retain_height: 0, // TODO: what this means??
-                }
+            // blocks below the retain height are no longer needed to serve the
+            // state at any height we keep, so CometBFT may delete them
+            Ok(retain_height) => ResponseCommit {
+                retain_height: i64::try_from(retain_height).unwrap_or(i64::MAX),
             },
             Err(err) => panic!("failed to commit: {err}"),
         }
     }
 
-    fn check_tx(&self, _req: RequestCheckTx) -> ResponseCheckTx {
-        // TODO
-        ResponseCheckTx {
-            ..Default::default()
+    fn check_tx(&self, req: RequestCheckTx) -> ResponseCheckTx {
+        match self.do_check_tx_raw(&req.tx) {
+            Ok(outcome) => to_tm_check_tx_result(outcome),
+            Err(err) => {
+                ResponseCheckTx {
+                    code:      err.code(),
+                    codespace: err.codespace(),
+                    log:       format!("failed to decode tx: {err}"),
+                    ..Default::default()
+                }
+            },
         }
     }
 
@@ -114,22 +239,59 @@ where
     // > be specified in data. Applications SHOULD allow queries over specific
     // > types like /accounts/... or /votes/....
     //
-    // Currently we're going neither of these. We ignore `path`, `height`, and
-    // `prove` fields, and interpret `data` as a JSON-encoded QueryRequest.
+    // Currently we're doing neither of these. The `/app` path interprets `data`
+    // as a JSON-encoded QueryRequest. If `prove` is true, the response includes
+    // one proof op for each storage key that the query response is derived from.
+    // Only `Info`, `Account`, `Code`, `CodeInfo`, and `WasmRaw` queries, as well
+    // as `Multi` queries consisting only of these, can be proved.
+    //
+    // Additionally, the `/simulate` path interprets `data` as a JSON-encoded
+    // SimulateRequest, and executes the transaction in simulation mode.
     fn query(&self, req: RequestQuery) -> ResponseQuery {
         match req.path.as_str() {
             "/app" => match self.do_query_app_raw(&req.data, req.height as u64, req.prove) {
+                Ok((res, proofs)) => {
+                    let proof_ops = proofs.map(|proofs| {
+                        ProofOps {
+                            ops: proofs
+                                .into_iter()
+                                .map(|(key, proof)| ProofOp {
+                                    r#type: type_name::<DB::Proof>().into(),
+                                    key,
+                                    data: proof,
+                                })
+                                .collect(),
+                        }
+                    });
+                    ResponseQuery {
+                        code:   0,
+                        value:  res.into(),
+                        height: req.height,
+                        proof_ops,
+                        ..Default::default()
+                    }
+                },
+                Err(err) => {
+                    ResponseQuery {
+                        code:      err.code(),
+                        codespace: err.codespace(),
+                        log:       err.to_string(),
+                        ..Default::default()
+                    }
+                },
+            },
+            "/simulate" => match self.do_simulate_raw(&req.data) {
                 Ok(res) => {
                     ResponseQuery {
                         code:  0,
-                        value: res.to_vec().into(),
+                        value: res.into(),
                         ..Default::default()
                     }
                 },
                 Err(err) => {
                     ResponseQuery {
-                        code:      1,
-                        codespace: "app".into(),
+                        code:      err.code(),
+                        codespace: err.codespace(),
                         log:       err.to_string(),
                         ..Default::default()
                     }
@@ -156,23 +318,97 @@ where
                 },
                 Err(err) => {
                     ResponseQuery {
-                        code:      1,
-                        codespace: "store".into(),
+                        code:      err.code(),
+                        codespace: err.codespace(),
                         log:       err.to_string(),
                         ..Default::default()
                     }
                 },
-            }
+            },
             unknown => {
+                let err = AppError::UnknownQueryPath {
+                    path: unknown.into(),
+                };
                 ResponseQuery {
-                    code:      1,
-                    codespace: "app".into(),
-                    log:       format!("unknown path `{unknown}`; must be `/app` or `/store`"),
+                    code:      err.code(),
+                    codespace: err.codespace(),
+                    log:       err.to_string(),
                     ..Default::default()
                 }
-            }
+            },
+        }
+    }
+
+    fn list_snapshots(&self) -> ResponseListSnapshots {
+        match self.do_list_snapshots() {
+            Ok(snapshots) => {
+                ResponseListSnapshots {
+                    snapshots: snapshots.into_iter().map(to_tm_snapshot).collect(),
+                }
+            },
+            Err(err) => panic!("failed to list snapshots: {err}"),
         }
+    }
+
+    fn offer_snapshot(&self, req: RequestOfferSnapshot) -> ResponseOfferSnapshot {
+        // reject the snapshot if its metadata doesn't match its hash. CometBFT
+        // will try another one.
+        let Some(snapshot) = req.snapshot.and_then(from_tm_snapshot) else {
+            return ResponseOfferSnapshot {
+                result: OfferSnapshotResult::Reject as i32,
+            };
+        };
+
+        let result = match self.do_offer_snapshot(snapshot, from_tm_hash(req.app_hash)) {
+            Ok(()) => OfferSnapshotResult::Accept,
+            Err(AppError::UnsupportedSnapshotFormat { .. }) => OfferSnapshotResult::RejectFormat,
+            Err(_) => OfferSnapshotResult::Abort,
+        };
+
+        ResponseOfferSnapshot {
+            result: result as i32,
+        }
+    }
 
+    fn load_snapshot_chunk(&self, req: RequestLoadSnapshotChunk) -> ResponseLoadSnapshotChunk {
+        // if the chunk can't be loaded, e.g. the snapshot has been pruned since
+        // it was listed, return an empty chunk, which the peer will reject
+        let chunk = self
+            .do_load_snapshot_chunk(req.height, req.format, req.chunk)
+            .unwrap_or_default();
+
+        ResponseLoadSnapshotChunk {
+            chunk: chunk.into(),
+        }
+    }
+
+    fn apply_snapshot_chunk(&self, req: RequestApplySnapshotChunk) -> ResponseApplySnapshotChunk {
+        match self.do_apply_snapshot_chunk(req.index, &req.chunk) {
+            Ok(_) => {
+                ResponseApplySnapshotChunk {
+                    result: ApplySnapshotChunkResult::Accept as i32,
+                    ..Default::default()
+                }
+            },
+            // the chunk is bad. fetch it again, from another peer
+            Err(AppError::SnapshotChunkMismatch { index }) => {
+                ResponseApplySnapshotChunk {
+                    result:         ApplySnapshotChunkResult::Retry as i32,
+                    refetch_chunks: vec![index],
+                    reject_senders: vec![req.sender],
+                }
+            },
+            // the chunks match the snapshot, but either they can't be decoded,
+            // or the resulting state isn't good. the snapshot itself is bad, so
+            // try another one.
+            Err(AppError::MalformedSnapshotChunk { .. } | AppError::AppHashMismatch { .. }) => {
+                ResponseApplySnapshotChunk {
+                    result: ApplySnapshotChunkResult::RejectSnapshot as i32,
+                    ..Default::default()
+                }
+            },
+            Err(err) => panic!("failed to apply snapshot chunk: {err}"),
+        }
     }
 }
 
@@ -192,17 +428,214 @@ fn from_tm_hash(bytes: Bytes) -> Hash {
     bytes.to_vec().try_into().expect("incorrect block hash length")
 }
 
-fn to_tm_tx_result(tx_result: AppResult<Vec<Event>>) -> ExecTxResult {
-    match tx_result {
+fn from_tm_extended_commit(commit: TmExtendedCommitInfo) -> AppResult<ExtendedCommitInfo> {
+    let round = commit.round.try_into().map_err(|_| {
+        AppError::invalid_vote_extensions(format!("negative commit round: {}", commit.round))
+    })?;
+
+    let votes = commit.votes.into_iter().map(from_tm_extended_vote).collect::<AppResult<_>>()?;
+
+    Ok(ExtendedCommitInfo {
+        round,
+        votes,
+    })
+}
+
+// every vote is kept, including those for nil or that weren't received, since
+// other validators need all of them to check the voting power
+fn from_tm_extended_vote(vote: ExtendedVoteInfo) -> AppResult<ExtendedVote> {
+    let validator = vote
+        .validator
+        .ok_or_else(|| AppError::invalid_vote_extensions("vote without a validator"))?;
+
+    let power = validator.power.try_into().map_err(|_| {
+        AppError::invalid_vote_extensions(format!("negative voting power: {}", validator.power))
+    })?;
+
+    let block_id_flag = match vote.block_id_flag {
+        flag if flag == TmBlockIdFlag::Absent as i32 => BlockIdFlag::Absent,
+        flag if flag == TmBlockIdFlag::Commit as i32 => BlockIdFlag::Commit,
+        flag if flag == TmBlockIdFlag::Nil as i32 => BlockIdFlag::Nil,
+        flag => {
+            return Err(AppError::invalid_vote_extensions(format!(
+                "unknown block id flag: {flag}"
+            )));
+        },
+    };
+
+    Ok(ExtendedVote {
+        validator:           validator.address.to_vec().into(),
+        power:               Uint64::new(power),
+        block_id_flag,
+        extension:           vote.vote_extension.to_vec().into(),
+        extension_signature: vote.extension_signature.to_vec().into(),
+    })
+}
+
+// the snapshot's metadata is the chunk hashes. it must match the snapshot hash,
+// which is what peers agree on when offering the snapshot.
+fn from_tm_snapshot(snapshot: TmSnapshot) -> Option<Snapshot> {
+    let chunk_hashes: Vec<Hash> = from_json_slice(&snapshot.metadata).ok()?;
+
+    let converted = Snapshot {
+        height: snapshot.height,
+        format: snapshot.format,
+        chunk_hashes,
+    };
+
+    if converted.chunk_hashes.len() != snapshot.chunks as usize
+        || converted.hash().as_ref() != snapshot.hash.as_ref()
+    {
+        return None;
+    }
+
+    Some(converted)
+}
+
+fn to_tm_snapshot(snapshot: Snapshot) -> TmSnapshot {
+    TmSnapshot {
+        height:   snapshot.height,
+        format:   snapshot.format,
+        chunks:   snapshot.chunk_hashes.len() as u32,
+        hash:     snapshot.hash().into_vec().into(),
+        metadata: to_json_vec(&snapshot.chunk_hashes).unwrap().into(),
+    }
+}
+
+fn from_tm_validator_update(update: TmValidatorUpdate) -> AppResult<ValidatorUpdate> {
+    let pubkey = match update.pub_key.and_then(|pk| pk.sum) {
+        Some(Sum::Ed25519(bytes)) => ValidatorPubKey::Ed25519(bytes.into()),
+        Some(Sum::Secp256k1(bytes)) => ValidatorPubKey::Secp256k1(bytes.into()),
+        None => return Err(AppError::invalid_validator_update("public key not found")),
+    };
+
+    let power = update.power.try_into().map_err(|_| {
+        AppError::invalid_validator_update(format!("negative voting power: {}", update.power))
+    })?;
+
+    Ok(ValidatorUpdate {
+        pubkey,
+        power: Uint64::new(power),
+    })
+}
+
+fn to_tm_validator_update(update: ValidatorUpdate) -> TmValidatorUpdate {
+    let sum = match update.pubkey {
+        ValidatorPubKey::Ed25519(bytes) => Sum::Ed25519(bytes.into()),
+        ValidatorPubKey::Secp256k1(bytes) => Sum::Secp256k1(bytes.into()),
+    };
+
+    TmValidatorUpdate {
+        pub_key: Some(PublicKey { sum: Some(sum) }),
+        power:   update.power.u64() as i64,
+    }
+}
+
+fn from_tm_consensus_params(params: TmConsensusParams) -> AppResult<ConsensusParams> {
+    let evidence = match params.evidence {
+        Some(evidence) => Some(EvidenceParams {
+            max_age_num_blocks: evidence.max_age_num_blocks,
+            max_age_duration:   from_tm_duration(evidence.max_age_duration.unwrap_or_default())?,
+            max_bytes:          evidence.max_bytes,
+        }),
+        None => None,
+    };
+
+    Ok(ConsensusParams {
+        block:     params.block.map(|block| BlockParams {
+            max_bytes: block.max_bytes,
+            max_gas:   block.max_gas,
+        }),
+        evidence,
+        validator: params.validator.map(|validator| ValidatorParams {
+            pub_key_types: validator.pub_key_types,
+        }),
+        abci:      params.abci.map(|abci| AbciParams {
+            vote_extensions_enable_height: abci.vote_extensions_enable_height,
+        }),
+    })
+}
+
+fn to_tm_consensus_params(params: ConsensusParams) -> TmConsensusParams {
+    TmConsensusParams {
+        block:     params.block.map(|block| TmBlockParams {
+            max_bytes: block.max_bytes,
+            max_gas:   block.max_gas,
+        }),
+        evidence:  params.evidence.map(|evidence| TmEvidenceParams {
+            max_age_num_blocks: evidence.max_age_num_blocks,
+            max_age_duration:   Some(to_tm_duration(evidence.max_age_duration)),
+            max_bytes:          evidence.max_bytes,
+        }),
+        validator: params.validator.map(|validator| TmValidatorParams {
+            pub_key_types: validator.pub_key_types,
+        }),
+        abci:      params.abci.map(|abci| TmAbciParams {
+            vote_extensions_enable_height: abci.vote_extensions_enable_height,
+        }),
+        ..Default::default()
+    }
+}
+
+fn from_tm_duration(duration: TmDuration) -> AppResult<Duration> {
+    // a negative duration can't be represented, and it would otherwise wrap
+    // around into a huge one when cast
+    let nanos = u64::try_from(duration.seconds)
+        .ok()
+        .zip(u64::try_from(duration.nanos).ok())
+        .and_then(|(seconds, nanos)| seconds.checked_mul(1_000_000_000)?.checked_add(nanos))
+        .ok_or_else(|| {
+            AppError::invalid_consensus_params(format!(
+                "duration {}s {}ns is negative or too large",
+                duration.seconds, duration.nanos
+            ))
+        })?;
+
+    Ok(Duration::from_nanos(nanos))
+}
+
+fn to_tm_duration(duration: Duration) -> TmDuration {
+    TmDuration {
+        seconds: duration.seconds() as i64,
+        nanos:   (duration.nanos() % 1_000_000_000) as i32,
+    }
+}
+
+fn to_tm_tx_result(outcome: TxOutcome) -> ExecTxResult {
+    match outcome.result {
         Ok(events) => ExecTxResult {
-            code:   0,
-            events: events.into_iter().map(to_tm_event).collect(),
+            code:       0,
+            events:     events.into_iter().map(to_tm_event).collect(),
+            gas_wanted: i64::try_from(outcome.gas_limit).unwrap_or(i64::MAX),
+            gas_used:   i64::try_from(outcome.gas_used).unwrap_or(i64::MAX),
             ..Default::default()
         },
         Err(err) => ExecTxResult {
-            code:      1,                // TODO: custom error code
-            codespace: "tx".to_string(), // TODO: custom error codespace
-            log:       err.to_string(),
+            code:       err.code(),
+            codespace:  err.codespace(),
+            log:        err.to_string(),
+            gas_wanted: i64::try_from(outcome.gas_limit).unwrap_or(i64::MAX),
+            gas_used:   i64::try_from(outcome.gas_used).unwrap_or(i64::MAX),
+            ..Default::default()
+        },
+    }
+}
+
+fn to_tm_check_tx_result(outcome: TxOutcome) -> ResponseCheckTx {
+    match outcome.result {
+        Ok(events) => ResponseCheckTx {
+            code:       0,
+            events:     events.into_iter().map(to_tm_event).collect(),
+            gas_wanted: i64::try_from(outcome.gas_limit).unwrap_or(i64::MAX),
+            gas_used:   i64::try_from(outcome.gas_used).unwrap_or(i64::MAX),
+            ..Default::default()
+        },
+        Err(err) => ResponseCheckTx {
+            code:       err.code(),
+            codespace:  err.codespace(),
+            log:        format!("failed to authenticate tx: {err}"),
+            gas_wanted: i64::try_from(outcome.gas_limit).unwrap_or(i64::MAX),
+            gas_used:   i64::try_from(outcome.gas_used).unwrap_or(i64::MAX),
             ..Default::default()
         },
     }
@@ -222,3 +655,34 @@ fn to_tm_attribute(attr: Attribute) -> TmAttribute {
         index: true,
     }
 }
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, grug_types::StdError};
+
+    #[test]
+    fn gas_is_reported_to_cometbft() {
+        // a tx that ran out of gas reports its limit and the gas it used
+        let outcome = TxOutcome {
+            gas_limit: 100_000,
+            gas_used:  100_001,
+            result:    Err(StdError::out_of_gas(100_000, 100_001, "wasm").into()),
+        };
+        let res = to_tm_tx_result(outcome);
+        assert_eq!(res.code, 13);
+        assert_eq!(res.gas_wanted, 100_000);
+        assert_eq!(res.gas_used, 100_001);
+
+        // amounts that don't fit in an i64 are capped instead of wrapping around
+        let outcome = TxOutcome {
+            gas_limit: u64::MAX,
+            gas_used:  u64::MAX,
+            result:    Ok(vec![]),
+        };
+        let res = to_tm_check_tx_result(outcome);
+        assert_eq!(res.gas_wanted, i64::MAX);
+        assert_eq!(res.gas_used, i64::MAX);
+    }
+}
@@ -0,0 +1,64 @@
+use {
+    crate::{new_clear_admin_event, new_update_admin_event, AppError, AppResult, ACCOUNTS},
+    grug_types::{Addr, Event, Storage},
+    tracing::{info, warn},
+};
+
+pub fn do_update_admin(
+    store:     &mut dyn Storage,
+    contract:  &Addr,
+    sender:    &Addr,
+    new_admin: &Addr,
+) -> AppResult<Vec<Event>> {
+    match _do_set_admin(store, contract, sender, Some(new_admin.clone())) {
+        Ok(old_admin) => {
+            info!(contract = contract.to_string(), "Updated admin");
+            Ok(vec![new_update_admin_event(contract, &old_admin, new_admin)])
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to update admin");
+            Err(err)
+        },
+    }
+}
+
+pub fn do_clear_admin(
+    store:    &mut dyn Storage,
+    contract: &Addr,
+    sender:   &Addr,
+) -> AppResult<Vec<Event>> {
+    match _do_set_admin(store, contract, sender, None) {
+        Ok(old_admin) => {
+            info!(contract = contract.to_string(), "Cleared admin");
+            Ok(vec![new_clear_admin_event(contract, &old_admin)])
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to clear admin");
+            Err(err)
+        },
+    }
+}
+
+// return the old admin, for purpose of emitting events
+fn _do_set_admin(
+    store:     &mut dyn Storage,
+    contract:  &Addr,
+    sender:    &Addr,
+    new_admin: Option<Addr>,
+) -> AppResult<Addr> {
+    let mut account = ACCOUNTS.load(store, contract)?;
+
+    // only the admin can update the admin
+    let Some(old_admin) = account.admin else {
+        return Err(AppError::AdminNotSet);
+    };
+    if sender != old_admin {
+        return Err(AppError::not_admin(sender.clone(), old_admin));
+    }
+
+    // save the new admin
+    account.admin = new_admin;
+    ACCOUNTS.save(store, contract, &account)?;
+
+    Ok(old_admin)
+}
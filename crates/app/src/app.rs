@@ -1,26 +1,76 @@
 use {
     crate::{
-        do_after_block, do_after_tx, do_before_block, do_before_tx, do_create_client, do_execute,
-        do_freeze_client, do_instantiate, do_migrate, do_set_config, do_transfer, do_update_client,
-        do_upload, query_account, query_accounts, query_balance, query_balances, query_code,
-        query_codes, query_info, query_supplies, query_supply, query_wasm_raw, query_wasm_smart,
-        AppError, AppResult, CacheStore, Db, SharedStore, Vm, CHAIN_ID, CONFIG,
-        LAST_FINALIZED_BLOCK,
+        apply_validator_updates, committed_vote_extensions, do_after_tx, do_before_tx, do_blocker,
+        do_clear_admin, do_create_client, do_execute, do_extend_vote, do_freeze_client,
+        do_handle_fee, do_init_validators, do_instantiate, do_migrate, do_prepare_proposal,
+        do_process_proposal, do_receive_vote_extensions, do_schedule_upgrade, do_set_config,
+        do_transfer, do_update_admin, do_update_client, do_update_consensus_params, do_upgrade,
+        do_upload, do_validator_updates, do_verify_vote_extension, encoded_tx_size, export_state,
+        import_state, process_cronjobs, query_account, query_accounts, query_balance,
+        query_balances, query_code, query_code_info, query_code_infos, query_codes, query_info,
+        query_next_cronjobs, query_supplies, query_supply, query_wasm_raw, query_wasm_smart,
+        save_config, truncate_txs, verify_extended_commit, AppError, AppResult, BlockHook,
+        CacheStore, Db, GasTracker, PruningStrategy, SharedStore, Snapshot, SnapshotRestore,
+        SnapshotStore, SnapshotTask, UpgradeHandler, Vm, ACCOUNTS, CHAIN_ID, CODES, CODE_INFOS,
+        CONFIG, CONSENSUS_PARAMS, CONTRACT_NAMESPACE, LAST_FINALIZED_BLOCK, NEXT_UPGRADE,
+        PENDING_CONSENSUS_PARAMS, PENDING_VALIDATOR_UPDATES, SNAPSHOT_FORMAT,
     },
     grug_types::{
-        from_json_slice, hash, to_json_vec, Addr, BlockInfo, Event, GenesisState, Hash, Message,
-        Permission, QueryRequest, QueryResponse, StdResult, Storage, Tx, GENESIS_SENDER,
+        concat, from_json_slice, hash, to_json_vec, Addr, Batch, Binary, BlockInfo, ConsensusParams,
+        Event, ExtendedCommitInfo, GasReport, GenesisState, Hash, Message, Permission,
+        QueryRequest, QueryResponse, SimulateRequest, SimulateResponse, StdResult, Storage, Tx,
+        Uint64, UpgradePlan, ValidatorUpdate, VerifyVoteExtensionMsg, VoteExtension,
+        GENESIS_SENDER,
     },
-    std::marker::PhantomData,
-    tracing::{debug, info},
+    std::{
+        collections::BTreeMap,
+        marker::PhantomData,
+        sync::{Arc, Mutex, MutexGuard, RwLock, RwLockWriteGuard},
+        thread,
+    },
+    tracing::{debug, info, warn},
 };
 
+/// The default maximum amount of gas a query may consume, if not specified when
+/// creating the app.
+pub const DEFAULT_QUERY_GAS_LIMIT: u64 = 100_000_000;
+
+/// The default maximum amount of gas a transaction may consume when simulated,
+/// if not specified when creating the app.
+pub const DEFAULT_SIMULATE_GAS_LIMIT: u64 = 100_000_000;
+
 /// The ABCI application.
 ///
 /// Must be clonable which is required by `tendermint-abci` library:
 /// https://github.com/informalsystems/tendermint-rs/blob/v0.34.0/abci/src/application.rs#L22-L25
 pub struct App<DB, VM> {
     db: DB,
+    /// State changes made during `CheckTx` calls since the last `Commit`.
+    ///
+    /// Each `CheckTx` call is performed on top of the last committed state plus
+    /// these changes, so that e.g. an account can submit multiple transactions
+    /// to the mempool within the same block, each with an increased sequence.
+    ///
+    /// This is wrapped in an `Arc` so that it's shared across clones of the app
+    /// (`tendermint-abci` clones the app for each of its ABCI connections).
+    check_tx_batch: Arc<RwLock<Batch>>,
+    /// Where state sync snapshots are created and served from, if enabled.
+    snapshot_store: Option<Arc<SnapshotStore>>,
+    /// The snapshot being created in the background, if any. Shared across
+    /// clones of the app, like `check_tx_batch`.
+    snapshot_task: Arc<Mutex<Option<SnapshotTask>>>,
+    /// The state sync snapshot being restored, if any. Shared across clones of
+    /// the app, like `check_tx_batch`.
+    snapshot_restore: Arc<Mutex<Option<SnapshotRestore>>>,
+    /// Which historical states to keep.
+    pruning: PruningStrategy,
+    /// The maximum amount of gas a query may consume.
+    query_gas_limit: u64,
+    /// The maximum amount of gas a transaction may consume when simulated.
+    simulate_gas_limit: u64,
+    /// Handlers of the upgrades that this binary knows how to apply, indexed
+    /// by upgrade names.
+    upgrade_handlers: BTreeMap<String, UpgradeHandler>,
     vm: PhantomData<VM>,
 }
 
@@ -28,9 +78,85 @@ impl<DB, VM> App<DB, VM> {
     pub fn new(db: DB) -> Self {
         Self {
             db,
+            check_tx_batch: Arc::new(RwLock::new(Batch::new())),
+            snapshot_store: None,
+            snapshot_task: Arc::new(Mutex::new(None)),
+            snapshot_restore: Arc::new(Mutex::new(None)),
+            pruning: PruningStrategy::Nothing,
+            query_gas_limit: DEFAULT_QUERY_GAS_LIMIT,
+            simulate_gas_limit: DEFAULT_SIMULATE_GAS_LIMIT,
+            upgrade_handlers: BTreeMap::new(),
             vm: PhantomData,
         }
     }
+
+    /// Prune historical states after each block according to the given
+    /// strategy. By default, nothing is pruned.
+    pub fn with_pruning(mut self, pruning: PruningStrategy) -> Self {
+        self.pruning = pruning;
+        self
+    }
+
+    /// Cap the amount of gas a query may consume, so that a contract that loops
+    /// forever in its `query` entry point can't keep the node busy. By default,
+    /// this is `DEFAULT_QUERY_GAS_LIMIT`.
+    pub fn with_query_gas_limit(mut self, query_gas_limit: u64) -> Self {
+        self.query_gas_limit = query_gas_limit;
+        self
+    }
+
+    /// Cap the amount of gas a transaction may consume when simulated. By
+    /// default, this is `DEFAULT_SIMULATE_GAS_LIMIT`.
+    pub fn with_simulate_gas_limit(mut self, simulate_gas_limit: u64) -> Self {
+        self.simulate_gas_limit = simulate_gas_limit;
+        self
+    }
+
+    /// Periodically create state sync snapshots in the given store, and serve
+    /// them to other nodes.
+    pub fn with_snapshot_store(mut self, snapshot_store: SnapshotStore) -> Self {
+        self.snapshot_store = Some(Arc::new(snapshot_store));
+        self
+    }
+
+    /// Register the handler of an upgrade. Once the owner schedules an upgrade
+    /// of this name, the handler is run at the upgrade height. Blocks at and
+    /// after the height are refused by binaries that haven't registered it.
+    pub fn with_upgrade_handler<F>(mut self, name: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(&mut dyn Storage, &BlockInfo) -> AppResult<()> + Send + Sync + 'static,
+    {
+        self.upgrade_handlers.insert(name.into(), Arc::new(handler));
+        self
+    }
+
+    fn check_tx_batch(&self) -> RwLockWriteGuard<Batch> {
+        self.check_tx_batch.write().unwrap_or_else(|err| {
+            panic!("poisoned lock: {err:?}")
+        })
+    }
+
+    fn snapshot_task(&self) -> MutexGuard<Option<SnapshotTask>> {
+        self.snapshot_task.lock().unwrap_or_else(|err| {
+            panic!("poisoned lock: {err:?}")
+        })
+    }
+
+    /// Block until the snapshot being created in the background, if any, has
+    /// been written to disk.
+    pub fn wait_for_snapshot(&self) {
+        if let Some(task) = self.snapshot_task().take() {
+            // the thread logs its own errors. if it panicked, the snapshot is
+            // left in a temporary directory, which is never served.
+            let _ = task.handle.join();
+        }
+    }
+
+    fn snapshot_restore(&self) -> MutexGuard<Option<SnapshotRestore>> {
+        self.snapshot_restore.lock().unwrap_or_else(|err| {
+            panic!("poisoned lock: {err:?}")
+        })
+    }
 }
 
 // For some reason, using a derive macro `#[derive(Clone)]` on App doesn't work.
@@ -44,6 +170,14 @@ where
     fn clone(&self) -> Self {
         Self {
             db: self.db.clone(),
+            check_tx_batch: Arc::clone(&self.check_tx_batch),
+            snapshot_store: self.snapshot_store.clone(),
+            snapshot_task: Arc::clone(&self.snapshot_task),
+            snapshot_restore: Arc::clone(&self.snapshot_restore),
+            pruning: self.pruning,
+            query_gas_limit: self.query_gas_limit,
+            simulate_gas_limit: self.simulate_gas_limit,
+            upgrade_handlers: self.upgrade_handlers.clone(),
             vm: PhantomData,
         }
     }
@@ -60,16 +194,25 @@ where
         chain_id: String,
         block: BlockInfo,
         raw_genesis_state: &[u8],
+        consensus_params: ConsensusParams,
+        validators: Vec<ValidatorUpdate>,
     ) -> AppResult<Hash> {
         let genesis_state = from_json_slice(raw_genesis_state)?;
-        self.do_init_chain(chain_id, block, genesis_state)
+        self.do_init_chain(chain_id, block, genesis_state, consensus_params, validators)
     }
 
+    /// Initialize the chain with the given genesis state. If the config names
+    /// a validator set contract, it's given the genesis validators after the
+    /// genesis messages have been executed (which should have instantiated it),
+    /// unless the genesis state includes exported state, which the contract's
+    /// storage is restored from instead.
     pub fn do_init_chain(
         &self,
         chain_id: String,
         block: BlockInfo,
         genesis_state: GenesisState,
+        consensus_params: ConsensusParams,
+        validators: Vec<ValidatorUpdate>,
     ) -> AppResult<Hash> {
         let mut cached = SharedStore::new(CacheStore::new(self.db.state_storage(None), None));
 
@@ -81,21 +224,59 @@ where
 
         // save the config and genesis block. some genesis messages may need it
         CHAIN_ID.save(&mut cached, &chain_id)?;
-        CONFIG.save(&mut cached, &genesis_state.config)?;
+        save_config(&mut cached, &genesis_state.config)?;
+        CONSENSUS_PARAMS.save(&mut cached, &consensus_params)?;
         LAST_FINALIZED_BLOCK.save(&mut cached, &block)?;
 
+        // keep track of CometBFT's validator set, to verify vote extensions
+        apply_validator_updates(&mut cached, &validators)?;
+
+        // restore the state exported from another chain, if any
+        let imported = genesis_state.exported.is_some();
+        if let Some(exported) = genesis_state.exported {
+            info!(
+                chain_id = exported.chain_id,
+                height = exported.height.u64(),
+                codes = exported.codes.len(),
+                accounts = exported.accounts.len(),
+                "Importing exported state"
+            );
+            import_state(Box::new(cached.clone()), exported)?;
+        }
+
         // loop through genesis messages and execute each one.
         // it's expected that genesis messages should all successfully execute.
         // if anyone fails, it's fatal error and we abort the genesis.
         // the developer should examine the error, fix it, and retry.
         for (idx, msg) in genesis_state.msgs.into_iter().enumerate() {
             info!(idx, "Processing genesis message");
-            process_msg::<VM>(Box::new(cached.clone()), &block, &GENESIS_SENDER, msg)?;
+            process_msg::<VM>(
+                Box::new(cached.clone()),
+                &block,
+                GasTracker::new_limitless(),
+                &[],
+                &GENESIS_SENDER,
+                msg,
+            )?;
+        }
+
+        // hand the genesis validators to the validator set contract. if its
+        // storage was imported, it already holds the validator set, so it's
+        // not initialized again.
+        let validator_set = genesis_state.config.validator_set.as_ref().filter(|_| !imported);
+        if let Some(contract) = validator_set {
+            do_init_validators::<VM>(
+                Box::new(cached.clone()),
+                &block,
+                GasTracker::new_limitless(),
+                contract,
+                &validators,
+            )?;
         }
 
         // persist the state changes to disk
-        let (_, pending) = cached.disassemble().disassemble();
-        let (version, root_hash) = self.db.flush_and_commit(pending)?;
+        let (_, removed_ranges, pending) = cached.disassemble().disassemble_with_ranges();
+        let (version, root_hash) = self.db.flush_and_commit(removed_ranges, pending)?;
 
         // BaseStore version should be 0
         debug_assert_eq!(version, 0);
@@ -116,29 +297,57 @@ where
         Ok(root_hash.unwrap())
     }
 
-    #[allow(clippy::type_complexity)]
     pub fn do_finalize_block_raw(
         &self,
         block: BlockInfo,
         raw_txs: Vec<impl AsRef<[u8]>>,
-    ) -> AppResult<(Hash, Vec<Event>, Vec<AppResult<Vec<Event>>>)> {
+    ) -> AppResult<BlockOutcome> {
+        let cfg = CONFIG.load(&self.db.state_storage(None))?;
+        let mut raw_txs = raw_txs.into_iter();
+
+        // if vote extensions are enabled, the block's first tx is the votes for
+        // the previous height, put there in PrepareProposal. they have already
+        // been verified in ProcessProposal.
+        let vote_extensions = if cfg.vote_extension_handler.is_some() {
+            let raw_tx = raw_txs.next().ok_or(AppError::VoteExtensionsNotFound)?;
+            Some(committed_vote_extensions(from_json_slice(raw_tx)?))
+        } else {
+            None
+        };
+
         let txs = raw_txs
-            .into_iter()
             .map(|raw_tx| {
                 let tx_hash = hash(raw_tx.as_ref());
                 let tx = from_json_slice(raw_tx.as_ref())?;
                 Ok((tx_hash, tx))
             })
             .collect::<StdResult<Vec<_>>>()?;
-        self.do_finalize_block(block, txs)
+
+        let has_vote_extensions = vote_extensions.is_some();
+        let mut outcome =
+            self.do_finalize_block(block, vote_extensions.unwrap_or_default(), txs)?;
+
+        // CometBFT expects a result for each tx in the block, including the
+        // vote extensions
+        if has_vote_extensions {
+            outcome.tx_results.insert(0, TxOutcome {
+                gas_limit: 0,
+                gas_used:  0,
+                result:    Ok(vec![]),
+            });
+        }
+
+        Ok(outcome)
     }
 
-    #[allow(clippy::type_complexity)]
+    /// Execute a block. If the config names a vote extension handler contract,
+    /// it's first given the vote extensions from the previous height.
     pub fn do_finalize_block(
         &self,
         block: BlockInfo,
+        vote_extensions: Vec<VoteExtension>,
         txs: Vec<(Hash, Tx)>,
-    ) -> AppResult<(Hash, Vec<Event>, Vec<AppResult<Vec<Event>>>)> {
+    ) -> AppResult<BlockOutcome> {
         let mut cached = SharedStore::new(CacheStore::new(self.db.state_storage(None), None));
         let mut events = vec![];
         let mut tx_results = vec![];
@@ -156,28 +365,110 @@ where
             ));
         }
 
+        // apply the validator updates that take effect at this height, so that
+        // the set matches the one that signs this block
+        if let Some(updates) = PENDING_VALIDATOR_UPDATES.may_load(&cached, block.height.u64())? {
+            PENDING_VALIDATOR_UPDATES.remove(&mut cached, block.height.u64());
+            apply_validator_updates(&mut cached, &updates)?;
+        }
+
+        // if an upgrade is due, apply it before anything else. if this binary
+        // doesn't know how to, it must be replaced by one that does, so we halt.
+        if let Some(plan) = NEXT_UPGRADE.may_load(&cached)? {
+            if block.height >= plan.height {
+                let Some(handler) = self.upgrade_handlers.get(&plan.name) else {
+                    warn!(
+                        name = plan.name,
+                        height = plan.height.u64(),
+                        info = plan.info,
+                        "Upgrade needed! Halting the chain"
+                    );
+                    return Err(AppError::upgrade_needed(plan.name, plan.height.u64()));
+                };
+
+                events.extend(do_upgrade(&mut cached, &block, plan, handler)?);
+            }
+        }
+
+        // deliver the vote extensions from the previous height
+        if let Some(contract) = &cfg.vote_extension_handler {
+            events.extend(do_receive_vote_extensions::<VM>(
+                Box::new(cached.share()),
+                &block,
+                GasTracker::new_limitless(),
+                contract,
+                &vote_extensions,
+            )?);
+        }
+
         // call begin blockers
+        // whether an error halts the chain depends on the blocker's failure
+        // policy in the config.
         for (idx, contract) in cfg.begin_blockers.iter().enumerate() {
             debug!(idx, contract = contract.to_string(), "Calling begin blocker");
-            // NOTE: error in begin blocker is considered fatal error. a begin
-            // blocker erroring causes the chain to halt.
-            // TODO: we need to think whether this is the desired behavior
-            events.extend(do_before_block::<VM>(Box::new(cached.share()), &block, contract)?);
+            events.extend(do_blocker::<VM>(
+                Box::new(cached.share()),
+                &block,
+                &cfg,
+                contract,
+                BlockHook::BeforeBlock,
+            )?);
         }
 
+        // call cronjobs that are due
+        events.extend(process_cronjobs::<VM>(Box::new(cached.share()), &block, &cfg)?);
+
         // process transactions one-by-one
         for (idx, (tx_hash, tx)) in txs.into_iter().enumerate() {
             debug!(idx, ?tx_hash, "Processing transaction");
-            tx_results.push(process_tx::<_, VM>(cached.share(), &block, tx));
+            let gas_tracker = GasTracker::new_limited(tx.gas_limit.u64());
+            tx_results.push(process_tx::<_, VM>(cached.share(), &block, gas_tracker, tx, false));
         }
 
         // call end blockers
         for (idx, contract) in cfg.end_blockers.iter().enumerate() {
             debug!(idx, contract = contract.to_string(), "Calling end blocker");
-            // NOTE: error in end blocker is considered fatal error. an end
-            // blocker erroring causes the chain to halt.
-            // TODO: we need to think whether this is the desired behavior
-            events.extend(do_after_block::<VM>(Box::new(cached.share()), &block, contract)?);
+            events.extend(do_blocker::<VM>(
+                Box::new(cached.share()),
+                &block,
+                &cfg,
+                contract,
+                BlockHook::AfterBlock,
+            )?);
+        }
+
+        // collect changes to the validator set, if a contract manages it.
+        // unlike blockers, this can't be skipped: if it fails, we can't know
+        // what the validator set should be, so we halt.
+        let mut validator_updates = vec![];
+        if let Some(contract) = &cfg.validator_set {
+            let (updates, new_events) = do_validator_updates::<VM>(
+                Box::new(cached.share()),
+                &block,
+                GasTracker::new_limitless(),
+                contract,
+            )?;
+            validator_updates = updates;
+            events.extend(new_events);
+        }
+
+        // CometBFT applies the updates two blocks later
+        if !validator_updates.is_empty() {
+            PENDING_VALIDATOR_UPDATES.save(
+                &mut cached,
+                block.height.u64() + 2,
+                &validator_updates,
+            )?;
+        }
+
+        // pass on consensus param updates scheduled by the owner, and record
+        // the params that will be in effect once CometBFT applies them
+        let consensus_param_updates = PENDING_CONSENSUS_PARAMS.may_load(&cached)?;
+        if let Some(updates) = &consensus_param_updates {
+            PENDING_CONSENSUS_PARAMS.remove(&mut cached);
+            let mut params = CONSENSUS_PARAMS.may_load(&cached)?.unwrap_or_default();
+            params.merge(updates.clone());
+            CONSENSUS_PARAMS.save(&mut cached, &params)?;
         }
 
         // save the last committed block
@@ -189,8 +480,8 @@ where
 
         // flush the state changes to the DB, but keep it in memory, not persist
         // to disk yet. it will be done in the ABCI `Commit` call.
-        let (_, batch) = cached.disassemble().disassemble();
-        let (version, root_hash) = self.db.flush_but_not_commit(batch)?;
+        let (_, removed_ranges, batch) = cached.disassemble().disassemble_with_ranges();
+        let (version, root_hash) = self.db.flush_but_not_commit(removed_ranges, batch)?;
 
         // block height should match the DB version
         debug_assert_eq!(block.height.u64(), version);
@@ -205,18 +496,438 @@ where
             "Finalized block"
         );
 
-        Ok((root_hash.unwrap(), events, tx_results))
+        Ok(BlockOutcome {
+            app_hash: root_hash.unwrap(),
+            events,
+            tx_results,
+            validator_updates,
+            consensus_param_updates,
+        })
     }
 
+    /// Persist the state changes of the last finalized block. Then, create a
+    /// snapshot and prune old states, if due.
+    ///
+    /// Creating a snapshot requires scanning the entire state, so it's done in
+    /// a background thread, so as not to hold up consensus. If the previous
+    /// snapshot is still being created when the next one is due, the next one
+    /// is skipped. States aren't pruned past the one being snapshotted.
+    ///
+    /// Return the oldest height whose state is retained, which is reported to
+    /// CometBFT as the height below which blocks may be deleted. Zero if no
+    /// state has been pruned, or if states are kept at an interval, as the
+    /// genesis state is one of them.
     // TODO: we need to think about what to do if the flush fails here?
-    pub fn do_commit(&self) -> AppResult<()> {
+    pub fn do_commit(&self) -> AppResult<u64> {
         self.db.commit()?;
 
-        info!(height = self.db.latest_version(), "Committed state");
+        // the CheckTx state changes were made on top of the previously committed
+        // state, which is now outdated. discard them.
+        self.check_tx_batch().clear();
+
+        let height = self.db.latest_version().unwrap_or(0);
+
+        info!(height, "Committed state");
+
+        // creating a snapshot doesn't affect consensus, so if it fails, we log
+        // the error and move on.
+        let mut snapshot_task = self.snapshot_task();
+        if snapshot_task.as_ref().is_some_and(|task| task.handle.is_finished()) {
+            *snapshot_task = None;
+        }
+
+        if let Some(snapshot_store) = &self.snapshot_store {
+            if snapshot_store.is_due(height) {
+                if let Some(task) = snapshot_task.as_ref() {
+                    warn!(
+                        height,
+                        snapshotting = task.height,
+                        "Previous snapshot not done yet, skipping"
+                    );
+                } else {
+                    let snapshot_store = Arc::clone(snapshot_store);
+                    let storage = self.db.state_storage(Some(height));
+                    let handle = thread::spawn(move || {
+                        if let Err(err) = snapshot_store.create(height, &storage) {
+                            warn!(height, err = err.to_string(), "Failed to create snapshot");
+                        }
+                    });
+                    *snapshot_task = Some(SnapshotTask {
+                        height,
+                        handle,
+                    });
+                }
+            }
+        }
+
+        // neither does pruning. if it fails, it's attempted again next block.
+        // the state being snapshotted must be kept until the snapshot is done.
+        let snapshotting = snapshot_task.as_ref().map(|task| task.height);
+        drop(snapshot_task);
+
+        let retain_height = self.pruning.retain_height(height).map(|retain_height| {
+            snapshotting.map_or(retain_height, |snapshotting| retain_height.min(snapshotting))
+        });
+        if let Some(retain_height) = retain_height {
+            if self.db.oldest_version().map_or(true, |oldest| oldest < retain_height) {
+                match self.db.prune(retain_height, self.pruning.keep_every()) {
+                    Ok(()) => info!(retain_height, "Pruned state"),
+                    Err(err) => {
+                        warn!(retain_height, err = err.to_string(), "Failed to prune state");
+                    },
+                }
+            }
+        }
+
+        // states kept at an interval go back to genesis, and so must blocks,
+        // from which the state at any height can be replayed
+        if self.pruning.keep_every().is_some() {
+            return Ok(0);
+        }
+
+        Ok(self.db.oldest_version().unwrap_or(0))
+    }
+
+    /// Return the state sync snapshots this node can serve, the most recent
+    /// first. Empty if snapshots aren't enabled.
+    pub fn do_list_snapshots(&self) -> AppResult<Vec<Snapshot>> {
+        match &self.snapshot_store {
+            Some(snapshot_store) => snapshot_store.list(),
+            None => Ok(vec![]),
+        }
+    }
+
+    pub fn do_load_snapshot_chunk(
+        &self,
+        height: u64,
+        format: u32,
+        index: u32,
+    ) -> AppResult<Vec<u8>> {
+        let Some(snapshot_store) = &self.snapshot_store else {
+            return Err(AppError::Snapshot("snapshots are not enabled".to_string()));
+        };
+
+        snapshot_store.load_chunk(height, format, index)
+    }
+
+    /// Start restoring a snapshot offered by a peer. `app_hash` is the app hash
+    /// at the snapshot's height, which CometBFT has verified with the light
+    /// client, and which the restored state must match.
+    ///
+    /// The DB must be empty, i.e. the node must not have processed any block.
+    /// If another snapshot was being restored, it's abandoned.
+    pub fn do_offer_snapshot(&self, snapshot: Snapshot, app_hash: Hash) -> AppResult<()> {
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Err(AppError::unsupported_snapshot_format(snapshot.format));
+        }
+
+        if self.db.latest_version().is_some() {
+            let msg = "can't restore a snapshot into a non-empty DB";
+            return Err(AppError::Snapshot(msg.to_string()));
+        }
+
+        info!(
+            height = snapshot.height,
+            chunks = snapshot.chunk_hashes.len(),
+            "Restoring snapshot"
+        );
+
+        *self.snapshot_restore() = Some(SnapshotRestore::new(snapshot, app_hash));
 
         Ok(())
     }
 
+    /// Apply a chunk of the snapshot being restored. Once all the chunks have
+    /// been applied, the Merkle tree is rebuilt from the restored state, and its
+    /// root hash is checked against the trusted app hash before the state is
+    /// committed. Return whether the snapshot has been completely restored.
+    pub fn do_apply_snapshot_chunk(&self, index: u32, chunk: &[u8]) -> AppResult<bool> {
+        let mut snapshot_restore = self.snapshot_restore();
+
+        let Some(restore) = snapshot_restore.as_mut() else {
+            return Err(AppError::Snapshot("no snapshot is being restored".to_string()));
+        };
+
+        restore.apply_chunk(index, chunk)?;
+
+        if !restore.is_complete() {
+            return Ok(false);
+        }
+
+        // all chunks have been received. whether it succeeds or not, the
+        // restore is over, so we take it out.
+        let restore = snapshot_restore.take().unwrap();
+        let height = restore.snapshot.height;
+        let root_hash = self.db.restore(height, restore.batch)?;
+
+        if root_hash.as_ref() != Some(&restore.app_hash) {
+            return Err(AppError::app_hash_mismatch(restore.app_hash, root_hash));
+        }
+
+        self.db.commit()?;
+
+        info!(height, app_hash = restore.app_hash.to_string(), "Restored snapshot");
+
+        Ok(true)
+    }
+
+    /// Decide the transactions to include in a block proposed by this node.
+    ///
+    /// If the config names a proposal handler contract, it's given the txs
+    /// reaped from the mempool to reorder, drop, or add to. If there isn't one,
+    /// or if it fails, the txs are proposed as is. Either way, txs in excess of
+    /// `max_tx_bytes` are dropped.
+    ///
+    /// If the config names a vote extension handler contract, the votes for
+    /// the previous height, as CometBFT gives them to the proposer, are put in
+    /// front of the txs, so that other validators can verify them, and the
+    /// extensions can be delivered to the handler in FinalizeBlock. The
+    /// proposal handler is given the extensions of the votes for the block.
+    pub fn do_prepare_proposal_raw(
+        &self,
+        block: BlockInfo,
+        raw_txs: Vec<impl AsRef<[u8]>>,
+        max_tx_bytes: u64,
+        extended_commit: ExtendedCommitInfo,
+    ) -> AppResult<Vec<Binary>> {
+        let mut max_tx_bytes = max_tx_bytes;
+        let txs = raw_txs
+            .into_iter()
+            .map(|raw_tx| raw_tx.as_ref().to_vec().into())
+            .collect::<Vec<Binary>>();
+
+        // the handlers can't make state changes. we give them a cached store,
+        // so that if they attempt to, the changes are discarded.
+        let store = SharedStore::new(CacheStore::new(self.db.state_storage(None), None));
+        let cfg = CONFIG.load(&store)?;
+
+        let mut vote_extensions = vec![];
+        let vote_extensions_tx = if cfg.vote_extension_handler.is_some() {
+            let tx = Binary::from(to_json_vec(&extended_commit)?);
+            max_tx_bytes = max_tx_bytes.saturating_sub(encoded_tx_size(&tx));
+            vote_extensions = committed_vote_extensions(extended_commit);
+            Some(tx)
+        } else {
+            None
+        };
+
+        let mut txs = if let Some(contract) = &cfg.proposal_handler {
+            // a failing handler shouldn't prevent this node from proposing, so
+            // we fall back to proposing the txs as is. other validators may of
+            // course reject the proposal in ProcessProposal.
+            let res = do_prepare_proposal::<VM>(
+                Box::new(store.share()),
+                &block,
+                GasTracker::new_limited(cfg.handler_gas_limit),
+                contract,
+                txs.clone(),
+                max_tx_bytes,
+                vote_extensions,
+            );
+            res.unwrap_or_else(|_| truncate_txs(txs, max_tx_bytes))
+        } else {
+            truncate_txs(txs, max_tx_bytes)
+        };
+
+        if let Some(tx) = vote_extensions_tx {
+            txs.insert(0, tx);
+        }
+
+        Ok(txs)
+    }
+
+    /// Decide whether to accept a block proposed by another validator. The
+    /// proposal is accepted if the config doesn't name a proposal handler
+    /// contract, or if the handler returns successfully.
+    ///
+    /// If the config names a vote extension handler contract, the block must
+    /// start with the votes for the previous height. They must be signed by
+    /// validators holding more than 2/3 of the voting power, with the powers
+    /// of the validator set that signed the previous block (see
+    /// [`verify_extended_commit`]), and each extension must pass the handler's
+    /// verification. The proposal handler is given the verified extensions.
+    ///
+    /// The handlers must be deterministic: all honest validators must come to
+    /// the same decision for the same proposal.
+    pub fn do_process_proposal_raw(
+        &self,
+        block: BlockInfo,
+        raw_txs: Vec<impl AsRef<[u8]>>,
+    ) -> AppResult<()> {
+        let store = SharedStore::new(CacheStore::new(self.db.state_storage(None), None));
+        let cfg = CONFIG.load(&store)?;
+        let mut raw_txs = raw_txs.into_iter();
+
+        // CometBFT doesn't verify the votes the proposer includes, so we check
+        // them against the validator set, the same way CometBFT verifies its
+        // own commit, before letting the handler verify the extensions
+        let mut vote_extensions = vec![];
+        if let Some(contract) = &cfg.vote_extension_handler {
+            let raw_tx = raw_txs.next().ok_or(AppError::VoteExtensionsNotFound)?;
+            let extended_commit = from_json_slice(raw_tx)?;
+            vote_extensions = verify_extended_commit(&store, &block, &extended_commit)?;
+
+            for vote_extension in &vote_extensions {
+                do_verify_vote_extension::<VM>(
+                    Box::new(store.share()),
+                    &block,
+                    GasTracker::new_limited(cfg.handler_gas_limit),
+                    contract,
+                    VerifyVoteExtensionMsg {
+                        validator: vote_extension.validator.clone(),
+                        extension: vote_extension.extension.clone(),
+                    },
+                )?;
+            }
+        }
+
+        let Some(contract) = &cfg.proposal_handler else {
+            return Ok(());
+        };
+
+        let txs = raw_txs.map(|raw_tx| raw_tx.as_ref().to_vec().into()).collect();
+
+        do_process_proposal::<VM>(
+            Box::new(store),
+            &block,
+            GasTracker::new_limited(cfg.handler_gas_limit),
+            contract,
+            txs,
+            vote_extensions,
+        )
+    }
+
+    /// Produce the data to be attached to this node's precommit vote, by
+    /// calling the vote extension handler contract. Empty if there isn't one.
+    pub fn do_extend_vote(&self) -> AppResult<Binary> {
+        // the handler is given the state as of the last finalized block, like
+        // a query, since the block being voted on isn't finalized yet
+        let store = CacheStore::new(self.db.state_storage(None), None);
+        let cfg = CONFIG.load(&store)?;
+
+        let Some(contract) = &cfg.vote_extension_handler else {
+            return Ok(Binary::empty());
+        };
+
+        let block = LAST_FINALIZED_BLOCK.load(&store)?;
+        let gas_tracker = GasTracker::new_limited(cfg.handler_gas_limit);
+
+        do_extend_vote::<VM>(Box::new(store), &block, gas_tracker, contract)
+    }
+
+    /// Verify the data another validator attached to its precommit vote, by
+    /// calling the vote extension handler contract. Always accepted if there
+    /// isn't one.
+    pub fn do_verify_vote_extension(&self, msg: VerifyVoteExtensionMsg) -> AppResult<()> {
+        let store = CacheStore::new(self.db.state_storage(None), None);
+        let cfg = CONFIG.load(&store)?;
+
+        let Some(contract) = &cfg.vote_extension_handler else {
+            return Ok(());
+        };
+
+        let block = LAST_FINALIZED_BLOCK.load(&store)?;
+
+        do_verify_vote_extension::<VM>(
+            Box::new(store),
+            &block,
+            GasTracker::new_limited(cfg.handler_gas_limit),
+            contract,
+            msg,
+        )
+    }
+
+    pub fn do_check_tx_raw(&self, raw_tx: &[u8]) -> AppResult<TxOutcome> {
+        let tx = from_json_slice(raw_tx)?;
+        Ok(self.do_check_tx(tx))
+    }
+
+    /// Authenticate a transaction, by calling the sender account's `before_tx`
+    /// method, and make sure the sender can pay the fee for it, by calling the
+    /// taxman's `handle_fee` method as if the tx used up its gas limit, to
+    /// decide whether it should be accepted into the mempool. The messages are
+    /// not executed.
+    pub fn do_check_tx(&self, tx: Tx) -> TxOutcome {
+        // hold the lock for the entire duration, so that concurrent CheckTx
+        // calls are performed one at a time
+        let mut check_tx_batch = self.check_tx_batch();
+
+        let cached = SharedStore::new(CacheStore::new(
+            self.db.state_storage(None),
+            Some(check_tx_batch.clone()),
+        ));
+
+        let gas_limit = tx.gas_limit.u64();
+        let gas_tracker = GasTracker::new_limited(gas_limit);
+        let result = _do_check_tx::<_, VM>(cached.share(), gas_tracker.clone(), &tx);
+
+        // if the tx is accepted, keep its state changes (e.g. the sender's
+        // sequence increment and fee payment) for subsequent CheckTx calls
+        if result.is_ok() {
+            let (_, batch) = cached.disassemble().disassemble();
+            *check_tx_batch = batch;
+        }
+
+        TxOutcome {
+            gas_used: gas_tracker.used(),
+            gas_limit,
+            result,
+        }
+    }
+
+    pub fn do_simulate_raw(&self, raw_req: &[u8]) -> AppResult<Vec<u8>> {
+        let req: SimulateRequest = from_json_slice(raw_req)?;
+        let res = self.do_simulate(req.tx, req.with_written_keys)?;
+        Ok(to_json_vec(&res)?)
+    }
+
+    /// Execute a transaction in simulation mode, without persisting any state
+    /// changes. Return the events emitted, the amount of gas consumed, and
+    /// optionally the keys that the transaction has written to or deleted, and
+    /// the ranges it has removed.
+    ///
+    /// The transaction's own gas limit isn't enforced, since finding out how
+    /// much gas it needs is typically the purpose of the simulation. Instead,
+    /// it's capped by the node's simulation gas limit, so that anyone calling
+    /// this via an ABCI query can't keep the node busy forever. A transaction
+    /// that exceeds it fails with an out of gas error.
+    pub fn do_simulate(&self, tx: Tx, with_written_keys: bool) -> AppResult<SimulateResponse> {
+        // simulate on top of the last committed state plus the state changes
+        // from CheckTx calls, so that the simulation takes into account txs
+        // that are pending in the mempool (e.g. their sequence increments).
+        let base = SharedStore::new(CacheStore::new(
+            self.db.state_storage(None),
+            Some(self.check_tx_batch().clone()),
+        ));
+        let cached = SharedStore::new(CacheStore::new(base, None));
+
+        // the tx will be included in a future block, which we don't know yet,
+        // so we use the last finalized block instead
+        let block = LAST_FINALIZED_BLOCK.load(&cached)?;
+        let gas_tracker = GasTracker::new_limited(self.simulate_gas_limit);
+        let outcome = process_tx::<_, VM>(cached.share(), &block, gas_tracker, tx, true);
+
+        let (written_keys, removed_ranges) = if with_written_keys {
+            let cache = cached.disassemble();
+            let removed_ranges = cache
+                .removed_ranges()
+                .into_iter()
+                .map(|(min, max)| (min.map(Binary::from), max.map(Binary::from)))
+                .collect();
+            let (_, batch) = cache.disassemble();
+            (Some(batch.into_keys().map(Binary::from).collect()), Some(removed_ranges))
+        } else {
+            (None, None)
+        };
+
+        Ok(SimulateResponse {
+            gas_used: outcome.gas_used,
+            result: outcome.result.into(),
+            written_keys,
+            removed_ranges,
+        })
+    }
+
     // returns (last_block_height, last_block_app_hash)
     // note that we are returning the app hash, not the block hash
     pub fn do_info(&self) -> AppResult<(u64, Hash)> {
@@ -238,19 +949,77 @@ where
         Ok((version, root_hash))
     }
 
-    pub fn do_query_app_raw(&self, raw_req: &[u8], height: u64, prove: bool) -> AppResult<Vec<u8>> {
-        let req = from_json_slice(raw_req)?;
-        let res = self.do_query_app(req, height, prove)?;
-        Ok(to_json_vec(&res)?)
-    }
+    /// Export the state at the given height as a genesis state, from which a
+    /// new chain can be started. Height being zero means the latest height.
+    pub fn do_export(&self, height: u64) -> AppResult<GenesisState> {
+        let version = if height == 0 {
+            None
+        } else {
+            Some(height)
+        };
 
-    pub fn do_query_app(&self, req: QueryRequest, height: u64, prove: bool) -> AppResult<QueryResponse> {
-        if prove {
-            // we can't do merkle proof for smart queries. only raw store query
-            // can be merkle proved.
-            return Err(AppError::ProofNotSupported);
+        self.ensure_not_pruned(height)?;
+
+        let store = self.db.state_storage(version);
+        let config = CONFIG.load(&store)?;
+        if let Some(plan) = NEXT_UPGRADE.may_load(&store)? {
+            warn!(name = plan.name, height = plan.height.u64(), "Scheduled upgrade isn't exported");
         }
+        let exported = export_state(Box::new(store))?;
+
+        info!(
+            height = exported.height.u64(),
+            codes = exported.codes.len(),
+            accounts = exported.accounts.len(),
+            "Exported state"
+        );
 
+        Ok(GenesisState {
+            config,
+            msgs: vec![],
+            exported: Some(exported),
+        })
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn do_query_app_raw(
+        &self,
+        raw_req: &[u8],
+        height: u64,
+        prove: bool,
+    ) -> AppResult<(Vec<u8>, Option<Vec<(Vec<u8>, Vec<u8>)>>)> {
+        let req = from_json_slice(raw_req)?;
+        let (res, proofs) = self.do_query_app(req, height, prove)?;
+        let proofs = proofs
+            .map(|proofs| {
+                proofs
+                    .into_iter()
+                    .map(|(key, proof)| Ok((key, to_json_vec(&proof)?)))
+                    .collect::<StdResult<Vec<_>>>()
+            })
+            .transpose()?;
+        Ok((to_json_vec(&res)?, proofs))
+    }
+
+    /// Performs a query at the given height. Returns two values:
+    /// - the query response;
+    /// - the Merkle proofs of the storage keys that the response is derived
+    ///   from, as (key, proof) pairs; `None` if proofs are not requested
+    ///   (`prove` is false).
+    ///
+    /// The query may consume up to the node's query gas limit. A batch query
+    /// shares the limit across all the queries in it.
+    ///
+    /// Only queries whose responses are read directly from the storage can be
+    /// proved. These are `Info`, `Account`, `Code`, `CodeInfo`, and `WasmRaw`,
+    /// as well as `Multi` queries consisting only of these.
+    #[allow(clippy::type_complexity)]
+    pub fn do_query_app(
+        &self,
+        req: QueryRequest,
+        height: u64,
+        prove: bool,
+    ) -> AppResult<(QueryResponse, Option<Vec<(Vec<u8>, DB::Proof)>>)> {
         let version = if height == 0 {
             // height being zero means unspecified (protobuf doesn't have a null
             // type) in which case we use the latest version.
@@ -259,11 +1028,35 @@ where
             Some(height)
         };
 
+        self.ensure_not_pruned(height)?;
+
+        let proofs = if prove {
+            // we can't do merkle proof for smart queries. only queries that
+            // directly read from the store can be merkle proved.
+            let Some(keys) = storage_keys_for_query(&req) else {
+                return Err(AppError::ProofNotSupported);
+            };
+
+            let proofs = keys
+                .into_iter()
+                .map(|key| {
+                    let proof = self.db.prove(&key, version)?;
+                    Ok((key, proof))
+                })
+                .collect::<AppResult<Vec<_>>>()?;
+
+            Some(proofs)
+        } else {
+            None
+        };
+
         // use the state storage at the given version to perform the query
         let store = self.db.state_storage(version);
         let block = LAST_FINALIZED_BLOCK.load(&store)?;
+        let gas_tracker = GasTracker::new_limited(self.query_gas_limit);
+        let res = process_query::<VM>(Box::new(store), &block, gas_tracker, req)?;
 
-        process_query::<VM>(Box::new(store), &block, req)
+        Ok((res, proofs))
     }
 
     /// Performs a raw query of the app's underlying key-value store.
@@ -285,6 +1078,8 @@ where
             Some(height)
         };
 
+        self.ensure_not_pruned(height)?;
+
         let proof = if prove {
             Some(to_json_vec(&self.db.prove(key, version)?)?)
         } else {
@@ -295,28 +1090,226 @@ where
 
         Ok((value, proof))
     }
+
+    /// Return an error if the state at the given height has been pruned.
+    /// Height being zero means the latest height, which is never pruned.
+    fn ensure_not_pruned(&self, height: u64) -> AppResult<()> {
+        if let Some(oldest_version) = self.db.oldest_version() {
+            if height != 0
+                && height < oldest_version
+                && !self.pruning.is_kept_beyond_retain_height(height)
+            {
+                return Err(AppError::height_pruned(height, oldest_version));
+            }
+        }
+
+        Ok(())
+    }
 }
 
-fn process_tx<S, VM>(store: S, block: &BlockInfo, tx: Tx) -> AppResult<Vec<Event>>
+/// Return the storage keys from which the response to the query is derived,
+/// so that Merkle proofs can be generated for them. `None` if the query can't
+/// be proved, i.e. it involves calling a contract.
+fn storage_keys_for_query(req: &QueryRequest) -> Option<Vec<Vec<u8>>> {
+    match req {
+        QueryRequest::Info {} => Some(vec![
+            CHAIN_ID.storage_key().to_vec(),
+            CONFIG.storage_key().to_vec(),
+            LAST_FINALIZED_BLOCK.storage_key().to_vec(),
+            NEXT_UPGRADE.storage_key().to_vec(),
+            CONSENSUS_PARAMS.storage_key().to_vec(),
+        ]),
+        QueryRequest::Account {
+            address,
+        } => Some(vec![ACCOUNTS.storage_key(address)]),
+        QueryRequest::Code {
+            hash,
+        } => Some(vec![CODES.storage_key(hash)]),
+        QueryRequest::CodeInfo {
+            hash,
+        } => Some(vec![CODE_INFOS.storage_key(hash)]),
+        QueryRequest::WasmRaw {
+            contract,
+            key,
+        } => Some(vec![concat(&concat(CONTRACT_NAMESPACE, contract), key)]),
+        // a batch query can be proved if all the queries in it can be proved
+        QueryRequest::Multi(reqs) => reqs
+            .iter()
+            .map(storage_keys_for_query)
+            .collect::<Option<Vec<_>>>()
+            .map(|keys| keys.into_iter().flatten().collect()),
+        _ => None,
+    }
+}
+
+/// The outcome of finalizing a block.
+pub struct BlockOutcome {
+    /// The app hash after the block's state changes have been applied.
+    pub app_hash:                Hash,
+    /// Events emitted outside of transactions, e.g. by begin and end blockers.
+    pub events:                  Vec<Event>,
+    /// The outcome of each transaction in the block, in order.
+    pub tx_results:              Vec<TxOutcome>,
+    /// Changes to the validator set returned by the validator set contract.
+    pub validator_updates:       Vec<ValidatorUpdate>,
+    /// Changes to the consensus params scheduled by the owner, if any.
+    pub consensus_param_updates: Option<ConsensusParams>,
+}
+
+/// The outcome of processing a transaction.
+pub struct TxOutcome {
+    /// The gas limit requested by the transaction.
+    pub gas_limit: u64,
+    /// The amount of gas consumed by the transaction.
+    pub gas_used:  u64,
+    /// Events emitted, or the error if the transaction failed.
+    pub result:    AppResult<Vec<Event>>,
+}
+
+fn _do_check_tx<S, VM>(store: S, gas_tracker: GasTracker, tx: &Tx) -> AppResult<Vec<Event>>
 where
     S: Storage + Clone + 'static,
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    let mut events = vec![];
+    // the tx will be included in a future block, which we don't know yet, so
+    // we use the last finalized block instead
+    let block = LAST_FINALIZED_BLOCK.load(&store)?;
+
+    // the earliest block the tx can be included in is the next one. reject the
+    // tx if it will have expired by then.
+    let next_block = BlockInfo {
+        height: block.height + Uint64::new(1),
+        ..block.clone()
+    };
+    if tx.is_expired(&next_block) {
+        return Err(AppError::tx_expired(tx, &next_block));
+    }
+
+    let mut events =
+        do_before_tx::<VM>(Box::new(store.clone()), &block, gas_tracker, tx, false)?;
+
+    // reject the tx if the sender can't pay the fee. otherwise, the tx would
+    // fail without the sender being charged anything, so it could be spammed
+    // for free. we don't know how much gas the tx will use, so we charge for
+    // the most it can use.
+    let report = GasReport {
+        sender: tx.sender.clone(),
+        limit:  tx.gas_limit,
+        used:   tx.gas_limit,
+    };
+    events.extend(do_handle_fee::<VM>(Box::new(store), &block, &report)?);
+
+    Ok(events)
+}
+
+/// Process a transaction under the given gas tracker, which is shared across
+/// all VM instances created while processing it, including those for
+/// submessages and queries made by contracts.
+fn process_tx<S, VM>(
+    store:       S,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          Tx,
+    simulate:    bool,
+) -> TxOutcome
+where
+    S: Storage + Clone + 'static,
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let gas_limit = tx.gas_limit.u64();
+
+    // reject the tx if it has expired. the sender isn't authenticated yet, so
+    // we don't charge any fee, and no state change is made.
+    if tx.is_expired(block) {
+        return TxOutcome {
+            gas_used: 0,
+            gas_limit,
+            result: Err(AppError::tx_expired(&tx, block)),
+        };
+    }
 
     // create cached store for this tx
     let cached = SharedStore::new(CacheStore::new(store, None));
 
-    // call the sender account's `before_tx` method.
-    // if this fails, abort, discard uncommitted state changes.
-    events.extend(do_before_tx::<VM>(Box::new(cached.share()), block, &tx)?);
+    // call the sender account's `before_tx` method to authenticate the tx.
+    // if this fails, abort, discard uncommitted state changes. the taxman isn't
+    // called: the tx may not have been signed by the sender, in which case
+    // charging the sender a fee would be wrong.
+    let mut events = match do_before_tx::<VM>(
+        Box::new(cached.share()),
+        block,
+        gas_tracker.clone(),
+        &tx,
+        simulate,
+    ) {
+        Ok(events) => events,
+        Err(err) => {
+            return TxOutcome {
+                gas_used: gas_tracker.used(),
+                gas_limit,
+                result: Err(err),
+            };
+        },
+    };
 
-    // update the account state. as long as authentication succeeds, regardless
-    // of whether the message are successful, we update account state. if auth
-    // fails, we don't update account state.
+    // update the account state. as long as authentication succeeds, the state
+    // changes from `before_tx` are kept, regardless of whether the messages
+    // and the fee handling succeed, so that the tx can't be replayed.
     cached.write_access().commit();
 
+    let sender = tx.sender.clone();
+    let mut result = _process_tx::<_, VM>(cached.share(), block, gas_tracker.clone(), tx, simulate)
+        .map(|msg_events| {
+            events.extend(msg_events);
+            events
+        });
+
+    // call the taxman's `handle_fee` method, regardless of whether the messages
+    // succeeded. if this fails, including if the taxman runs out of gas, the tx
+    // fails: abort, discard the state changes from messages and the fee handling.
+    let report = GasReport {
+        sender,
+        limit: Uint64::new(gas_limit),
+        used: Uint64::new(gas_tracker.used()),
+    };
+    match do_handle_fee::<VM>(Box::new(cached.share()), block, &report) {
+        Ok(fee_events) => {
+            cached.write_access().commit();
+            if let Ok(events) = &mut result {
+                events.extend(fee_events);
+            }
+        },
+        Err(err) => {
+            result = Err(err);
+        },
+    }
+
+    TxOutcome {
+        gas_used: report.used.u64(),
+        gas_limit,
+        result,
+    }
+}
+
+fn _process_tx<S, VM>(
+    store:       S,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          Tx,
+    simulate:    bool,
+) -> AppResult<Vec<Event>>
+where
+    S: Storage + Clone + 'static,
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let mut events = vec![];
+
+    // create cached store for the messages
+    let cached = SharedStore::new(CacheStore::new(store, None));
+
     // now that the tx is authenticated, we loop through the messages and
     // execute them one by one.
     // if any one of the msgs fails, the entire tx fails; abort, discard
@@ -324,13 +1317,25 @@ where
     // persisted)
     for (idx, msg) in tx.msgs.iter().enumerate() {
         debug!(idx, "Processing message");
-        events.extend(process_msg::<VM>(Box::new(cached.share()), block, &tx.sender, msg.clone())?);
+        events.extend(process_msg::<VM>(
+            Box::new(cached.share()),
+            block,
+            gas_tracker.clone(),
+            &[],
+            &tx.sender,
+            msg.clone(),
+        )?);
     }
 
     // call the sender account's `after_tx` method.
     // if this fails, abort, discard uncommitted state changes from messages.
-    // state changes from `before_tx` are always kept.
-    events.extend(do_after_tx::<VM>(Box::new(cached.share()), block, &tx)?);
+    events.extend(do_after_tx::<VM>(
+        Box::new(cached.share()),
+        block,
+        gas_tracker,
+        &tx,
+        simulate,
+    )?);
 
     // all messages succeeded. commit the state changes
     cached.write_access().commit();
@@ -339,10 +1344,12 @@ where
 }
 
 pub fn process_msg<VM>(
-    mut store: Box<dyn Storage>,
-    block: &BlockInfo,
-    sender: &Addr,
-    msg: Message,
+    mut store:   Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    sender:      &Addr,
+    msg:         Message,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
@@ -352,51 +1359,136 @@ where
         Message::SetConfig {
             new_cfg,
         } => do_set_config(&mut store, sender, &new_cfg),
+        Message::UpdateConsensusParams {
+            updates,
+        } => do_update_consensus_params(&mut store, sender, updates),
+        Message::ScheduleUpgrade {
+            height,
+            name,
+            info,
+        } => do_schedule_upgrade(
+            &mut store,
+            block,
+            sender,
+            UpgradePlan {
+                height,
+                name,
+                info,
+            },
+        ),
         Message::Transfer {
             to,
             coins,
-        } => do_transfer::<VM>(store, block, sender.clone(), to, coins, true),
+        } => do_transfer::<VM>(
+            store,
+            block,
+            gas_tracker,
+            call_stack,
+            sender.clone(),
+            to,
+            coins,
+            true,
+        ),
         Message::Upload {
             code,
-        } => do_upload(&mut store, sender, code.into()),
+            instantiate_permission,
+        } => do_upload::<VM>(&mut store, block, sender, code.into(), instantiate_permission),
         Message::Instantiate {
             code_hash,
             msg,
             salt,
             funds,
             admin,
-        } => do_instantiate::<VM>(store, block, sender, code_hash, &msg, salt, funds, admin),
+            non_reentrant,
+        } => do_instantiate::<VM>(
+            store,
+            block,
+            gas_tracker,
+            call_stack,
+            sender,
+            code_hash,
+            &msg,
+            salt,
+            funds,
+            admin,
+            non_reentrant,
+        ),
         Message::Execute {
             contract,
             msg,
             funds,
-        } => do_execute::<VM>(store, block, &contract, sender, &msg, funds),
+        } => {
+            do_execute::<VM>(store, block, gas_tracker, call_stack, &contract, sender, &msg, funds)
+        },
         Message::Migrate {
             contract,
             new_code_hash,
             msg,
-        } => do_migrate::<VM>(store, block, &contract, sender, new_code_hash, &msg),
+        } => do_migrate::<VM>(
+            store,
+            block,
+            gas_tracker,
+            call_stack,
+            &contract,
+            sender,
+            new_code_hash,
+            &msg,
+        ),
+        Message::UpdateAdmin {
+            contract,
+            new_admin,
+        } => do_update_admin(&mut store, &contract, sender, &new_admin),
+        Message::ClearAdmin {
+            contract,
+        } => do_clear_admin(&mut store, &contract, sender),
         Message::CreateClient {
             code_hash,
             client_state,
             consensus_state,
             salt,
-        } => do_create_client::<VM>(store, block, sender, code_hash, client_state, consensus_state, salt),
+        } => do_create_client::<VM>(
+            store,
+            block,
+            gas_tracker,
+            call_stack,
+            sender,
+            code_hash,
+            client_state,
+            consensus_state,
+            salt,
+        ),
         Message::UpdateClient {
             client_id,
             header,
-        } => do_update_client::<VM>(store, block, sender, &client_id, header),
+        } => do_update_client::<VM>(
+            store,
+            block,
+            gas_tracker,
+            call_stack,
+            sender,
+            &client_id,
+            header,
+        ),
         Message::FreezeClient {
             client_id,
             misbehavior,
-        } => do_freeze_client::<VM>(store, block, sender, &client_id, misbehavior),
+        } => do_freeze_client::<VM>(
+            store,
+            block,
+            gas_tracker,
+            call_stack,
+            sender,
+            &client_id,
+            misbehavior,
+        ),
     }
 }
 
 pub fn process_query<VM>(
-    store: Box<dyn Storage>,
-    block: &BlockInfo,
-    req: QueryRequest,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    req:         QueryRequest,
 ) -> AppResult<QueryResponse>
 where
     VM: Vm + 'static,
@@ -407,19 +1499,22 @@ where
         QueryRequest::Balance {
             address,
             denom,
-        } => query_balance::<VM>(store, block, address, denom).map(QueryResponse::Balance),
+        } => query_balance::<VM>(store, block, gas_tracker, address, denom)
+            .map(QueryResponse::Balance),
         QueryRequest::Balances {
             address,
             start_after,
             limit,
-        } => query_balances::<VM>(store, block, address, start_after, limit).map(QueryResponse::Balances),
+        } => query_balances::<VM>(store, block, gas_tracker, address, start_after, limit)
+            .map(QueryResponse::Balances),
         QueryRequest::Supply {
             denom,
-        } => query_supply::<VM>(store, block, denom).map(QueryResponse::Supply),
+        } => query_supply::<VM>(store, block, gas_tracker, denom).map(QueryResponse::Supply),
         QueryRequest::Supplies {
             start_after,
             limit,
-        } => query_supplies::<VM>(store, block, start_after, limit).map(QueryResponse::Supplies),
+        } => query_supplies::<VM>(store, block, gas_tracker, start_after, limit)
+            .map(QueryResponse::Supplies),
         QueryRequest::Code {
             hash,
         } => query_code(&store, hash).map(QueryResponse::Code),
@@ -427,6 +1522,14 @@ where
             start_after,
             limit,
         } => query_codes(&store, start_after, limit).map(QueryResponse::Codes),
+        QueryRequest::CodeInfo {
+            hash,
+        } => query_code_info(&store, hash).map(QueryResponse::CodeInfo),
+        QueryRequest::CodeInfos {
+            start_after,
+            limit,
+            uploader,
+        } => query_code_infos(&store, start_after, limit, uploader).map(QueryResponse::CodeInfos),
         QueryRequest::Account {
             address,
         } => query_account(&store, address).map(QueryResponse::Account),
@@ -434,6 +1537,10 @@ where
             start_after,
             limit,
         } => query_accounts(&store, start_after, limit).map(QueryResponse::Accounts),
+        QueryRequest::NextCronjobs {
+            start_after,
+            limit,
+        } => query_next_cronjobs(&store, start_after, limit).map(QueryResponse::NextCronjobs),
         QueryRequest::WasmRaw {
             contract,
             key,
@@ -441,7 +1548,13 @@ where
         QueryRequest::WasmSmart {
             contract,
             msg,
-        } => query_wasm_smart::<VM>(store, block, contract, msg).map(QueryResponse::WasmSmart),
+        } => query_wasm_smart::<VM>(store, block, gas_tracker, contract, msg)
+            .map(QueryResponse::WasmSmart),
+        QueryRequest::Multi(reqs) => reqs
+            .into_iter()
+            .map(|req| process_query::<VM>(store.clone(), block, gas_tracker.clone(), req))
+            .collect::<AppResult<Vec<_>>>()
+            .map(QueryResponse::Multi),
     }
 }
 
@@ -1,7 +1,8 @@
 use {
     crate::{
         create_vm_instance, handle_submessages, load_program, new_after_tx_event,
-        new_before_tx_event, AppError, AppResult, Vm, ACCOUNTS, CHAIN_ID,
+        new_before_tx_event, AppError, AppResult, ContractResult, GasTracker, Vm, ACCOUNTS,
+        CHAIN_ID,
     },
     grug_types::{BlockInfo, Context, Event, Storage, Tx},
     tracing::{debug, warn},
@@ -9,12 +10,18 @@ use {
 
 // --------------------------------- before tx ---------------------------------
 
-pub fn do_before_tx<VM>(store: Box<dyn Storage>, block: &BlockInfo, tx: &Tx) -> AppResult<Vec<Event>>
+pub fn do_before_tx<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    simulate:    bool,
+) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_before_tx::<VM>(store, block, tx) {
+    match _do_before_tx::<VM>(store, block, gas_tracker, tx, simulate) {
         Ok(events) => {
             // TODO: add txhash here?
             debug!(sender = tx.sender.to_string(), "Called before transaction hook");
@@ -27,7 +34,13 @@ where
     }
 }
 
-fn _do_before_tx<VM>(store: Box<dyn Storage>, block: &BlockInfo, tx: &Tx) -> AppResult<Vec<Event>>
+fn _do_before_tx<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    simulate:    bool,
+) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
@@ -36,7 +49,13 @@ where
     let account = ACCOUNTS.load(&store, &tx.sender)?;
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), &tx.sender, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        &tx.sender,
+        program,
+    )?;
 
     // call `before_tx` entry point
     let ctx = Context {
@@ -47,25 +66,38 @@ where
         contract:        tx.sender.clone(),
         sender:          None,
         funds:           None,
-        simulate:        Some(false),
+        simulate:        Some(simulate),
     };
-    let resp = instance.call_before_tx(&ctx, tx)?.into_std_result()?;
+    let resp = instance.call_before_tx(&ctx, tx)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_before_tx_event(&ctx.contract, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        &[],
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
 
 // --------------------------------- after tx ----------------------------------
 
-pub fn do_after_tx<VM>(store: Box<dyn Storage>, block: &BlockInfo, tx: &Tx) -> AppResult<Vec<Event>>
+pub fn do_after_tx<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    simulate:    bool,
+) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_after_tx::<VM>(store, block, tx) {
+    match _do_after_tx::<VM>(store, block, gas_tracker, tx, simulate) {
         Ok(events) => {
             // TODO: add txhash here?
             debug!(sender = tx.sender.to_string(), "Called after transaction hook");
@@ -78,7 +110,13 @@ where
     }
 }
 
-fn _do_after_tx<VM>(store: Box<dyn Storage>, block: &BlockInfo, tx: &Tx) -> AppResult<Vec<Event>>
+fn _do_after_tx<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    tx:          &Tx,
+    simulate:    bool,
+) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
@@ -87,7 +125,13 @@ where
     let account = ACCOUNTS.load(&store, &tx.sender)?;
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), &tx.sender, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        &tx.sender,
+        program,
+    )?;
 
     // call `after_tx` entry point
     let ctx = Context {
@@ -98,13 +142,20 @@ where
         contract:        tx.sender.clone(),
         sender:          None,
         funds:           None,
-        simulate:        Some(false),
+        simulate:        Some(simulate),
     };
-    let resp = instance.call_after_tx(&ctx, tx)?.into_std_result()?;
+    let resp = instance.call_after_tx(&ctx, tx)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_after_tx_event(&ctx.contract, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        &[],
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
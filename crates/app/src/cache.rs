@@ -1,6 +1,6 @@
 use {
     grug_types::{Batch, Op, Order, Record, Storage},
-    std::{cmp::Ordering, iter, iter::Peekable, mem, ops::Bound},
+    std::{cmp::Ordering, collections::BTreeMap, iter, iter::Peekable, mem, ops::Bound},
 };
 
 /// Adapted from cw-multi-test:
@@ -9,27 +9,105 @@ use {
 pub struct CacheStore<S: Clone> {
     base: S,
     pub(crate) pending: Batch,
+    /// Ranges removed from the underlying store, as a mapping from the minimum
+    /// bound to the maximum bound. Overlapping ranges are merged, so that the
+    /// one a key may fall in can be looked up by its minimum bound. Records in
+    /// the pending batch take precedence over them, as pending records in a
+    /// range are dropped at the time it's removed.
+    removed_ranges: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
 }
 
+/// The minimum (inclusive) and maximum (exclusive) bounds of a range that was
+/// removed from a cached store. See `Storage::remove_range`.
+pub type RemovedRange = (Option<Vec<u8>>, Option<Vec<u8>>);
+
 impl<S: Clone> CacheStore<S> {
     /// Create a new cached store with an optional write batch.
     pub fn new(base: S, pending: Option<Batch>) -> Self {
         Self {
             base,
             pending: pending.unwrap_or_default(),
+            removed_ranges: BTreeMap::new(),
+        }
+    }
+
+    /// Consume self, do not flush, just return the underlying store, the
+    /// removed ranges, and the pending ops. The ranges are to be removed from
+    /// the underlying store before the ops are applied.
+    pub fn disassemble_with_ranges(self) -> (S, Vec<RemovedRange>, Batch) {
+        let removed_ranges = self.removed_ranges();
+        (self.base, removed_ranges, self.pending)
+    }
+
+    /// The ranges removed from the underlying store that are yet to be flushed,
+    /// with overlapping ones merged.
+    pub fn removed_ranges(&self) -> Vec<RemovedRange> {
+        self.removed_ranges
+            .iter()
+            .map(|(min, max)| (Some(min.clone()), max.clone()))
+            .collect()
+    }
+
+    fn is_removed(&self, key: &[u8]) -> bool {
+        // the only range the key may fall in is the last one starting at or
+        // before it
+        self.removed_ranges
+            .range::<[u8], _>((Bound::Unbounded, Bound::Included(key)))
+            .next_back()
+            .is_some_and(|(_, max)| max.as_deref().map_or(true, |max| key < max))
+    }
+
+    fn insert_removed_range(&mut self, mut min: Vec<u8>, mut max: Option<Vec<u8>>) {
+        // merge with the range before it, if they overlap or touch
+        if let Some((start, end)) = self.removed_ranges.range(..=min.clone()).next_back() {
+            if end.as_ref().map_or(true, |end| *end >= min) {
+                min.clone_from(start);
+                max = max_bound(end.clone(), max);
+            }
         }
+
+        // merge with the ranges starting within it
+        let starts = self
+            .removed_ranges
+            .range(min.clone()..)
+            .take_while(|(start, _)| max.as_ref().map_or(true, |max| *start <= max))
+            .map(|(start, _)| start.clone())
+            .collect::<Vec<_>>();
+        for start in starts {
+            let end = self.removed_ranges.remove(&start).unwrap();
+            max = max_bound(end, max);
+        }
+
+        self.removed_ranges.insert(min, max);
     }
+}
 
+impl<S: Storage + Clone> CacheStore<S> {
     /// Comsume self, do not flush, just return the underlying store and the
     /// pending ops.
+    ///
+    /// A batch can't express range removals, so the records in the underlying
+    /// store that fall in a removed range are added to the batch as deletions.
     pub fn disassemble(self) -> (S, Batch) {
-        (self.base, self.pending)
+        let (base, removed_ranges, mut pending) = self.disassemble_with_ranges();
+
+        for (min, max) in &removed_ranges {
+            for (key, _) in base.scan(min.as_deref(), max.as_deref(), Order::Ascending) {
+                // a pending op on a key in the range must have been made after
+                // the range was removed, so it takes precedence
+                pending.entry(key).or_insert(Op::Delete);
+            }
+        }
+
+        (base, pending)
     }
-}
 
-impl<S: Storage + Clone> CacheStore<S> {
     /// Flush pending ops to the underlying store.
     pub fn commit(&mut self) {
+        for (min, max) in mem::take(&mut self.removed_ranges) {
+            self.base.remove_range(Some(&min), max.as_deref());
+        }
+
         let pending = mem::take(&mut self.pending);
         self.base.flush(pending);
     }
@@ -37,7 +115,7 @@ impl<S: Storage + Clone> CacheStore<S> {
     /// Consume self, flush pending ops to the underlying store, return the
     /// underlying store.
     pub fn consume(mut self) -> S {
-        self.base.flush(self.pending);
+        self.commit();
         self.base
     }
 }
@@ -47,6 +125,7 @@ impl<S: Storage + Clone> Storage for CacheStore<S> {
         match self.pending.get(key) {
             Some(Op::Insert(value)) => Some(value.clone()),
             Some(Op::Delete) => None,
+            None if self.is_removed(key) => None,
             None => self.base.read(key),
         }
     }
@@ -63,7 +142,7 @@ impl<S: Storage + Clone> Storage for CacheStore<S> {
             }
         }
 
-        let base = self.base.scan(min, max, order);
+        let base = self.base.scan(min, max, order).filter(|(key, _)| !self.is_removed(key));
 
         let min = min.map_or(Bound::Unbounded, |bytes| Bound::Included(bytes.to_vec()));
         let max = max.map_or(Bound::Unbounded, |bytes| Bound::Excluded(bytes.to_vec()));
@@ -84,6 +163,26 @@ impl<S: Storage + Clone> Storage for CacheStore<S> {
         self.pending.insert(key.to_vec(), Op::Delete);
     }
 
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                return;
+            }
+        }
+
+        // a range with nothing in it isn't recorded. this way, the number of
+        // ranges, which every read has to check, is bounded by the number of
+        // records removed, each of which the caller is charged for.
+        if self.scan(min, max, Order::Ascending).next().is_none() {
+            return;
+        }
+
+        // pending ops in the range are simply dropped. the ones on records in
+        // the underlying store are covered by the range being recorded.
+        self.pending.retain(|key, _| !in_range(key, min, max));
+        self.insert_removed_range(min.unwrap_or_default().to_vec(), max.map(|max| max.to_vec()));
+    }
+
     fn flush(&mut self, batch: Batch) {
         // if we do a.extend(b), while a and b have common keys, the values in b
         // are chosen. this is exactly what we want.
@@ -91,6 +190,15 @@ impl<S: Storage + Clone> Storage for CacheStore<S> {
     }
 }
 
+fn in_range(key: &[u8], min: Option<&[u8]>, max: Option<&[u8]>) -> bool {
+    min.map_or(true, |min| key >= min) && max.map_or(true, |max| key < max)
+}
+
+/// Return the greater of two maximum bounds, where `None` means unbounded.
+fn max_bound(a: Option<Vec<u8>>, b: Option<Vec<u8>>) -> Option<Vec<u8>> {
+    Some(a?.max(b?))
+}
+
 struct Merged<'a, B, P>
 where
     B: Iterator<Item = Record>,
@@ -207,5 +315,64 @@ mod tests {
         assert_eq!(collect_records(&cached, Order::Descending), merged);
     }
 
+    // base    : 1 2 _ 4 5 6 7 _
+    // pending :   D P _ _ P D 8
+    // removed :     [3     7)
+    // pending :           P
+    // merged  : 1 _ _ _ _ 6 _ 8
+    #[test]
+    fn removing_range_works() {
+        let (mut cached, _) = make_test_case();
+        cached.remove_range(Some(&[3]), Some(&[7]));
+        cached.write(&[6], &[6]);
+
+        let merged = vec![(vec![1], vec![1]), (vec![6], vec![6]), (vec![8], vec![8])];
+        assert_eq!(collect_records(&cached, Order::Ascending), merged);
+        assert_eq!(cached.read(&[4]), None);
+        assert_eq!(cached.read(&[6]), Some(vec![6]));
+
+        // flushing to the base store and disassembling into a batch should
+        // both result in the same records
+        let (base, batch) = cached.clone().disassemble();
+        let mut flushed = CacheStore::new(base, None);
+        flushed.flush(batch);
+        assert_eq!(collect_records(&flushed.consume(), Order::Ascending), merged);
+        assert_eq!(collect_records(&cached.consume(), Order::Ascending), merged);
+    }
+
+    #[test]
+    fn removing_empty_range_is_not_recorded() {
+        let (mut cached, merged) = make_test_case();
+        cached.remove_range(Some(&[9]), None);
+        cached.remove_range(Some(&[7]), Some(&[8]));
+        assert!(cached.removed_ranges.is_empty());
+        assert_eq!(collect_records(&cached, Order::Ascending), merged);
+    }
+
+    // base    : 1 2 _ 4 5 6 7 _
+    // pending :   D P _ _ P D 8
+    // removed :       [4 5)
+    // removed :         [5 6)
+    // removed : [1 2)
+    // merged  : _ _ 3 _ _ 6 _ 8
+    #[test]
+    fn removing_overlapping_ranges_merges_them() {
+        let (mut cached, _) = make_test_case();
+        cached.remove_range(Some(&[4]), Some(&[5]));
+        cached.remove_range(Some(&[5]), Some(&[6]));
+        cached.remove_range(Some(&[1]), Some(&[2]));
+        assert_eq!(
+            cached.removed_ranges,
+            BTreeMap::from([(vec![1], Some(vec![2])), (vec![4], Some(vec![6]))])
+        );
+
+        let merged = vec![(vec![3], vec![3]), (vec![6], vec![255]), (vec![8], vec![8])];
+        assert_eq!(collect_records(&cached, Order::Ascending), merged);
+
+        cached.remove_range(Some(&[1]), None);
+        assert_eq!(cached.removed_ranges, BTreeMap::from([(vec![1], None)]));
+        assert_eq!(collect_records(&cached, Order::Ascending), vec![]);
+    }
+
     // TODO: add fuzz test
 }
@@ -1,8 +1,8 @@
 use {
     crate::{
-        create_vm_instance, handle_submessages, has_permission, load_program,
+        assert_not_reentered, create_vm_instance, handle_submessages, has_permission, load_program,
         new_client_misbehavior_event, new_create_client_event, new_update_client_event, AppError,
-        AppResult, Vm, ACCOUNTS, CHAIN_ID, CONFIG,
+        AppResult, ContractResult, GasTracker, Vm, ACCOUNTS, CHAIN_ID, CONFIG,
     },
     grug_types::{
         Account, Addr, Binary, BlockInfo, Context, Event, Hash, IbcClientUpdateMsg, Json, Storage,
@@ -12,9 +12,12 @@ use {
 
 // ------------------------------- create client -------------------------------
 
+#[allow(clippy::too_many_arguments)]
 pub fn do_create_client<VM>(
     store:           Box<dyn Storage>,
     block:           &BlockInfo,
+    gas_tracker:     GasTracker,
+    call_stack:      &[Addr],
     sender:          &Addr,
     code_hash:       Hash,
     client_state:    Json,
@@ -25,7 +28,17 @@ where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_create_client::<VM>(store, block, sender, code_hash, client_state, consensus_state, salt) {
+    match _do_create_client::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        sender,
+        code_hash,
+        client_state,
+        consensus_state,
+        salt,
+    ) {
         Ok((events, address)) => {
             info!(address = address.to_string(), "Create IBC client");
             Ok(events)
@@ -37,9 +50,12 @@ where
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn _do_create_client<VM>(
     mut store:       Box<dyn Storage>,
     block:           &BlockInfo,
+    gas_tracker:     GasTracker,
+    call_stack:      &[Addr],
     sender:          &Addr,
     code_hash:       Hash,
     client_state:    Json,
@@ -75,11 +91,18 @@ where
         code_hash,
         // IBC clients are not upgradable
         admin: None,
+        non_reentrant: false,
     };
     ACCOUNTS.save(&mut store, &address, &account)?;
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), &address, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        &address,
+        program,
+    )?;
 
     // call `ibc_client_create` entry point
     let ctx = Context {
@@ -92,11 +115,20 @@ where
         funds:           None,
         simulate:        None,
     };
-    let resp = instance.call_ibc_client_create(&ctx, &client_state, &consensus_state)?.into_std_result()?;
+    let resp = instance
+        .call_ibc_client_create(&ctx, &client_state, &consensus_state)?
+        .into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_create_client_event(&ctx.contract, &account.code_hash, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, sender, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        sender,
+        resp.submsgs,
+    )?);
 
     Ok((events, ctx.contract))
 }
@@ -104,17 +136,27 @@ where
 // ------------------------------- update client -------------------------------
 
 pub fn do_update_client<VM>(
-    store:     Box<dyn Storage>,
-    block:     &BlockInfo,
-    sender:    &Addr,
-    client_id: &Addr,
-    header:    Json,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    sender:      &Addr,
+    client_id:   &Addr,
+    header:      Json,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_update_client::<VM>(store, block, sender, client_id, header) {
+    match _do_update_client::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        sender,
+        client_id,
+        header,
+    ) {
         Ok(events) => {
             info!(client_id = client_id.to_string(), "Update IBC client");
             Ok(events)
@@ -127,11 +169,13 @@ where
 }
 
 fn _do_update_client<VM>(
-    store:     Box<dyn Storage>,
-    block:     &BlockInfo,
-    sender:    &Addr,
-    client_id: &Addr,
-    header:    Json,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    sender:      &Addr,
+    client_id:   &Addr,
+    header:      Json,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
@@ -139,9 +183,16 @@ where
 {
     let chain_id = CHAIN_ID.load(&store)?;
     let account = ACCOUNTS.load(&store, client_id)?;
+    assert_not_reentered(call_stack, client_id, &account)?;
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), client_id, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        client_id,
+        program,
+    )?;
 
     // call `ibc_client_update` entry point
     let ctx = Context {
@@ -157,11 +208,18 @@ where
     let msg = IbcClientUpdateMsg::Update {
         header,
     };
-    let resp = instance.call_ibc_client_update(&ctx, &msg)?.into_std_result()?;
+    let resp = instance.call_ibc_client_update(&ctx, &msg)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_update_client_event(&ctx.contract, &account.code_hash, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
@@ -171,6 +229,8 @@ where
 pub fn do_freeze_client<VM>(
     store:       Box<dyn Storage>,
     block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
     sender:      &Addr,
     client_id:   &Addr,
     misbehavior: Json,
@@ -179,7 +239,15 @@ where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_freeze_client::<VM>(store, block, sender, client_id, misbehavior) {
+    match _do_freeze_client::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        sender,
+        client_id,
+        misbehavior,
+    ) {
         Ok(events) => {
             warn!(client = client_id.to_string(), "Froze IBC client due to misbehavior");
             Ok(events)
@@ -194,6 +262,8 @@ where
 fn _do_freeze_client<VM>(
     store:       Box<dyn Storage>,
     block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
     sender:      &Addr,
     client_id:   &Addr,
     misbehavior: Json,
@@ -204,9 +274,16 @@ where
 {
     let chain_id = CHAIN_ID.load(&store)?;
     let account = ACCOUNTS.load(&store, client_id)?;
+    assert_not_reentered(call_stack, client_id, &account)?;
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), client_id, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        client_id,
+        program,
+    )?;
 
     // call `ibc_client_update` entry point
     let ctx = Context {
@@ -222,11 +299,18 @@ where
     let msg = IbcClientUpdateMsg::UpdateOnMisbehavior {
         misbehavior,
     };
-    let resp = instance.call_ibc_client_update(&ctx, &msg)?.into_std_result()?;
+    let resp = instance.call_ibc_client_update(&ctx, &msg)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_client_misbehavior_event(&ctx.contract, &account.code_hash, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
@@ -1,6 +1,10 @@
 use {
-    crate::{new_set_config_event, AppError, AppResult, CONFIG},
-    grug_types::{Addr, Config, Event, Storage},
+    crate::{
+        new_set_config_event, new_update_consensus_params_event, AppError, AppResult,
+        BLOCKER_FAILURES, CONFIG, CONSENSUS_PARAMS, GAS_COSTS, MAX_CALL_DEPTH,
+        PENDING_CONSENSUS_PARAMS,
+    },
+    grug_types::{Addr, Config, ConsensusParams, Event, StdResult, Storage},
     tracing::{info, warn},
 };
 
@@ -27,6 +31,140 @@ fn _do_set_config(
     new_cfg: &Config,
 ) -> AppResult<Vec<Event>> {
     // make sure the sender is authorized to set the config
+    assert_owner(store, sender)?;
+
+    // save the new config
+    save_config(store, new_cfg)?;
+
+    // the owner may have fixed the blockers that were failing, so give them a
+    // fresh start. this also enables the ones that were disabled.
+    BLOCKER_FAILURES.clear(store, None, None, None)?;
+
+    Ok(vec![new_set_config_event(sender)])
+}
+
+/// Save the config, along with the copies of its gas costs that VM instances
+/// are created with, and of its maximum call depth that submessages are
+/// handled with.
+pub fn save_config(store: &mut dyn Storage, cfg: &Config) -> StdResult<()> {
+    CONFIG.save(store, cfg)?;
+    GAS_COSTS.save(store, &cfg.gas_costs)?;
+    MAX_CALL_DEPTH.save(store, &cfg.max_call_depth)
+}
+
+pub fn do_update_consensus_params(
+    store:   &mut dyn Storage,
+    sender:  &Addr,
+    updates: ConsensusParams,
+) -> AppResult<Vec<Event>> {
+    match _do_update_consensus_params(store, sender, updates) {
+        Ok(events) => {
+            info!("Scheduled consensus params updates");
+            Ok(events)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to schedule consensus params updates");
+            Err(err)
+        },
+    }
+}
+
+fn _do_update_consensus_params(
+    store:   &mut dyn Storage,
+    sender:  &Addr,
+    updates: ConsensusParams,
+) -> AppResult<Vec<Event>> {
+    assert_owner(store, sender)?;
+
+    // if updates were already scheduled in this block, the new ones are merged
+    // on top of them
+    let mut pending = PENDING_CONSENSUS_PARAMS.may_load(store)?.unwrap_or_default();
+    pending.merge(updates);
+
+    // CometBFT halts if it's given invalid params, so they must be rejected
+    // here. the sections that aren't updated keep their current values, which
+    // some of the checks depend on.
+    let mut params = CONSENSUS_PARAMS.may_load(store)?.unwrap_or_default();
+    params.merge(pending.clone());
+    validate_consensus_params(&params)?;
+
+    PENDING_CONSENSUS_PARAMS.save(store, &pending)?;
+
+    Ok(vec![new_update_consensus_params_event(sender)])
+}
+
+/// The largest block size CometBFT allows, which is 100 MiB. A block params'
+/// `max_bytes` of -1 means this.
+const MAX_BLOCK_BYTES: i64 = 100 * 1024 * 1024;
+
+/// Perform the same checks on the consensus params as CometBFT does.
+fn validate_consensus_params(params: &ConsensusParams) -> AppResult<()> {
+    let mut max_block_bytes = MAX_BLOCK_BYTES;
+
+    if let Some(block) = &params.block {
+        if block.max_bytes == 0 || block.max_bytes < -1 || block.max_bytes > MAX_BLOCK_BYTES {
+            return Err(AppError::invalid_consensus_params(format!(
+                "block max bytes must be -1 or in the range [1, {MAX_BLOCK_BYTES}], got {}",
+                block.max_bytes
+            )));
+        }
+
+        if block.max_gas < -1 {
+            return Err(AppError::invalid_consensus_params(format!(
+                "block max gas must be -1 or non-negative, got {}",
+                block.max_gas
+            )));
+        }
+
+        if block.max_bytes != -1 {
+            max_block_bytes = block.max_bytes;
+        }
+    }
+
+    if let Some(evidence) = &params.evidence {
+        if evidence.max_age_num_blocks <= 0 {
+            return Err(AppError::invalid_consensus_params(format!(
+                "evidence max age num blocks must be positive, got {}",
+                evidence.max_age_num_blocks
+            )));
+        }
+
+        if evidence.max_age_duration.nanos() == 0 {
+            return Err(AppError::invalid_consensus_params(
+                "evidence max age duration must be positive",
+            ));
+        }
+
+        if evidence.max_bytes < 0 || evidence.max_bytes > max_block_bytes {
+            return Err(AppError::invalid_consensus_params(format!(
+                "evidence max bytes must be in the range [0, {max_block_bytes}], got {}",
+                evidence.max_bytes
+            )));
+        }
+    }
+
+    if let Some(validator) = &params.validator {
+        if validator.pub_key_types.is_empty() {
+            return Err(AppError::invalid_consensus_params(
+                "validator pubkey types must not be empty",
+            ));
+        }
+    }
+
+    if let Some(abci) = &params.abci {
+        if abci.vote_extensions_enable_height < 0 {
+            return Err(AppError::invalid_consensus_params(format!(
+                "vote extensions enable height must be non-negative, got {}",
+                abci.vote_extensions_enable_height
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Make sure the sender is the chain's owner.
+pub fn assert_owner(store: &dyn Storage, sender: &Addr) -> AppResult<()> {
     let cfg = CONFIG.load(store)?;
     let Some(owner) = cfg.owner else {
         return Err(AppError::OwnerNotSet);
@@ -35,8 +173,74 @@ fn _do_set_config(
         return Err(AppError::not_owner(sender.clone(), owner));
     }
 
-    // save the new config
-    CONFIG.save(store, new_cfg)?;
+    Ok(())
+}
 
-    Ok(vec![new_set_config_event(sender)])
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        grug_types::{AbciParams, BlockParams, Duration, EvidenceParams, ValidatorParams},
+        test_case::test_case,
+    };
+
+    fn mock_block(max_bytes: i64, max_gas: i64) -> Option<BlockParams> {
+        Some(BlockParams { max_bytes, max_gas })
+    }
+
+    fn mock_evidence(
+        max_age_num_blocks: i64,
+        max_age_seconds: u64,
+        max_bytes: i64,
+    ) -> Option<EvidenceParams> {
+        Some(EvidenceParams {
+            max_age_num_blocks,
+            max_age_duration: Duration::from_seconds(max_age_seconds).unwrap(),
+            max_bytes,
+        })
+    }
+
+    #[test_case(mock_block(-1, -1), None => true; "unlimited block")]
+    #[test_case(mock_block(0, -1), None => false; "zero block max bytes")]
+    #[test_case(mock_block(-2, -1), None => false; "negative block max bytes")]
+    #[test_case(mock_block(MAX_BLOCK_BYTES + 1, -1), None => false; "block too large")]
+    #[test_case(mock_block(1000, -2), None => false; "negative block max gas")]
+    #[test_case(None, mock_evidence(100, 3600, 1000) => true; "evidence")]
+    #[test_case(None, mock_evidence(0, 3600, 1000) => false; "zero evidence max age")]
+    #[test_case(None, mock_evidence(100, 0, 1000) => false; "zero evidence max duration")]
+    #[test_case(None, mock_evidence(100, 3600, -1) => false; "negative evidence max bytes")]
+    #[test_case(mock_block(1000, -1), mock_evidence(100, 3600, 1000) => true; "evidence as block")]
+    #[test_case(mock_block(1000, -1), mock_evidence(100, 3600, 1001) => false; "too much evidence")]
+    fn validating_block_and_evidence_params(
+        block: Option<BlockParams>,
+        evidence: Option<EvidenceParams>,
+    ) -> bool {
+        validate_consensus_params(&ConsensusParams {
+            block,
+            evidence,
+            ..Default::default()
+        })
+        .is_ok()
+    }
+
+    #[test]
+    fn validating_validator_and_abci_params() {
+        let params = ConsensusParams {
+            validator: Some(ValidatorParams {
+                pub_key_types: vec![],
+            }),
+            ..Default::default()
+        };
+        assert!(validate_consensus_params(&params).is_err());
+
+        let params = ConsensusParams {
+            abci: Some(AbciParams {
+                vote_extensions_enable_height: -1,
+            }),
+            ..Default::default()
+        };
+        assert!(validate_consensus_params(&params).is_err());
+    }
 }
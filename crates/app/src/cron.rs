@@ -1,39 +1,67 @@
 use {
     crate::{
         create_vm_instance, handle_submessages, load_program, new_after_block_event,
-        new_before_block_event, AppError, AppResult, Vm, ACCOUNTS, CHAIN_ID,
+        new_before_block_event, new_blocker_failure_event, new_cron_execute_event, AppError,
+        AppResult, CacheStore, ContractResult, GasTracker, SharedStore, Vm, ACCOUNTS,
+        BLOCKER_FAILURES, CHAIN_ID, NEXT_CRONJOBS,
     },
-    grug_types::{Addr, BlockInfo, Context, Event, Storage},
-    tracing::{info, warn},
+    grug_types::{
+        Addr, BlockInfo, BlockerFailurePolicy, Config, Context, Event, Order, StdResult, Storage,
+    },
+    tracing::{debug, info, warn},
 };
 
-// ------------------------------- before block --------------------------------
+// -------------------------------- block hooks --------------------------------
+
+/// The entry points called by begin blockers, end blockers, and cronjobs,
+/// respectively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockHook {
+    BeforeBlock,
+    AfterBlock,
+    CronExecute,
+}
 
-pub fn do_before_block<VM>(
-    store:    Box<dyn Storage>,
-    block:    &BlockInfo,
-    contract: &Addr,
+impl BlockHook {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockHook::BeforeBlock => "before_block",
+            BlockHook::AfterBlock => "after_block",
+            BlockHook::CronExecute => "cron_execute",
+        }
+    }
+}
+
+/// Call the entry point of a begin blocker, end blocker, or cronjob.
+pub fn do_block_hook<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+    hook:        BlockHook,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_before_block::<VM>(store, block, contract) {
+    match _do_block_hook::<VM>(store, block, gas_tracker, contract, hook) {
         Ok(events) => {
-            info!(contract = contract.to_string(), "Called before block hook");
+            info!(contract = contract.to_string(), hook = hook.as_str(), "Called block hook");
             Ok(events)
         },
         Err(err) => {
-            warn!(err = err.to_string(), "Failed to call before block hook");
+            warn!(hook = hook.as_str(), err = err.to_string(), "Failed to call block hook");
             Err(err)
         },
     }
 }
 
-fn _do_before_block<VM>(
-    store:    Box<dyn Storage>,
-    block:    &BlockInfo,
-    contract: &Addr,
+fn _do_block_hook<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+    hook:        BlockHook,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
@@ -43,9 +71,15 @@ where
     let account = ACCOUNTS.load(&store, contract)?;
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), contract, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        contract,
+        program,
+    )?;
 
-    // call the recipient contract's `before_block` entry point
+    // call the contract's entry point corresponding to the hook
     let ctx = Context {
         chain_id,
         block_height:    block.height,
@@ -56,69 +90,161 @@ where
         funds:           None,
         simulate:        None,
     };
-    let resp = instance.call_before_block(&ctx)?.into_std_result()?;
+    let resp = match hook {
+        BlockHook::BeforeBlock => instance.call_before_block(&ctx)?,
+        BlockHook::AfterBlock => instance.call_after_block(&ctx)?,
+        BlockHook::CronExecute => instance.call_cron_execute(&ctx)?,
+    }
+    .into_app_result(&ctx.contract)?;
 
     // handle submessages
-    let mut events = vec![new_before_block_event(contract, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+    let event = match hook {
+        BlockHook::BeforeBlock => new_before_block_event(contract, resp.attributes),
+        BlockHook::AfterBlock => new_after_block_event(contract, resp.attributes),
+        BlockHook::CronExecute => new_cron_execute_event(contract, resp.attributes),
+    };
+    let mut events = vec![event];
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        &[],
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
 
-// -------------------------------- after block --------------------------------
+// ------------------------------ failure policy -------------------------------
 
-pub fn do_after_block<VM>(
-    store:    Box<dyn Storage>,
-    block:    &BlockInfo,
-    contract: &Addr,
+/// Call a begin blocker, end blocker, or cronjob in its own cache layer, so that its state
+/// changes can be discarded if it fails, and handle the failure according to
+/// the blocker's failure policy in the chain config. Each call may consume up
+/// to the config's `handler_gas_limit`.
+///
+/// Unless the policy is to halt, a failure is recorded as an event instead of
+/// an error, so that the block can proceed.
+pub fn do_blocker<VM>(
+    mut store: Box<dyn Storage>,
+    block:     &BlockInfo,
+    cfg:       &Config,
+    contract:  &Addr,
+    hook:      BlockHook,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_after_block::<VM>(store, block, contract) {
-        Ok(events) => {
-            info!(contract = contract.to_string(), "Called after block hook");
+    let policy = cfg
+        .blocker_failure_policies
+        .get(contract)
+        .unwrap_or(&BlockerFailurePolicy::Halt);
+    // failures are counted separately for each hook, as a contract may e.g. be
+    // both an end blocker and a cronjob
+    let failures = BLOCKER_FAILURES.may_load(&store, (contract, hook.as_str()))?.unwrap_or(0);
+
+    if let BlockerFailurePolicy::DisableAfter(limit) = policy {
+        if failures >= *limit {
+            debug!(contract = contract.to_string(), failures, "Skipping disabled blocker");
+            return Ok(vec![]);
+        }
+    }
+
+    // the call isn't part of any transaction, so nobody pays for it. it's
+    // limited like the proposal and vote extension handlers, so that a blocker
+    // that loops forever fails with an out of gas error, which is subject to
+    // the failure policy, rather than stalling the chain.
+    let gas_tracker = GasTracker::new_limited(cfg.handler_gas_limit);
+    let cached = SharedStore::new(CacheStore::new(store.clone(), None));
+    let res = do_block_hook::<VM>(Box::new(cached.share()), block, gas_tracker, contract, hook);
+
+    match (res, policy) {
+        // success - flush state changes, and reset the failure count
+        (Ok(events), _) => {
+            cached.disassemble().consume();
+            if failures > 0 {
+                BLOCKER_FAILURES.remove(&mut store, (contract, hook.as_str()));
+            }
             Ok(events)
         },
-        Err(err) => {
-            warn!(err = err.to_string(), "Failed to call after block hook");
-            Err(err)
+        // error and the policy is to halt - abort the block
+        (Err(err), BlockerFailurePolicy::Halt) => Err(err),
+        // error otherwise - discard state changes, record the failure, move on
+        (Err(err), _) => {
+            let failures = failures + 1;
+            let disabled = matches!(
+                policy,
+                BlockerFailurePolicy::DisableAfter(limit) if failures >= *limit
+            );
+            BLOCKER_FAILURES.save(&mut store, (contract, hook.as_str()), &failures)?;
+
+            warn!(
+                contract = contract.to_string(),
+                hook = hook.as_str(),
+                failures,
+                disabled,
+                "Skipped failed blocker"
+            );
+
+            Ok(vec![new_blocker_failure_event(
+                contract,
+                hook.as_str(),
+                failures,
+                disabled,
+                &err.to_string(),
+            )])
         },
     }
 }
 
-fn _do_after_block<VM>(
-    store:    Box<dyn Storage>,
-    block:    &BlockInfo,
-    contract: &Addr,
+// --------------------------------- cronjobs ----------------------------------
+
+/// Call the cronjobs that are due in this block, and schedule their next runs.
+///
+/// A cronjob that was just added to the config is scheduled, but not called,
+/// in the first block after it's added. The schedules of cronjobs that have
+/// been removed from the config are deleted.
+pub fn process_cronjobs<VM>(
+    mut store: Box<dyn Storage>,
+    block:     &BlockInfo,
+    cfg:       &Config,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    let chain_id = CHAIN_ID.load(&store)?;
-    let account = ACCOUNTS.load(&store, contract)?;
+    let mut events = vec![];
 
-    let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), contract, program)?;
+    let removed = NEXT_CRONJOBS
+        .keys(&store, None, None, Order::Ascending)
+        .filter(|res| !matches!(res, Ok(contract) if cfg.cronjobs.contains_key(contract)))
+        .collect::<StdResult<Vec<_>>>()?;
+    for contract in removed {
+        NEXT_CRONJOBS.remove(&mut store, &contract);
+    }
 
-    // call the recipient contract's `after_block` entry point
-    let ctx = Context {
-        chain_id,
-        block_height:    block.height,
-        block_timestamp: block.timestamp,
-        block_hash:      block.hash.clone(),
-        contract:        contract.clone(),
-        sender:          None,
-        funds:           None,
-        simulate:        None,
-    };
-    let resp = instance.call_after_block(&ctx)?.into_std_result()?;
+    for (contract, interval) in &cfg.cronjobs {
+        match NEXT_CRONJOBS.may_load(&store, contract)? {
+            Some(next_run) if next_run > block.timestamp => continue,
+            Some(_) => {
+                debug!(contract = contract.to_string(), "Calling cronjob");
+                events.extend(do_blocker::<VM>(
+                    store.clone(),
+                    block,
+                    cfg,
+                    contract,
+                    BlockHook::CronExecute,
+                )?);
+            },
+            None => (),
+        }
 
-    // handle submessages
-    let mut events = vec![new_after_block_event(contract, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+        // an interval so long that the next run overflows the timestamp means
+        // the cronjob never runs again, rather than the chain halting
+        let next_run = block.timestamp.saturating_plus(*interval);
+        NEXT_CRONJOBS.save(&mut store, contract, &next_run)?;
+    }
 
     Ok(events)
 }
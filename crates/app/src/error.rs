@@ -1,15 +1,25 @@
 use {
-    grug_types::{Addr, Hash, StdError},
+    grug_types::{Addr, BlockInfo, GenericResult, Hash, StdError, Timestamp, Tx},
     thiserror::Error,
 };
 
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error(transparent)]
-    Std(#[from] StdError),
+    Std(StdError),
 
-    #[error("VM error: {0}")]
-    Vm(String),
+    #[error("VM error: {msg}")]
+    Vm {
+        codespace: &'static str,
+        code:      u32,
+        msg:       String,
+    },
+
+    #[error("Contract `{address}` returned error: {msg}")]
+    Contract {
+        address: Addr,
+        msg:     String,
+    },
 
     #[error("DB error: {0}")]
     Db(String),
@@ -58,9 +68,134 @@ pub enum AppError {
     NotAllowedClient {
         code_hash: Hash,
     },
+
+    #[error(
+        "Transaction has expired! expiry height: {expiry_height:?}, expiry timestamp: \
+         {expiry_timestamp:?}, block height: {block_height}, block timestamp: {block_timestamp:?}"
+    )]
+    TxExpired {
+        expiry_height:    Option<u64>,
+        expiry_timestamp: Option<Timestamp>,
+        block_height:     u64,
+        block_timestamp:  Timestamp,
+    },
+
+    #[error("Out of gas! limit: {limit}, used: {used}, comment: {comment}")]
+    OutOfGas {
+        limit:   u64,
+        used:    u64,
+        comment: &'static str,
+    },
+
+    #[error("Unknown query path `{path}`; must be `/app`, `/simulate`, or `/store`")]
+    UnknownQueryPath {
+        path: String,
+    },
+
+    #[error("Exceeded maximum call depth of {max_depth}")]
+    ExceedMaxCallDepth {
+        max_depth: u32,
+    },
+
+    #[error("Contract `{contract}` can't be re-entered while it's on the call stack")]
+    Reentrancy {
+        contract: Addr,
+    },
+
+    #[error("Vote extensions are enabled, but the block doesn't start with them")]
+    VoteExtensionsNotFound,
+
+    #[error("Invalid vote extensions: {reason}")]
+    InvalidVoteExtensions {
+        reason: String,
+    },
+
+    #[error("Snapshot error: {0}")]
+    Snapshot(String),
+
+    #[error("Snapshot format {format} is not supported")]
+    UnsupportedSnapshotFormat {
+        format: u32,
+    },
+
+    #[error("Snapshot chunk {index} doesn't match its hash")]
+    SnapshotChunkMismatch {
+        index: u32,
+    },
+
+    #[error("Snapshot chunk {index} matches its hash, but can't be decoded: {reason}")]
+    MalformedSnapshotChunk {
+        index:  u32,
+        reason: String,
+    },
+
+    #[error(
+        "Restored app hash doesn't match the trusted one! expecting: {expect}, actual: {actual:?}"
+    )]
+    AppHashMismatch {
+        expect: Hash,
+        actual: Option<Hash>,
+    },
+
+    #[error("State at height {height} has been pruned! oldest available: {oldest}")]
+    HeightPruned {
+        height: u64,
+        oldest: u64,
+    },
+
+    #[error("Upgrade height must be after the current block! height: {height}, current: {current}")]
+    UpgradeHeightTooLow {
+        height:  u64,
+        current: u64,
+    },
+
+    #[error(
+        "Upgrade `{name}` is scheduled at height {height}, but this binary has no handler for it"
+    )]
+    UpgradeNeeded {
+        name:   String,
+        height: u64,
+    },
+
+    #[error("Invalid consensus params: {reason}")]
+    InvalidConsensusParams {
+        reason: String,
+    },
+
+    #[error("Invalid validator update: {reason}")]
+    InvalidValidatorUpdate {
+        reason: String,
+    },
+}
+
+// out of gas errors are emitted by the gas tracker as `StdError`s, so that VMs
+// can propagate them. we convert them to the dedicated AppError variant here,
+// so that they can be distinguished from other errors.
+impl From<StdError> for AppError {
+    fn from(err: StdError) -> Self {
+        match err {
+            StdError::OutOfGas { limit, used, comment } => Self::OutOfGas { limit, used, comment },
+            err => Self::Std(err),
+        }
+    }
 }
 
 impl AppError {
+    pub fn vm(codespace: &'static str, code: u32, msg: impl ToString) -> Self {
+        Self::Vm {
+            codespace,
+            code,
+            msg: msg.to_string(),
+        }
+    }
+
+    pub fn contract(address: Addr, msg: impl Into<String>) -> Self {
+        Self::Contract {
+            address,
+            msg: msg.into(),
+        }
+    }
+
     pub fn incorrect_block_height(expect: u64, actual: u64) -> Self {
         Self::IncorrectBlockHeight { expect, actual }
     }
@@ -84,6 +219,334 @@ impl AppError {
     pub fn not_allowed_client(code_hash: Hash) -> Self {
         Self::NotAllowedClient { code_hash }
     }
+
+    pub fn tx_expired(tx: &Tx, block: &BlockInfo) -> Self {
+        Self::TxExpired {
+            expiry_height:    tx.expiry_height.map(|height| height.u64()),
+            expiry_timestamp: tx.expiry_timestamp,
+            block_height:     block.height.u64(),
+            block_timestamp:  block.timestamp,
+        }
+    }
+
+    pub fn exceed_max_call_depth(max_depth: u32) -> Self {
+        Self::ExceedMaxCallDepth { max_depth }
+    }
+
+    pub fn reentrancy(contract: Addr) -> Self {
+        Self::Reentrancy { contract }
+    }
+
+    pub fn unsupported_snapshot_format(format: u32) -> Self {
+        Self::UnsupportedSnapshotFormat { format }
+    }
+
+    pub fn snapshot_chunk_mismatch(index: u32) -> Self {
+        Self::SnapshotChunkMismatch { index }
+    }
+
+    pub fn invalid_vote_extensions(reason: impl ToString) -> Self {
+        Self::InvalidVoteExtensions {
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn malformed_snapshot_chunk(index: u32, reason: impl ToString) -> Self {
+        Self::MalformedSnapshotChunk {
+            index,
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn app_hash_mismatch(expect: Hash, actual: Option<Hash>) -> Self {
+        Self::AppHashMismatch { expect, actual }
+    }
+
+    pub fn height_pruned(height: u64, oldest: u64) -> Self {
+        Self::HeightPruned { height, oldest }
+    }
+
+    pub fn upgrade_height_too_low(height: u64, current: u64) -> Self {
+        Self::UpgradeHeightTooLow { height, current }
+    }
+
+    pub fn upgrade_needed(name: String, height: u64) -> Self {
+        Self::UpgradeNeeded { name, height }
+    }
+
+    pub fn invalid_consensus_params(reason: impl ToString) -> Self {
+        Self::InvalidConsensusParams {
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn invalid_validator_update(reason: impl ToString) -> Self {
+        Self::InvalidValidatorUpdate {
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl AppError {
+    /// The ABCI codespace under which errors raised by the app itself are
+    /// reported.
+    pub const CODESPACE: &'static str = "app";
+
+    /// Return the ABCI codespace of this error.
+    ///
+    /// Errors from the standard library and the VM are reported under their
+    /// own codespaces. Errors returned by contracts are reported under a
+    /// codespace specific to the contract, in the format `contract/{address}`.
+    pub fn codespace(&self) -> String {
+        match self {
+            AppError::Std(_) => StdError::CODESPACE.to_string(),
+            AppError::Vm { codespace, .. } => codespace.to_string(),
+            AppError::Contract { address, .. } => format!("contract/{address}"),
+            _ => Self::CODESPACE.to_string(),
+        }
+    }
+
+    /// Return the ABCI error code of this error. The code is unique within the
+    /// error's codespace, and must not change, as clients rely on them to
+    /// identify errors.
+    ///
+    /// Contracts return errors as strings, so all contract errors have the same
+    /// code. Zero is reserved for success in ABCI, so codes start from 1.
+    pub fn code(&self) -> u32 {
+        match self {
+            AppError::Std(err) => err.code(),
+            AppError::Vm { code, .. } => *code,
+            AppError::Contract { .. } => 1,
+            AppError::Db(_) => 1,
+            AppError::ProofNotSupported => 2,
+            AppError::Unauthorized => 3,
+            AppError::IncorrectBlockHeight { .. } => 4,
+            AppError::OwnerNotSet => 5,
+            AppError::NotOwner { .. } => 6,
+            AppError::AdminNotSet => 7,
+            AppError::NotAdmin { .. } => 8,
+            AppError::CodeExists { .. } => 9,
+            AppError::AccountExists { .. } => 10,
+            AppError::NotAllowedClient { .. } => 11,
+            AppError::TxExpired { .. } => 12,
+            AppError::OutOfGas { .. } => 13,
+            AppError::UnknownQueryPath { .. } => 14,
+            AppError::ExceedMaxCallDepth { .. } => 15,
+            AppError::Reentrancy { .. } => 16,
+            AppError::VoteExtensionsNotFound => 17,
+            AppError::Snapshot(_) => 18,
+            AppError::UnsupportedSnapshotFormat { .. } => 19,
+            AppError::SnapshotChunkMismatch { .. } => 20,
+            AppError::AppHashMismatch { .. } => 21,
+            AppError::HeightPruned { .. } => 22,
+            AppError::UpgradeHeightTooLow { .. } => 23,
+            AppError::UpgradeNeeded { .. } => 24,
+            AppError::InvalidVoteExtensions { .. } => 25,
+            AppError::MalformedSnapshotChunk { .. } => 26,
+            AppError::InvalidConsensusParams { .. } => 27,
+            AppError::InvalidValidatorUpdate { .. } => 28,
+        }
+    }
+}
+
+/// Converts the result returned by a contract call into an `AppResult`. If the
+/// contract returned an error, it is tagged with the contract's address, so
+/// that it's reported under the contract's own codespace.
+pub trait ContractResult<T> {
+    fn into_app_result(self, contract: &Addr) -> AppResult<T>;
+}
+
+impl<T> ContractResult<T> for GenericResult<T> {
+    fn into_app_result(self, contract: &Addr) -> AppResult<T> {
+        match self {
+            GenericResult::Ok(data) => Ok(data),
+            GenericResult::Err(msg) => Err(AppError::contract(contract.clone(), msg)),
+        }
+    }
 }
 
 pub type AppResult<T> = std::result::Result<T, AppError>;
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // clients rely on the codes to identify errors, so they must not change
+    #[test]
+    fn codes_are_stable() {
+        let addr = Addr::mock(1);
+        for (err, codespace, code) in [
+            (AppError::Std(StdError::VerificationFailed), "std", 4),
+            (StdError::out_of_gas(1, 2, "test").into(), "app", 13),
+            (AppError::vm("wasm_vm", 5, "trap"), "wasm_vm", 5),
+            (AppError::Db("oops".into()), "app", 1),
+            (AppError::ProofNotSupported, "app", 2),
+            (AppError::Unauthorized, "app", 3),
+            (
+                AppError::IncorrectBlockHeight {
+                    expect: 1,
+                    actual: 2,
+                },
+                "app",
+                4,
+            ),
+            (AppError::OwnerNotSet, "app", 5),
+            (
+                AppError::NotOwner {
+                    sender: addr.clone(),
+                    owner:  addr.clone(),
+                },
+                "app",
+                6,
+            ),
+            (AppError::AdminNotSet, "app", 7),
+            (
+                AppError::NotAdmin {
+                    sender: addr.clone(),
+                    admin:  addr.clone(),
+                },
+                "app",
+                8,
+            ),
+            (
+                AppError::CodeExists {
+                    hash: Hash::ZERO,
+                },
+                "app",
+                9,
+            ),
+            (
+                AppError::AccountExists {
+                    address: addr.clone(),
+                },
+                "app",
+                10,
+            ),
+            (
+                AppError::NotAllowedClient {
+                    code_hash: Hash::ZERO,
+                },
+                "app",
+                11,
+            ),
+            (
+                AppError::TxExpired {
+                    expiry_height:    Some(1),
+                    expiry_timestamp: None,
+                    block_height:     2,
+                    block_timestamp:  Timestamp::from_seconds(2),
+                },
+                "app",
+                12,
+            ),
+            (
+                AppError::UnknownQueryPath {
+                    path: "/foo".into(),
+                },
+                "app",
+                14,
+            ),
+            (
+                AppError::ExceedMaxCallDepth {
+                    max_depth: 3,
+                },
+                "app",
+                15,
+            ),
+            (
+                AppError::Reentrancy {
+                    contract: addr.clone(),
+                },
+                "app",
+                16,
+            ),
+            (AppError::VoteExtensionsNotFound, "app", 17),
+            (AppError::Snapshot("oops".into()), "app", 18),
+            (
+                AppError::UnsupportedSnapshotFormat {
+                    format: 2,
+                },
+                "app",
+                19,
+            ),
+            (
+                AppError::SnapshotChunkMismatch {
+                    index: 0,
+                },
+                "app",
+                20,
+            ),
+            (
+                AppError::AppHashMismatch {
+                    expect: Hash::ZERO,
+                    actual: None,
+                },
+                "app",
+                21,
+            ),
+            (
+                AppError::HeightPruned {
+                    height: 1,
+                    oldest: 2,
+                },
+                "app",
+                22,
+            ),
+            (
+                AppError::UpgradeHeightTooLow {
+                    height:  1,
+                    current: 2,
+                },
+                "app",
+                23,
+            ),
+            (
+                AppError::UpgradeNeeded {
+                    name:   "v2".into(),
+                    height: 1,
+                },
+                "app",
+                24,
+            ),
+            (
+                AppError::InvalidVoteExtensions {
+                    reason: "oops".into(),
+                },
+                "app",
+                25,
+            ),
+            (
+                AppError::MalformedSnapshotChunk {
+                    index:  0,
+                    reason: "oops".into(),
+                },
+                "app",
+                26,
+            ),
+            (
+                AppError::InvalidConsensusParams {
+                    reason: "oops".into(),
+                },
+                "app",
+                27,
+            ),
+            (
+                AppError::InvalidValidatorUpdate {
+                    reason: "oops".into(),
+                },
+                "app",
+                28,
+            ),
+        ] {
+            assert_eq!(err.codespace(), codespace, "codespace of {err:?}");
+            assert_eq!(err.code(), code, "code of {err:?}");
+        }
+
+        // contract errors are reported under the contract's own codespace
+        let err = AppError::contract(addr.clone(), "oops");
+        assert_eq!(err.codespace(), format!("contract/{addr}"));
+        assert_eq!(err.code(), 1);
+    }
+}
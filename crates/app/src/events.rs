@@ -1,4 +1,4 @@
-use grug_types::{Addr, Attribute, Event, Hash};
+use grug_types::{Addr, Attribute, Event, Hash, UpgradePlan};
 
 // Event attribute keys emitted by the state machine are prefixed by an
 // underscore. Contracts are not allowed to emit event attributes whose keys are
@@ -33,6 +33,24 @@ pub fn new_set_config_event(sender: &Addr) -> Event {
         .add_attribute("sender", sender)
 }
 
+pub fn new_update_consensus_params_event(sender: &Addr) -> Event {
+    Event::new("update_consensus_params")
+        .add_attribute("sender", sender)
+}
+
+pub fn new_schedule_upgrade_event(sender: &Addr, plan: &UpgradePlan) -> Event {
+    Event::new("schedule_upgrade")
+        .add_attribute("sender", sender)
+        .add_attribute("height", plan.height)
+        .add_attribute("name", &plan.name)
+}
+
+pub fn new_upgrade_event(plan: &UpgradePlan) -> Event {
+    Event::new("upgrade")
+        .add_attribute("height", plan.height)
+        .add_attribute("name", &plan.name)
+}
+
 pub fn new_upload_event(code_hash: &Hash, uploader: &Addr) -> Event {
     Event::new("upload")
         .add_attribute("hash", code_hash)
@@ -51,6 +69,45 @@ pub fn new_after_block_event(sender: &Addr, attrs: Vec<Attribute>) -> Event {
         .add_attributes(attrs)
 }
 
+pub fn new_cron_execute_event(sender: &Addr, attrs: Vec<Attribute>) -> Event {
+    Event::new("cron_execute")
+        .add_attribute(CONTRACT_ADDRESS_KEY, sender)
+        .add_attributes(attrs)
+}
+
+pub fn new_blocker_failure_event(
+    contract: &Addr,
+    hook:     &str,
+    failures: u32,
+    disabled: bool,
+    error:    &str,
+) -> Event {
+    Event::new("blocker_failure")
+        .add_attribute(CONTRACT_ADDRESS_KEY, contract)
+        .add_attribute("hook", hook)
+        .add_attribute("failures", failures)
+        .add_attribute("disabled", disabled)
+        .add_attribute("error", error)
+}
+
+pub fn new_init_validators_event(sender: &Addr, attrs: Vec<Attribute>) -> Event {
+    Event::new("init_validators")
+        .add_attribute(CONTRACT_ADDRESS_KEY, sender)
+        .add_attributes(attrs)
+}
+
+pub fn new_validator_updates_event(sender: &Addr, num_updates: usize) -> Event {
+    Event::new("validator_updates")
+        .add_attribute(CONTRACT_ADDRESS_KEY, sender)
+        .add_attribute("num_updates", num_updates)
+}
+
+pub fn new_receive_vote_extensions_event(sender: &Addr, attrs: Vec<Attribute>) -> Event {
+    Event::new("receive_vote_extensions")
+        .add_attribute(CONTRACT_ADDRESS_KEY, sender)
+        .add_attributes(attrs)
+}
+
 pub fn new_before_tx_event(sender: &Addr, attrs: Vec<Attribute>) -> Event {
     Event::new("before_tx")
         .add_attribute(CONTRACT_ADDRESS_KEY, sender)
@@ -63,6 +120,12 @@ pub fn new_after_tx_event(sender: &Addr, attrs: Vec<Attribute>) -> Event {
         .add_attributes(attrs)
 }
 
+pub fn new_handle_fee_event(taxman: &Addr, attrs: Vec<Attribute>) -> Event {
+    Event::new("handle_fee")
+        .add_attribute(CONTRACT_ADDRESS_KEY, taxman)
+        .add_attributes(attrs)
+}
+
 pub fn new_transfer_event(bank: &Addr, attrs: Vec<Attribute>) -> Event {
     Event::new("transfer")
         .add_attribute(CONTRACT_ADDRESS_KEY, bank)
@@ -101,6 +164,19 @@ pub fn new_migrate_event(
         .add_attributes(attrs)
 }
 
+pub fn new_update_admin_event(contract: &Addr, old_admin: &Addr, new_admin: &Addr) -> Event {
+    Event::new("update_admin")
+        .add_attribute(CONTRACT_ADDRESS_KEY, contract)
+        .add_attribute("old_admin", old_admin)
+        .add_attribute("new_admin", new_admin)
+}
+
+pub fn new_clear_admin_event(contract: &Addr, old_admin: &Addr) -> Event {
+    Event::new("clear_admin")
+        .add_attribute(CONTRACT_ADDRESS_KEY, contract)
+        .add_attribute("old_admin", old_admin)
+}
+
 pub fn new_reply_event(contract: &Addr, attrs: Vec<Attribute>) -> Event {
     Event::new("reply")
         .add_attribute(CONTRACT_ADDRESS_KEY, contract)
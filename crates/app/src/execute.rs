@@ -1,25 +1,28 @@
 use {
     crate::{
-        create_vm_instance, do_transfer, handle_submessages, load_program, new_execute_event,
-        AppError, AppResult, Vm, ACCOUNTS, CHAIN_ID,
+        assert_not_reentered, create_vm_instance, do_transfer, handle_submessages, load_program,
+        new_execute_event, AppError, AppResult, ContractResult, GasTracker, Vm, ACCOUNTS, CHAIN_ID,
     },
     grug_types::{Addr, BlockInfo, Coins, Context, Event, Json, Storage},
     tracing::{info, warn},
 };
 
+#[allow(clippy::too_many_arguments)]
 pub fn do_execute<VM>(
-    store:    Box<dyn Storage>,
-    block:    &BlockInfo,
-    contract: &Addr,
-    sender:   &Addr,
-    msg:      &Json,
-    funds:    Coins,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    contract:    &Addr,
+    sender:      &Addr,
+    msg:         &Json,
+    funds:       Coins,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_execute::<VM>(store, block, contract, sender, msg, funds) {
+    match _do_execute::<VM>(store, block, gas_tracker, call_stack, contract, sender, msg, funds) {
         Ok(events) => {
             info!(contract = contract.to_string(), "Executed contract");
             Ok(events)
@@ -31,13 +34,16 @@ where
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn _do_execute<VM>(
-    store:    Box<dyn Storage>,
-    block:    &BlockInfo,
-    contract: &Addr,
-    sender:   &Addr,
-    msg:      &Json,
-    funds:    Coins,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    contract:    &Addr,
+    sender:      &Addr,
+    msg:         &Json,
+    funds:       Coins,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
@@ -45,12 +51,15 @@ where
 {
     let chain_id = CHAIN_ID.load(&store)?;
     let account = ACCOUNTS.load(&store, contract)?;
+    assert_not_reentered(call_stack, contract, &account)?;
 
     // make the coin transfers
     if !funds.is_empty() {
         do_transfer::<VM>(
             store.clone(),
             block,
+            gas_tracker.clone(),
+            call_stack,
             sender.clone(),
             contract.clone(),
             funds.clone(),
@@ -59,7 +68,13 @@ where
     }
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), contract, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        contract,
+        program,
+    )?;
 
     // call execute
     let ctx = Context {
@@ -72,11 +87,18 @@ where
         funds:           Some(funds),
         simulate:        None,
     };
-    let resp = instance.call_execute(&ctx, msg)?.into_std_result()?;
+    let resp = instance.call_execute(&ctx, msg)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_execute_event(&ctx.contract, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
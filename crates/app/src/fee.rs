@@ -0,0 +1,91 @@
+use {
+    crate::{
+        create_vm_instance, handle_submessages, load_program, new_handle_fee_event, AppError,
+        AppResult, ContractResult, GasTracker, Vm, ACCOUNTS, CHAIN_ID, CONFIG,
+    },
+    grug_types::{BlockInfo, Context, Event, GasReport, Storage},
+    tracing::{debug, warn},
+};
+
+/// Call the taxman's `handle_fee` entry point with the gas report of a
+/// transaction. The call may consume up to the config's `handler_gas_limit`.
+pub fn do_handle_fee<VM>(
+    store:  Box<dyn Storage>,
+    block:  &BlockInfo,
+    report: &GasReport,
+) -> AppResult<Vec<Event>>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    match _do_handle_fee::<VM>(store, block, report) {
+        Ok(events) => {
+            debug!(
+                sender = report.sender.to_string(),
+                gas_used = report.used.u64(),
+                "Handled transaction fee"
+            );
+            Ok(events)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to handle transaction fee");
+            Err(err)
+        },
+    }
+}
+
+fn _do_handle_fee<VM>(
+    store:  Box<dyn Storage>,
+    block:  &BlockInfo,
+    report: &GasReport,
+) -> AppResult<Vec<Event>>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let chain_id = CHAIN_ID.load(&store)?;
+    let cfg = CONFIG.load(&store)?;
+    let account = ACCOUNTS.load(&store, &cfg.taxman)?;
+
+    // the taxman is given its own gas tracker, so that it can still charge the
+    // sender if the transaction itself ran out of gas. it's limited by the
+    // config, rather than by the transaction, so a taxman that loops forever
+    // fails every transaction the same way on every node, rather than stalling
+    // the chain.
+    let gas_tracker = GasTracker::new_limited(cfg.handler_gas_limit);
+
+    let program = load_program::<VM>(&store, &account.code_hash)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        &cfg.taxman,
+        program,
+    )?;
+
+    // call the taxman's `handle_fee` entry point
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        cfg.taxman,
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+    };
+    let resp = instance.call_handle_fee(&ctx, report)?.into_app_result(&ctx.contract)?;
+
+    // handle submessages
+    let mut events = vec![new_handle_fee_event(&ctx.contract, resp.attributes)];
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        &[],
+        &ctx.contract,
+        resp.submsgs,
+    )?);
+
+    Ok(events)
+}
@@ -0,0 +1,127 @@
+use {
+    grug_types::{StdError, StdResult},
+    std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+struct GasTrackerInner {
+    // `None` means there is no gas limit. This is the case during genesis, and
+    // for calls to core contracts whose failure would halt the chain anyway,
+    // such as the validator set contract.
+    limit: Option<u64>,
+    used:  u64,
+}
+
+/// Tracks the amount of gas consumed during the processing of a transaction.
+///
+/// The tracker is cheaply clonable; clones share the same underlying counter.
+/// A single tracker is created for each transaction, and passed down to every
+/// VM instance that is created while processing it, including those created
+/// for submessages and for queries made by contracts via `query_chain`.
+#[derive(Clone)]
+pub struct GasTracker {
+    inner: Arc<RwLock<GasTrackerInner>>,
+}
+
+impl GasTracker {
+    /// Create a new gas tracker with the given gas limit.
+    pub fn new_limited(limit: u64) -> Self {
+        Self::new(Some(limit))
+    }
+
+    /// Create a new gas tracker without a gas limit.
+    pub fn new_limitless() -> Self {
+        Self::new(None)
+    }
+
+    fn new(limit: Option<u64>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(GasTrackerInner {
+                limit,
+                used: 0,
+            })),
+        }
+    }
+
+    fn read_access(&self) -> RwLockReadGuard<GasTrackerInner> {
+        self.inner.read().unwrap_or_else(|err| {
+            panic!("poisoned lock: {err:?}")
+        })
+    }
+
+    fn write_access(&self) -> RwLockWriteGuard<GasTrackerInner> {
+        self.inner.write().unwrap_or_else(|err| {
+            panic!("poisoned lock: {err:?}")
+        })
+    }
+
+    /// Return the gas limit. `None` if there isn't a limit.
+    pub fn limit(&self) -> Option<u64> {
+        self.read_access().limit
+    }
+
+    /// Return the amount of gas that has been consumed so far.
+    pub fn used(&self) -> u64 {
+        self.read_access().used
+    }
+
+    /// Return the amount of gas that is still available. `None` if there
+    /// isn't a limit.
+    pub fn remaining(&self) -> Option<u64> {
+        let inner = self.read_access();
+        inner.limit.map(|limit| limit.saturating_sub(inner.used))
+    }
+
+    /// Consume the given amount of gas. Error if the gas limit is exceeded.
+    ///
+    /// Note that once the limit has been exceeded, all subsequent calls also
+    /// return the error, even if the amount to consume is zero. This allows
+    /// callers further up the stack to find out the tracker has run out of gas,
+    /// even if the error itself was swallowed or transformed along the way.
+    pub fn consume(&self, consumed: u64, comment: &'static str) -> StdResult<()> {
+        let mut inner = self.write_access();
+
+        inner.used = inner.used.saturating_add(consumed);
+
+        if let Some(limit) = inner.limit {
+            if inner.used > limit {
+                return Err(StdError::out_of_gas(limit, inner.used, comment));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consuming_gas_works() {
+        let tracker = GasTracker::new_limited(100);
+
+        // consume some gas within the limit
+        tracker.clone().consume(60, "a").unwrap();
+        assert_eq!(tracker.used(), 60);
+        assert_eq!(tracker.remaining(), Some(40));
+
+        // consume more gas than what's remaining
+        assert!(matches!(
+            tracker.consume(50, "b"),
+            Err(StdError::OutOfGas { limit: 100, used: 110, comment: "b" })
+        ));
+        assert_eq!(tracker.remaining(), Some(0));
+
+        // once run out of gas, subsequent consumptions should also fail
+        assert!(tracker.consume(0, "c").is_err());
+    }
+
+    #[test]
+    fn limitless_tracker_works() {
+        let tracker = GasTracker::new_limitless();
+        tracker.consume(u64::MAX, "a").unwrap();
+        tracker.consume(u64::MAX, "b").unwrap();
+        assert_eq!(tracker.limit(), None);
+        assert_eq!(tracker.remaining(), None);
+    }
+}
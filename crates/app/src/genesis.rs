@@ -0,0 +1,88 @@
+use {
+    crate::{
+        AppResult, PrefixStore, ACCOUNTS, CHAIN_ID, CODES, CODE_INFOS, CONTRACT_NAMESPACE,
+        LAST_FINALIZED_BLOCK,
+    },
+    grug_types::{
+        concat, increment_last_byte, Binary, ExportedAccount, ExportedCode, ExportedState, Order,
+        StdResult, Storage,
+    },
+    std::collections::BTreeMap,
+};
+
+/// Walk the storage and collect the state to be included in a genesis state:
+/// the codes, and the accounts along with their contract substores.
+///
+/// Other chain-level state is not exported, and starts afresh in the new chain:
+///
+/// - The consensus params, along with updates scheduled but not yet applied,
+///   are taken from CometBFT's `genesis.json`.
+/// - Our copy of CometBFT's validator set, along with updates returned by the
+///   validator set contract but not yet applied, is rebuilt from the validators
+///   in `genesis.json`. These should match the set the contract holds, which
+///   is exported along with its storage.
+/// - A scheduled upgrade is dropped. It must be scheduled again in the new
+///   chain, if it's still needed.
+/// - Cronjob schedules and blocker failure counts are reset.
+pub fn export_state(store: Box<dyn Storage>) -> AppResult<ExportedState> {
+    let chain_id = CHAIN_ID.load(&store)?;
+    let height = LAST_FINALIZED_BLOCK.load(&store)?.height;
+
+    let codes = CODES
+        .range(&store, None, None, Order::Ascending)
+        .map(|item| {
+            let (code_hash, code) = item?;
+            let info = CODE_INFOS.load(&store, &code_hash)?;
+            Ok((code_hash, ExportedCode {
+                code: code.into(),
+                info,
+            }))
+        })
+        .collect::<StdResult<BTreeMap<_, _>>>()?;
+
+    let accounts = ACCOUNTS
+        .range(&store, None, None, Order::Ascending)
+        .map(|item| {
+            let (address, account) = item?;
+            // note: `PrefixStore::scan` returns the keys with the prefix, so we
+            // scan the store directly and trim the prefix ourselves
+            let prefix = concat(CONTRACT_NAMESPACE, &address);
+            let max = increment_last_byte(prefix.clone());
+            let storage = store
+                .scan(Some(&prefix), Some(&max), Order::Ascending)
+                .map(|(k, v)| (Binary::from(k[prefix.len()..].to_vec()), Binary::from(v)))
+                .collect();
+            Ok((address, ExportedAccount {
+                account,
+                storage,
+            }))
+        })
+        .collect::<StdResult<BTreeMap<_, _>>>()?;
+
+    Ok(ExportedState {
+        chain_id,
+        height,
+        codes,
+        accounts,
+    })
+}
+
+/// Write exported state into the storage as is. Unlike genesis messages, the
+/// contracts' `instantiate` entry points are not called.
+pub fn import_state(mut store: Box<dyn Storage>, exported: ExportedState) -> AppResult<()> {
+    for (code_hash, ExportedCode { code, info }) in exported.codes {
+        CODES.save(&mut store, &code_hash, &code.into())?;
+        CODE_INFOS.save(&mut store, &code_hash, &info)?;
+    }
+
+    for (address, ExportedAccount { account, storage }) in exported.accounts {
+        ACCOUNTS.save(&mut store, &address, &account)?;
+
+        let mut substore = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, &address]);
+        for (key, value) in storage {
+            substore.write(&key, &value);
+        }
+    }
+
+    Ok(())
+}
@@ -1,7 +1,8 @@
 use {
     crate::{
         create_vm_instance, do_transfer, handle_submessages, has_permission, load_program,
-        new_instantiate_event, AppError, AppResult, Vm, ACCOUNTS, CHAIN_ID, CONFIG,
+        new_instantiate_event, AppError, AppResult, ContractResult, GasTracker, Vm, ACCOUNTS,
+        CHAIN_ID, CODE_INFOS, CONFIG,
     },
     grug_types::{Account, Addr, Binary, BlockInfo, Coins, Context, Event, Hash, Json, Storage},
     tracing::{info, warn},
@@ -9,20 +10,35 @@ use {
 
 #[allow(clippy::too_many_arguments)]
 pub fn do_instantiate<VM>(
-    store:     Box<dyn Storage>,
-    block:     &BlockInfo,
-    sender:    &Addr,
-    code_hash: Hash,
-    msg:       &Json,
-    salt:      Binary,
-    funds:     Coins,
-    admin:     Option<Addr>,
+    store:         Box<dyn Storage>,
+    block:         &BlockInfo,
+    gas_tracker:   GasTracker,
+    call_stack:    &[Addr],
+    sender:        &Addr,
+    code_hash:     Hash,
+    msg:           &Json,
+    salt:          Binary,
+    funds:         Coins,
+    admin:         Option<Addr>,
+    non_reentrant: bool,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_instantiate::<VM>(store, block, sender, code_hash, msg, salt, funds, admin) {
+    match _do_instantiate::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        sender,
+        code_hash,
+        msg,
+        salt,
+        funds,
+        admin,
+        non_reentrant,
+    ) {
         Ok((events, address)) => {
             info!(address = address.to_string(), "Instantiated contract");
             Ok(events)
@@ -37,14 +53,17 @@ where
 // return the address of the contract that is instantiated.
 #[allow(clippy::too_many_arguments)]
 fn _do_instantiate<VM>(
-    mut store: Box<dyn Storage>,
-    block:     &BlockInfo,
-    sender:    &Addr,
-    code_hash: Hash,
-    msg:       &Json,
-    salt:      Binary,
-    funds:     Coins,
-    admin:     Option<Addr>,
+    mut store:     Box<dyn Storage>,
+    block:         &BlockInfo,
+    gas_tracker:   GasTracker,
+    call_stack:    &[Addr],
+    sender:        &Addr,
+    code_hash:     Hash,
+    msg:           &Json,
+    salt:          Binary,
+    funds:         Coins,
+    admin:         Option<Addr>,
+    non_reentrant: bool,
 ) -> AppResult<(Vec<Event>, Addr)>
 where
     VM: Vm + 'static,
@@ -56,6 +75,12 @@ where
         return Err(AppError::Unauthorized);
     }
 
+    // make sure the user has permission to instantiate this particular code
+    let code_info = CODE_INFOS.load(&store, &code_hash)?;
+    if !has_permission(&code_info.instantiate_permission, cfg.owner.as_ref(), sender) {
+        return Err(AppError::Unauthorized);
+    }
+
     // compute contract address and make sure there can't already be an account
     // of the same address
     let address = Addr::compute(sender, &code_hash, &salt);
@@ -64,7 +89,11 @@ where
     }
 
     // save the account info now that we know there's no duplicate
-    let account = Account { code_hash, admin };
+    let account = Account {
+        code_hash,
+        admin,
+        non_reentrant,
+    };
     ACCOUNTS.save(&mut store, &address, &account)?;
 
     // make the coin transfers
@@ -72,6 +101,8 @@ where
         do_transfer::<VM>(
             store.clone(),
             block,
+            gas_tracker.clone(),
+            call_stack,
             sender.clone(),
             address.clone(),
             funds.clone(),
@@ -81,7 +112,13 @@ where
 
     // create VM instance
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), &address, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        &address,
+        program,
+    )?;
 
     // call instantiate
     let ctx = Context {
@@ -94,11 +131,18 @@ where
         funds:           Some(funds),
         simulate:        None,
     };
-    let resp = instance.call_instantiate(&ctx, msg)?.into_std_result()?;
+    let resp = instance.call_instantiate(&ctx, msg)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_instantiate_event(&ctx.contract, &account.code_hash, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok((events, ctx.contract))
 }
@@ -1,5 +1,6 @@
 #[cfg(feature = "abci")]
 mod abci;
+mod admin;
 mod app;
 mod auth;
 mod cache;
@@ -11,21 +12,31 @@ mod cron;
 mod error;
 mod events;
 mod execute;
+mod fee;
+mod gas;
+mod genesis;
 mod instantiate;
 mod migrate;
 mod prefix;
+mod proposal;
+mod pruning;
 mod querier;
 mod query;
 mod shared;
+mod snapshot;
 mod state;
 mod submessage;
 mod traits;
 mod transfer;
+mod upgrade;
 mod upload;
+mod validator;
 mod vm;
+mod vote;
 
 pub use crate::{
-    app::*, auth::*, cache::*, client::*, config::*, cron::*, error::*, events::*, execute::*,
-    instantiate::*, migrate::*, prefix::*, querier::*, query::*, shared::*, state::*,
-    submessage::*, traits::*, transfer::*, upload::*, vm::*,
+    admin::*, app::*, auth::*, cache::*, client::*, config::*, cron::*, error::*, events::*,
+    execute::*, fee::*, gas::*, genesis::*, instantiate::*, migrate::*, prefix::*, proposal::*,
+    pruning::*, querier::*, query::*, shared::*, snapshot::*, state::*, submessage::*, traits::*,
+    transfer::*, upgrade::*, upload::*, validator::*, vm::*, vote::*,
 };
@@ -1,15 +1,18 @@
 use {
     crate::{
-        create_vm_instance, handle_submessages, load_program, new_migrate_event, AppError,
-        AppResult, Vm, ACCOUNTS, CHAIN_ID,
+        assert_not_reentered, create_vm_instance, handle_submessages, load_program,
+        new_migrate_event, AppError, AppResult, ContractResult, GasTracker, Vm, ACCOUNTS, CHAIN_ID,
     },
     grug_types::{Addr, BlockInfo, Context, Event, Hash, Json, Storage},
     tracing::{info, warn},
 };
 
+#[allow(clippy::too_many_arguments)]
 pub fn do_migrate<VM>(
     store:         Box<dyn Storage>,
     block:         &BlockInfo,
+    gas_tracker:   GasTracker,
+    call_stack:    &[Addr],
     contract:      &Addr,
     sender:        &Addr,
     new_code_hash: Hash,
@@ -19,7 +22,16 @@ where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_migrate::<VM>(store, block, contract, sender, new_code_hash, msg) {
+    match _do_migrate::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        contract,
+        sender,
+        new_code_hash,
+        msg,
+    ) {
         Ok(events) => {
             info!(contract = contract.to_string(), "Migrated contract");
             Ok(events)
@@ -31,9 +43,12 @@ where
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn _do_migrate<VM>(
     mut store:     Box<dyn Storage>,
     block:         &BlockInfo,
+    gas_tracker:   GasTracker,
+    call_stack:    &[Addr],
     contract:      &Addr,
     sender:        &Addr,
     new_code_hash: Hash,
@@ -45,6 +60,7 @@ where
 {
     let chain_id = CHAIN_ID.load(&store)?;
     let mut account = ACCOUNTS.load(&store, contract)?;
+    assert_not_reentered(call_stack, contract, &account)?;
 
     // only the admin can update code hash
     let Some(admin) = &account.admin else {
@@ -61,7 +77,13 @@ where
 
     // create VM instance
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), contract, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        contract,
+        program,
+    )?;
 
     // call the contract's migrate entry point
     let ctx = Context {
@@ -74,7 +96,7 @@ where
         funds:           None,
         simulate:        None,
     };
-    let resp = instance.call_migrate(&ctx, msg)?.into_std_result()?;
+    let resp = instance.call_migrate(&ctx, msg)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_migrate_event(
@@ -83,7 +105,14 @@ where
         &account.code_hash,
         resp.attributes,
     )];
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
@@ -54,4 +54,46 @@ impl Storage for PrefixStore {
         let prefixed_key = concat(&self.namespace, key);
         self.store.remove(&prefixed_key);
     }
+
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        let min = match min {
+            Some(bytes) => concat(&self.namespace, bytes),
+            None => self.namespace.to_vec(),
+        };
+        let max = match max {
+            Some(bytes) => concat(&self.namespace, bytes),
+            None => increment_last_byte(self.namespace.to_vec()),
+        };
+        self.store.remove_range(Some(&min), Some(&max));
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, grug_types::MockStorage};
+
+    #[test]
+    fn removing_range_works() {
+        // records right outside of the namespace on either side
+        let mut base = MockStorage::new();
+        base.write(b"fon", b"1");
+        base.write(b"fop", b"2");
+
+        let mut prefixed = PrefixStore::new(Box::new(base), &[b"fo", b"o"]);
+        for key in [b"a", b"b", b"c", b"d"] {
+            prefixed.write(key, key);
+        }
+
+        prefixed.remove_range(Some(b"b"), Some(b"d"));
+        let keys = prefixed.scan(None, None, Order::Ascending).map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys, [b"fooa".to_vec(), b"food".to_vec()]);
+
+        prefixed.remove_range(None, None);
+        assert_eq!(prefixed.scan(None, None, Order::Ascending).next(), None);
+
+        let records = prefixed.store.scan(None, None, Order::Ascending).collect::<Vec<_>>();
+        assert_eq!(records, [(b"fon".to_vec(), b"1".to_vec()), (b"fop".to_vec(), b"2".to_vec())]);
+    }
 }
@@ -0,0 +1,218 @@
+use {
+    crate::{
+        create_vm_instance, load_program, AppError, AppResult, ContractResult, GasTracker, Vm,
+        ACCOUNTS, CHAIN_ID,
+    },
+    grug_types::{
+        Addr, Binary, BlockInfo, Context, PrepareProposalMsg, ProcessProposalMsg, Storage, Uint64,
+        VoteExtension,
+    },
+    tracing::{info, warn},
+};
+
+// ------------------------------ prepare proposal -----------------------------
+
+pub fn do_prepare_proposal<VM>(
+    store:           Box<dyn Storage>,
+    block:           &BlockInfo,
+    gas_tracker:     GasTracker,
+    contract:        &Addr,
+    txs:             Vec<Binary>,
+    max_tx_bytes:    u64,
+    vote_extensions: Vec<VoteExtension>,
+) -> AppResult<Vec<Binary>>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    match _do_prepare_proposal::<VM>(
+        store,
+        block,
+        gas_tracker,
+        contract,
+        txs,
+        max_tx_bytes,
+        vote_extensions,
+    ) {
+        Ok(txs) => {
+            info!(num_txs = txs.len(), "Prepared proposal");
+            Ok(txs)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to prepare proposal");
+            Err(err)
+        },
+    }
+}
+
+fn _do_prepare_proposal<VM>(
+    store:           Box<dyn Storage>,
+    block:           &BlockInfo,
+    gas_tracker:     GasTracker,
+    contract:        &Addr,
+    txs:             Vec<Binary>,
+    max_tx_bytes:    u64,
+    vote_extensions: Vec<VoteExtension>,
+) -> AppResult<Vec<Binary>>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let chain_id = CHAIN_ID.load(&store)?;
+    let account = ACCOUNTS.load(&store, contract)?;
+
+    let program = load_program::<VM>(&store, &account.code_hash)?;
+    let instance = create_vm_instance::<VM>(
+        store,
+        block.clone(),
+        gas_tracker,
+        contract,
+        program,
+    )?;
+
+    // call the proposal handler contract's `prepare_proposal` entry point
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        contract.clone(),
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+    };
+    let msg = PrepareProposalMsg {
+        txs,
+        max_tx_bytes: Uint64::new(max_tx_bytes),
+        vote_extensions,
+    };
+    let txs = instance.call_prepare_proposal(&ctx, &msg)?.into_app_result(&ctx.contract)?;
+
+    Ok(truncate_txs(txs, max_tx_bytes))
+}
+
+// ------------------------------ process proposal -----------------------------
+
+pub fn do_process_proposal<VM>(
+    store:           Box<dyn Storage>,
+    block:           &BlockInfo,
+    gas_tracker:     GasTracker,
+    contract:        &Addr,
+    txs:             Vec<Binary>,
+    vote_extensions: Vec<VoteExtension>,
+) -> AppResult<()>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    match _do_process_proposal::<VM>(store, block, gas_tracker, contract, txs, vote_extensions) {
+        Ok(()) => {
+            info!(height = block.height.u64(), "Accepted proposal");
+            Ok(())
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Rejected proposal");
+            Err(err)
+        },
+    }
+}
+
+fn _do_process_proposal<VM>(
+    store:           Box<dyn Storage>,
+    block:           &BlockInfo,
+    gas_tracker:     GasTracker,
+    contract:        &Addr,
+    txs:             Vec<Binary>,
+    vote_extensions: Vec<VoteExtension>,
+) -> AppResult<()>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let chain_id = CHAIN_ID.load(&store)?;
+    let account = ACCOUNTS.load(&store, contract)?;
+
+    let program = load_program::<VM>(&store, &account.code_hash)?;
+    let instance = create_vm_instance::<VM>(
+        store,
+        block.clone(),
+        gas_tracker,
+        contract,
+        program,
+    )?;
+
+    // call the proposal handler contract's `process_proposal` entry point
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        contract.clone(),
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+    };
+    let msg = ProcessProposalMsg {
+        txs,
+        vote_extensions,
+    };
+    instance.call_process_proposal(&ctx, &msg)?.into_app_result(&ctx.contract)
+}
+
+/// Keep as many transactions as fit within `max_tx_bytes`, in order. CometBFT
+/// refuses proposals in excess of this, so it's enforced regardless of what
+/// the proposal handler returns.
+pub fn truncate_txs(txs: Vec<Binary>, max_tx_bytes: u64) -> Vec<Binary> {
+    let mut total_bytes = 0;
+
+    txs.into_iter()
+        .take_while(|tx| {
+            total_bytes += encoded_tx_size(tx);
+            total_bytes <= max_tx_bytes
+        })
+        .collect()
+}
+
+/// The number of bytes a transaction takes up in a block, as CometBFT counts
+/// them against `max_tx_bytes`: the transaction is encoded as a protobuf field,
+/// which adds a one-byte tag and the varint-encoded length.
+pub fn encoded_tx_size(tx: &[u8]) -> u64 {
+    let len = tx.len() as u64;
+    let len_bytes = (u64::BITS - len.leading_zeros()).max(1).div_ceil(7);
+    1 + len_bytes as u64 + len
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncating_txs_works() {
+        // each tx takes up two more bytes than its length
+        let txs = vec![
+            Binary::from(vec![0; 3]),
+            Binary::from(vec![1; 4]),
+            Binary::from(vec![2; 1]),
+        ];
+
+        // all txs fit
+        assert_eq!(truncate_txs(txs.clone(), 14), txs);
+        assert_eq!(truncate_txs(txs.clone(), 13), txs[..2]);
+
+        // txs are dropped from the end, even if a later one would fit
+        assert_eq!(truncate_txs(txs.clone(), 10), txs[..1]);
+
+        // no tx fits
+        assert!(truncate_txs(txs, 4).is_empty());
+    }
+
+    #[test]
+    fn encoded_tx_size_works() {
+        assert_eq!(encoded_tx_size(&[]), 2);
+        assert_eq!(encoded_tx_size(&[0; 127]), 129);
+        assert_eq!(encoded_tx_size(&[0; 128]), 131);
+        assert_eq!(encoded_tx_size(&[0; 16384]), 16388);
+    }
+}
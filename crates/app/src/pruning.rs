@@ -0,0 +1,70 @@
+/// Determines which historical states a node keeps.
+///
+/// The state at recent heights is kept contiguously, down to an oldest height
+/// to retain, given the latest. Older heights may additionally be kept at a
+/// regular interval.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PruningStrategy {
+    /// Keep the state at every height. This is what archive nodes do.
+    #[default]
+    Nothing,
+    /// Keep the state at the most recent `n` heights.
+    KeepRecent(u64),
+    /// Keep the state at the most recent `keep_recent` heights, as well as at
+    /// every height that is a multiple of `interval`.
+    KeepEvery { interval: u64, keep_recent: u64 },
+}
+
+impl PruningStrategy {
+    /// Return the oldest height whose state should be kept, given the latest
+    /// height. `None` if nothing should be pruned.
+    ///
+    /// The latest state is always kept. Heights older than this may still be
+    /// kept; see [`keep_every`](Self::keep_every).
+    pub fn retain_height(&self, latest_height: u64) -> Option<u64> {
+        match self {
+            PruningStrategy::Nothing => None,
+            PruningStrategy::KeepRecent(n)
+            | PruningStrategy::KeepEvery { keep_recent: n, .. } => {
+                Some(latest_height.saturating_sub(n.saturating_sub(1)))
+            },
+        }
+        .filter(|retain_height| *retain_height > 0)
+    }
+
+    /// Return the interval at which heights older than the retain height are
+    /// kept, if any.
+    pub fn keep_every(&self) -> Option<u64> {
+        match self {
+            PruningStrategy::KeepEvery { interval, .. } => Some(*interval),
+            _ => None,
+        }
+    }
+
+    /// Return whether the state at the given height is kept regardless of the
+    /// retain height.
+    pub fn is_kept_beyond_retain_height(&self, height: u64) -> bool {
+        self.keep_every().is_some_and(|interval| height % interval == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, test_case::test_case};
+
+    #[test_case(PruningStrategy::Nothing, 100 => None; "nothing")]
+    #[test_case(PruningStrategy::KeepRecent(10), 100 => Some(91); "keep recent")]
+    #[test_case(PruningStrategy::KeepRecent(10), 5 => None; "keep recent before enough heights")]
+    #[test_case(PruningStrategy::KeepRecent(1), 100 => Some(100); "keep only latest")]
+    #[test_case(PruningStrategy::KeepEvery { interval: 50, keep_recent: 10 }, 100 => Some(91); "keep every")]
+    fn computing_retain_height(strategy: PruningStrategy, latest_height: u64) -> Option<u64> {
+        strategy.retain_height(latest_height)
+    }
+
+    #[test_case(PruningStrategy::KeepRecent(10), 50 => false; "keep recent")]
+    #[test_case(PruningStrategy::KeepEvery { interval: 50, keep_recent: 10 }, 50 => true; "multiple of interval")]
+    #[test_case(PruningStrategy::KeepEvery { interval: 50, keep_recent: 10 }, 51 => false; "not multiple of interval")]
+    fn keeping_beyond_retain_height(strategy: PruningStrategy, height: u64) -> bool {
+        strategy.is_kept_beyond_retain_height(height)
+    }
+}
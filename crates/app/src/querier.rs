@@ -1,20 +1,22 @@
 use {
-    crate::{process_query, AppError, Vm},
+    crate::{process_query, AppError, GasTracker, Vm},
     grug_types::{BlockInfo, Querier, QueryRequest, QueryResponse, StdError, StdResult, Storage},
     std::marker::PhantomData,
 };
 
 pub struct QueryProvider<VM> {
-    store: Box<dyn Storage>,
-    block: BlockInfo,
-    vm: PhantomData<VM>,
+    store:       Box<dyn Storage>,
+    block:       BlockInfo,
+    gas_tracker: GasTracker,
+    vm:          PhantomData<VM>,
 }
 
 impl<VM> QueryProvider<VM> {
-    pub fn new(store: Box<dyn Storage>, block: BlockInfo) -> Self {
+    pub fn new(store: Box<dyn Storage>, block: BlockInfo, gas_tracker: GasTracker) -> Self {
         Self {
             store,
             block,
+            gas_tracker,
             vm: PhantomData,
         }
     }
@@ -26,7 +28,14 @@ where
     AppError: From<VM::Error>,
 {
     fn query_chain(&self, req: QueryRequest) -> StdResult<QueryResponse> {
-        process_query::<VM>(self.store.clone(), &self.block, req)
-            .map_err(|err| StdError::Generic(err.to_string()))
+        process_query::<VM>(self.store.clone(), &self.block, self.gas_tracker.clone(), req)
+            .map_err(|err| match err {
+                // preserve out of gas errors, so that the calling VM can tell
+                // it apart from other errors
+                AppError::OutOfGas { limit, used, comment } => {
+                    StdError::out_of_gas(limit, used, comment)
+                },
+                err => StdError::Generic(err.to_string()),
+            })
     }
 }
@@ -1,13 +1,14 @@
 use {
     crate::{
-        create_vm_instance, load_program, AppError, AppResult, PrefixStore, Vm, ACCOUNTS, CHAIN_ID,
-        CODES, CONFIG, CONTRACT_NAMESPACE, LAST_FINALIZED_BLOCK,
+        create_vm_instance, load_program, AppError, AppResult, ContractResult, GasTracker,
+        PrefixStore, Vm, ACCOUNTS, CHAIN_ID, CODES, CODE_INFOS, CONFIG, CONSENSUS_PARAMS,
+        CONTRACT_NAMESPACE, LAST_FINALIZED_BLOCK, NEXT_CRONJOBS, NEXT_UPGRADE,
     },
     grug_storage::Bound,
     grug_types::{
-        AccountResponse, Addr, BankQueryMsg, BankQueryResponse, Binary, BlockInfo, Coin, Coins,
-        Context, Hash, InfoResponse, Json, Order, StdResult, Storage, WasmRawResponse,
-        WasmSmartResponse,
+        AccountResponse, Addr, BankQueryMsg, BankQueryResponse, Binary, BlockInfo, CodeInfoResponse,
+        Coin, Coins, Context, CronjobResponse, Hash, InfoResponse, Json, Order, StdResult, Storage,
+        WasmRawResponse, WasmSmartResponse,
     },
 };
 
@@ -17,27 +18,31 @@ pub fn query_info(store: &dyn Storage) -> AppResult<InfoResponse> {
     Ok(InfoResponse {
         chain_id:             CHAIN_ID.load(store)?,
         config:               CONFIG.load(store)?,
+        consensus_params:     CONSENSUS_PARAMS.may_load(store)?.unwrap_or_default(),
         last_finalized_block: LAST_FINALIZED_BLOCK.load(store)?,
+        next_upgrade:         NEXT_UPGRADE.may_load(store)?,
     })
 }
 
 pub fn query_balance<VM>(
-    store:   Box<dyn Storage>,
-    block:   &BlockInfo,
-    address: Addr,
-    denom:   String,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    address:     Addr,
+    denom:       String,
 ) -> AppResult<Coin>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    _query_bank::<VM>(store, block, &BankQueryMsg::Balance { address, denom })
+    _query_bank::<VM>(store, block, gas_tracker, &BankQueryMsg::Balance { address, denom })
         .map(|res| res.as_balance())
 }
 
 pub fn query_balances<VM>(
     store:       Box<dyn Storage>,
     block:       &BlockInfo,
+    gas_tracker: GasTracker,
     address:     Addr,
     start_after: Option<String>,
     limit:       Option<u32>,
@@ -46,26 +51,28 @@ where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    _query_bank::<VM>(store, block, &BankQueryMsg::Balances { address, start_after, limit })
-        .map(|res| res.as_balances())
+    let msg = BankQueryMsg::Balances { address, start_after, limit };
+    _query_bank::<VM>(store, block, gas_tracker, &msg).map(|res| res.as_balances())
 }
 
 pub fn query_supply<VM>(
-    store: Box<dyn Storage>,
-    block: &BlockInfo,
-    denom: String,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    denom:       String,
 ) -> AppResult<Coin>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    _query_bank::<VM>(store, block, &BankQueryMsg::Supply { denom })
+    _query_bank::<VM>(store, block, gas_tracker, &BankQueryMsg::Supply { denom })
         .map(|res| res.as_supply())
 }
 
 pub fn query_supplies<VM>(
     store:       Box<dyn Storage>,
     block:       &BlockInfo,
+    gas_tracker: GasTracker,
     start_after: Option<String>,
     limit:       Option<u32>,
 ) -> AppResult<Coins>
@@ -73,14 +80,15 @@ where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    _query_bank::<VM>(store, block, &BankQueryMsg::Supplies { start_after, limit })
+    _query_bank::<VM>(store, block, gas_tracker, &BankQueryMsg::Supplies { start_after, limit })
         .map(|res| res.as_supplies())
 }
 
 pub fn _query_bank<VM>(
-    store: Box<dyn Storage>,
-    block: &BlockInfo,
-    msg:   &BankQueryMsg,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    msg:         &BankQueryMsg,
 ) -> AppResult<BankQueryResponse>
 where
     VM: Vm + 'static,
@@ -93,7 +101,13 @@ where
 
     // create VM instance
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), &cfg.bank, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        &cfg.bank,
+        program,
+    )?;
 
     // call query
     let ctx = Context {
@@ -106,7 +120,7 @@ where
         funds:           None,
         simulate:        None,
     };
-    instance.call_bank_query(&ctx, msg)?.into_std_result().map_err(Into::into)
+    instance.call_bank_query(&ctx, msg)?.into_app_result(&ctx.contract)
 }
 
 pub fn query_code(store: &dyn Storage, hash: Hash) -> AppResult<Binary> {
@@ -128,6 +142,46 @@ pub fn query_codes(
         .map_err(Into::into)
 }
 
+pub fn query_code_info(store: &dyn Storage, hash: Hash) -> AppResult<CodeInfoResponse> {
+    let info = CODE_INFOS.load(store, &hash)?;
+    Ok(CodeInfoResponse {
+        hash,
+        uploader:               info.uploader,
+        uploaded_at_height:     info.uploaded_at_height,
+        size:                   info.size,
+        instantiate_permission: info.instantiate_permission,
+    })
+}
+
+pub fn query_code_infos(
+    store:       &dyn Storage,
+    start_after: Option<Hash>,
+    limit:       Option<u32>,
+    uploader:    Option<Addr>,
+) -> AppResult<Vec<CodeInfoResponse>> {
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    CODE_INFOS
+        .range(store, start, None, Order::Ascending)
+        .filter(|item| match (item, &uploader) {
+            (Ok((_, info)), Some(uploader)) => info.uploader == uploader,
+            _ => true,
+        })
+        .take(limit as usize)
+        .map(|item| {
+            let (hash, info) = item?;
+            Ok(CodeInfoResponse {
+                hash,
+                uploader:               info.uploader,
+                uploaded_at_height:     info.uploaded_at_height,
+                size:                   info.size,
+                instantiate_permission: info.instantiate_permission,
+            })
+        })
+        .collect()
+}
+
 pub fn query_account(store: &dyn Storage, address: Addr) -> AppResult<AccountResponse> {
     let account = ACCOUNTS.load(store, &address)?;
     Ok(AccountResponse {
@@ -159,6 +213,24 @@ pub fn query_accounts(
         .collect()
 }
 
+pub fn query_next_cronjobs(
+    store:       &dyn Storage,
+    start_after: Option<Addr>,
+    limit:       Option<u32>,
+) -> AppResult<Vec<CronjobResponse>> {
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    NEXT_CRONJOBS
+        .range(store, start, None, Order::Ascending)
+        .take(limit as usize)
+        .map(|item| {
+            let (contract, next_run) = item?;
+            Ok(CronjobResponse { contract, next_run })
+        })
+        .collect()
+}
+
 pub fn query_wasm_raw(
     store:    Box<dyn Storage>,
     contract: Addr,
@@ -174,10 +246,11 @@ pub fn query_wasm_raw(
 }
 
 pub fn query_wasm_smart<VM>(
-    store:    Box<dyn Storage>,
-    block:    &BlockInfo,
-    contract: Addr,
-    msg:      Json,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    Addr,
+    msg:         Json,
 ) -> AppResult<WasmSmartResponse>
 where
     VM: Vm + 'static,
@@ -187,7 +260,13 @@ where
     let account = ACCOUNTS.load(&store, &contract)?;
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store, block.clone(), &contract, program)?;
+    let instance = create_vm_instance::<VM>(
+        store,
+        block.clone(),
+        gas_tracker.clone(),
+        &contract,
+        program,
+    )?;
 
     // call query
     let ctx = Context {
@@ -200,7 +279,7 @@ where
         funds:           None,
         simulate:        None,
     };
-    let data = instance.call_query(&ctx, &msg)?.into_std_result()?;
+    let data = instance.call_query(&ctx, &msg)?.into_app_result(&ctx.contract)?;
 
     Ok(WasmSmartResponse {
         contract: ctx.contract,
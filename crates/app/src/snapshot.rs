@@ -0,0 +1,244 @@
+use {
+    crate::{AppError, AppResult},
+    grug_types::{
+        from_borsh_slice, from_json_slice, hash, to_borsh_vec, to_json_vec, Batch, Hash, Op, Order,
+        Record, Storage,
+    },
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::BTreeSet,
+        fs,
+        path::{Path, PathBuf},
+        thread::JoinHandle,
+    },
+    tracing::info,
+};
+
+/// The snapshot format we produce and accept: the records in the state storage
+/// in ascending order by key, split into chunks, each chunk being the records
+/// encoded as a Borsh `Vec<Record>`.
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// The size, in bytes, above which records are split into a new chunk.
+/// CometBFT limits the size of a chunk to 16 MiB.
+pub const SNAPSHOT_CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
+/// Name of the file, in a snapshot's directory, that contains its metadata.
+const METADATA_FILE_NAME: &str = "metadata.json";
+
+/// A snapshot of the state storage at a given height, for state sync.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub height:       u64,
+    pub format:       u32,
+    /// Hashes of the chunks, in order. A chunk received from a peer is checked
+    /// against its hash, so that a bad chunk can be refetched.
+    pub chunk_hashes: Vec<Hash>,
+}
+
+impl Snapshot {
+    /// The hash that identifies the snapshot, which commits to its chunks.
+    pub fn hash(&self) -> Hash {
+        let bytes = self.chunk_hashes.iter().flat_map(|chunk_hash| chunk_hash.iter().copied());
+        hash(bytes.collect::<Vec<_>>())
+    }
+}
+
+/// Creates snapshots of the state storage at regular intervals, and stores
+/// them on disk, so that they can be served to nodes that are state syncing.
+///
+/// Each snapshot is stored in a directory named after its height, containing
+/// the metadata and one file per chunk.
+pub struct SnapshotStore {
+    dir:         PathBuf,
+    interval:    u64,
+    keep_recent: usize,
+}
+
+impl SnapshotStore {
+    /// Create a snapshot store that takes a snapshot every `interval` blocks,
+    /// and keeps the most recent `keep_recent` of them. Zero for `keep_recent`
+    /// means keeping all snapshots.
+    pub fn new(dir: PathBuf, interval: u64, keep_recent: usize) -> AppResult<Self> {
+        fs::create_dir_all(&dir).map_err(snapshot_error)?;
+
+        Ok(Self {
+            dir,
+            interval: interval.max(1),
+            keep_recent,
+        })
+    }
+
+    /// Whether a snapshot should be taken at the given height.
+    pub fn is_due(&self, height: u64) -> bool {
+        height > 0 && height % self.interval == 0
+    }
+
+    /// Return the snapshots available, the most recent first.
+    pub fn list(&self) -> AppResult<Vec<Snapshot>> {
+        self.heights()?
+            .into_iter()
+            .rev()
+            .map(|height| {
+                let bytes = fs::read(self.snapshot_dir(height).join(METADATA_FILE_NAME))
+                    .map_err(snapshot_error)?;
+                Ok(from_json_slice(bytes)?)
+            })
+            .collect()
+    }
+
+    pub fn load_chunk(&self, height: u64, format: u32, index: u32) -> AppResult<Vec<u8>> {
+        if format != SNAPSHOT_FORMAT {
+            return Err(AppError::unsupported_snapshot_format(format));
+        }
+
+        fs::read(self.snapshot_dir(height).join(index.to_string())).map_err(snapshot_error)
+    }
+
+    /// Take a snapshot of the state storage, which should be that at the given
+    /// height. Snapshots in excess of `keep_recent` are deleted afterwards.
+    pub fn create(&self, height: u64, storage: &dyn Storage) -> AppResult<Snapshot> {
+        // write to a temporary directory first, and move it in place once all
+        // the chunks are written, so that a partial snapshot is never served
+        let tmp_dir = self.dir.join(format!("{height}.tmp"));
+        if tmp_dir.exists() {
+            fs::remove_dir_all(&tmp_dir).map_err(snapshot_error)?;
+        }
+        fs::create_dir_all(&tmp_dir).map_err(snapshot_error)?;
+
+        let mut chunk_hashes = vec![];
+        let mut records: Vec<Record> = vec![];
+        let mut size = 0;
+
+        for (key, value) in storage.scan(None, None, Order::Ascending) {
+            size += key.len() + value.len();
+            records.push((key, value));
+
+            if size >= SNAPSHOT_CHUNK_SIZE {
+                let records = std::mem::take(&mut records);
+                chunk_hashes.push(write_chunk(&tmp_dir, chunk_hashes.len(), records)?);
+                size = 0;
+            }
+        }
+
+        if !records.is_empty() {
+            chunk_hashes.push(write_chunk(&tmp_dir, chunk_hashes.len(), records)?);
+        }
+
+        let snapshot = Snapshot {
+            height,
+            format: SNAPSHOT_FORMAT,
+            chunk_hashes,
+        };
+        fs::write(tmp_dir.join(METADATA_FILE_NAME), to_json_vec(&snapshot)?)
+            .map_err(snapshot_error)?;
+        fs::rename(&tmp_dir, self.snapshot_dir(height)).map_err(snapshot_error)?;
+
+        info!(
+            height,
+            chunks = snapshot.chunk_hashes.len(),
+            hash = snapshot.hash().to_string(),
+            "Created snapshot"
+        );
+
+        self.prune()?;
+
+        Ok(snapshot)
+    }
+
+    fn prune(&self) -> AppResult<()> {
+        if self.keep_recent == 0 {
+            return Ok(());
+        }
+
+        let heights = self.heights()?;
+        let num_to_delete = heights.len().saturating_sub(self.keep_recent);
+
+        for height in heights.into_iter().take(num_to_delete) {
+            fs::remove_dir_all(self.snapshot_dir(height)).map_err(snapshot_error)?;
+            info!(height, "Deleted snapshot");
+        }
+
+        Ok(())
+    }
+
+    // heights of the snapshots that are complete, in ascending order. partial
+    // ones are in directories with a `.tmp` suffix, so they're ignored.
+    fn heights(&self) -> AppResult<Vec<u64>> {
+        let mut heights = fs::read_dir(&self.dir)
+            .map_err(snapshot_error)?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect::<Vec<u64>>();
+        heights.sort();
+        Ok(heights)
+    }
+
+    fn snapshot_dir(&self, height: u64) -> PathBuf {
+        self.dir.join(height.to_string())
+    }
+}
+
+/// A snapshot that is being created in a background thread.
+pub(crate) struct SnapshotTask {
+    pub height: u64,
+    pub handle: JoinHandle<()>,
+}
+
+/// A snapshot that is being restored, and the chunks of it received so far.
+pub(crate) struct SnapshotRestore {
+    pub snapshot: Snapshot,
+    /// The app hash at the snapshot's height, as trusted by CometBFT through
+    /// the light client. The restored state must match it.
+    pub app_hash: Hash,
+    pub received: BTreeSet<u32>,
+    pub batch:    Batch,
+}
+
+impl SnapshotRestore {
+    pub fn new(snapshot: Snapshot, app_hash: Hash) -> Self {
+        Self {
+            snapshot,
+            app_hash,
+            received: BTreeSet::new(),
+            batch: Batch::new(),
+        }
+    }
+
+    /// Verify a chunk against its hash in the snapshot, and add its records to
+    /// the batch to be restored.
+    pub fn apply_chunk(&mut self, index: u32, chunk: &[u8]) -> AppResult<()> {
+        let Some(chunk_hash) = self.snapshot.chunk_hashes.get(index as usize) else {
+            return Err(AppError::snapshot_chunk_mismatch(index));
+        };
+
+        if hash(chunk) != *chunk_hash {
+            return Err(AppError::snapshot_chunk_mismatch(index));
+        }
+
+        // the chunk is what the snapshot's creator produced, so if it can't be
+        // decoded, the snapshot itself is bad
+        let records: Vec<Record> = from_borsh_slice(chunk)
+            .map_err(|err| AppError::malformed_snapshot_chunk(index, err))?;
+        for (key, value) in records {
+            self.batch.insert(key, Op::Insert(value));
+        }
+
+        self.received.insert(index);
+
+        Ok(())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.received.len() == self.snapshot.chunk_hashes.len()
+    }
+}
+
+fn write_chunk(dir: &Path, index: usize, records: Vec<Record>) -> AppResult<Hash> {
+    let chunk = to_borsh_vec(&records)?;
+    fs::write(dir.join(index.to_string()), &chunk).map_err(snapshot_error)?;
+    Ok(hash(chunk))
+}
+
+fn snapshot_error(err: std::io::Error) -> AppError {
+    AppError::Snapshot(err.to_string())
+}
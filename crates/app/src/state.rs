@@ -1,6 +1,9 @@
 use {
     grug_storage::{Item, Map},
-    grug_types::{Account, Addr, BlockInfo, Config, Hash},
+    grug_types::{
+        Account, Addr, BlockInfo, CodeInfo, Config, ConsensusParams, GasCosts, Hash, Timestamp,
+        UpgradePlan, ValidatorUpdate,
+    },
 };
 
 /// A string that identifies the chain
@@ -9,15 +12,60 @@ pub const CHAIN_ID: Item<String> = Item::new("chain_id");
 /// Chain-level configuration
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// A copy of the config's gas costs, kept under its own key so that creating a
+/// VM instance, which happens on every contract call, doesn't have to load the
+/// entire config. Use `save_config` to keep the two in sync.
+pub const GAS_COSTS: Item<GasCosts> = Item::new("gas_costs");
+
+/// A copy of the config's maximum call depth, kept under its own key so that
+/// handling submessages, which happens at every depth of the call stack,
+/// doesn't have to load the entire config. Use `save_config` to keep the two
+/// in sync.
+pub const MAX_CALL_DEPTH: Item<u32> = Item::new("max_call_depth");
+
+/// CometBFT's consensus parameters, as of the last finalized block. Missing in
+/// chains started before they were recorded, in which case they're taken to be
+/// the default, i.e. unknown.
+pub const CONSENSUS_PARAMS: Item<ConsensusParams> = Item::new("consensus_params");
+
+/// Consensus parameter updates to be passed on to CometBFT at the end of the
+/// current block
+pub const PENDING_CONSENSUS_PARAMS: Item<ConsensusParams> = Item::new("pending_consensus_params");
+
+/// CometBFT's validator set, as of the last finalized block. Used to verify
+/// the votes on the previous block that a proposer includes in its proposal:
+/// consensus_address => validator
+pub const CONSENSUS_VALIDATORS: Map<&[u8], ValidatorUpdate> = Map::new("consensus_validator");
+
+/// Validator updates that have been passed on to CometBFT, but haven't taken
+/// effect yet. CometBFT applies the updates returned in block `H` at height
+/// `H + 2`: height => updates
+pub const PENDING_VALIDATOR_UPDATES: Map<u64, Vec<ValidatorUpdate>> =
+    Map::new("pending_validator_update");
+
+/// The chain upgrade scheduled by the owner, if any. Removed once the upgrade
+/// has been applied.
+pub const NEXT_UPGRADE: Item<UpgradePlan> = Item::new("next_upgrade");
+
 /// The most recently finalized block
 pub const LAST_FINALIZED_BLOCK: Item<BlockInfo> = Item::new("last_finalized_block");
 
 /// Wasm contract byte codes: code_hash => byte_code
 pub const CODES: Map<&Hash, Vec<u8>> = Map::new("code");
 
+/// Wasm contract byte code metadata: code_hash => code_info
+pub const CODE_INFOS: Map<&Hash, CodeInfo> = Map::new("code_info");
+
 /// Account metadata: address => account
 pub const ACCOUNTS: Map<&Addr, Account> = Map::new("account");
 
+/// The time at which each cronjob is next due: address => timestamp
+pub const NEXT_CRONJOBS: Map<&Addr, Timestamp> = Map::new("next_cronjob");
+
+/// Number of consecutive times each begin blocker, end blocker, or cronjob has
+/// failed: (address, hook) => count
+pub const BLOCKER_FAILURES: Map<(&Addr, &str), u32> = Map::new("blocker_failure");
+
 /// Each contract has its own storage space, which we term the "substore".
 /// A key in a contract's substore is prefixed by the word "wasm" + contract address.
 pub const CONTRACT_NAMESPACE: &[u8] = b"wasm";
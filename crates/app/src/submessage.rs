@@ -1,12 +1,14 @@
 use {
     crate::{
         create_vm_instance, load_program, new_reply_event, process_msg, AppError, AppResult,
-        CacheStore, SharedStore, Vm, ACCOUNTS, CHAIN_ID,
+        CacheStore, ContractResult, GasTracker, SharedStore, Vm, ACCOUNTS, CHAIN_ID,
+        MAX_CALL_DEPTH,
     },
     grug_types::{
-        Addr, BlockInfo, Context, Event, GenericResult, Json, ReplyOn, Storage, SubMessage,
+        Account, Addr, BlockInfo, Context, Event, GenericResult, Json, ReplyOn, Storage, SubMessage,
         SubMsgResult,
     },
+    std::slice,
     tracing::{info, warn},
 };
 
@@ -39,19 +41,48 @@ pub fn handle_submessages<VM>(
     //
     // Instead, we use the `dyn_clone::DynClone` trait:
     // https://docs.rs/dyn-clone/1.0.16/dyn_clone/
-    store:   Box<dyn Storage>,
-    block:   &BlockInfo,
-    sender:  &Addr,
-    submsgs: Vec<SubMessage>,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    sender:      &Addr,
+    submsgs:     Vec<SubMessage>,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
+    if submsgs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // the sender is on the call stack while its submessages are being handled,
+    // including the replies to them. every layer of submessages or replies
+    // makes the stack one layer deeper, so limiting the stack's depth bounds
+    // the recursion.
+    let max_depth = MAX_CALL_DEPTH.load(&store)?;
+    if call_stack.len() >= max_depth as usize {
+        return Err(AppError::exceed_max_call_depth(max_depth));
+    }
+    let call_stack = [call_stack, slice::from_ref(sender)].concat();
+
     let mut events = vec![];
     for submsg in submsgs {
         let cached = SharedStore::new(CacheStore::new(store.clone(), None));
-        match (submsg.reply_on, process_msg::<VM>(Box::new(cached.share()), block, sender, submsg.msg)) {
+        let submsg_res = process_msg::<VM>(
+            Box::new(cached.share()),
+            block,
+            gas_tracker.clone(),
+            &call_stack,
+            sender,
+            submsg.msg,
+        );
+        match (submsg.reply_on, submsg_res) {
+            // out of gas - abort by throwing error, regardless of whether
+            // callback is requested. there's no gas left to do the callback.
+            (_, Result::Err(err @ AppError::OutOfGas { .. })) => {
+                return Err(err);
+            },
             // success - callback requested
             // flush state changes, log events, give callback
             (ReplyOn::Success(payload) | ReplyOn::Always(payload), Result::Ok(submsg_events)) => {
@@ -60,6 +91,8 @@ where
                 events.extend(do_reply::<VM>(
                     store.clone(),
                     block,
+                    gas_tracker.clone(),
+                    &call_stack,
                     sender,
                     &payload,
                     GenericResult::Ok(submsg_events),
@@ -71,6 +104,8 @@ where
                 events.extend(do_reply::<VM>(
                     store.clone(),
                     block,
+                    gas_tracker.clone(),
+                    &call_stack,
                     sender,
                     &payload,
                     GenericResult::Err(err.to_string()),
@@ -92,18 +127,35 @@ where
     Ok(events)
 }
 
-pub fn do_reply<VM>(
-    store:      Box<dyn Storage>,
-    block:      &BlockInfo,
+/// If the contract has opted in to the reentrancy guard, make sure it isn't
+/// already on the call stack, i.e. the call doesn't originate, directly or
+/// indirectly, from a submessage that the contract itself emitted.
+pub fn assert_not_reentered(
+    call_stack: &[Addr],
     contract:   &Addr,
-    payload:    &Json,
-    submsg_res: SubMsgResult,
+    account:    &Account,
+) -> AppResult<()> {
+    if account.non_reentrant && call_stack.contains(contract) {
+        return Err(AppError::reentrancy(contract.clone()));
+    }
+
+    Ok(())
+}
+
+pub fn do_reply<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    contract:    &Addr,
+    payload:     &Json,
+    submsg_res:  SubMsgResult,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_reply::<VM>(store, block, contract, payload, submsg_res) {
+    match _do_reply::<VM>(store, block, gas_tracker, call_stack, contract, payload, submsg_res) {
         Ok(events) => {
             info!(contract = contract.to_string(), "Performed callback");
             Ok(events)
@@ -116,11 +168,13 @@ where
 }
 
 fn _do_reply<VM>(
-    store:      Box<dyn Storage>,
-    block:      &BlockInfo,
-    contract:   &Addr,
-    payload:    &Json,
-    submsg_res: SubMsgResult,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    contract:    &Addr,
+    payload:     &Json,
+    submsg_res:  SubMsgResult,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
@@ -129,8 +183,16 @@ where
     let chain_id = CHAIN_ID.load(&store)?;
     let account = ACCOUNTS.load(&store, contract)?;
 
+    // note: the contract is always on the call stack when it receives a reply.
+    // this isn't considered reentrancy, so we don't check it here.
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), contract, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        contract,
+        program,
+    )?;
 
     // call reply
     let ctx = Context {
@@ -143,11 +205,18 @@ where
         funds:           None,
         simulate:        None,
     };
-    let resp = instance.call_reply(&ctx, payload, &submsg_res)?.into_std_result()?;
+    let resp = instance.call_reply(&ctx, payload, &submsg_res)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_reply_event(contract, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store, block, contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        contract,
+        resp.submsgs,
+    )?);
 
     Ok(events)
 }
@@ -1,10 +1,11 @@
 use {
-    crate::{PrefixStore, QueryProvider},
+    crate::{GasTracker, PrefixStore, QueryProvider, RemovedRange},
     borsh::{BorshDeserialize, BorshSerialize},
     grug_types::{
-        from_json_slice, to_json_vec, BankQueryMsg, BankQueryResponse, Batch, Context,
-        GenericResult, Hash, IbcClientUpdateMsg, IbcClientVerifyMsg, Json, Response, StdError,
-        Storage, SubMsgResult, TransferMsg, Tx,
+        from_json_slice, to_json_vec, BankQueryMsg, BankQueryResponse, Batch, Binary, Context,
+        GasCosts, GasReport, GenericResult, Hash, IbcClientUpdateMsg, IbcClientVerifyMsg, Json,
+        PrepareProposalMsg, ProcessProposalMsg, Response, StdError, Storage, SubMsgResult,
+        TransferMsg, Tx, ValidatorUpdate, VerifyVoteExtensionMsg, VoteExtension,
     },
     serde::{de::DeserializeOwned, ser::Serialize},
 };
@@ -54,6 +55,13 @@ pub trait Db {
     /// `None` if not a single version has been committed.
     fn latest_version(&self) -> Option<u64>;
 
+    /// Return the oldest version that is still available, i.e. the version up
+    /// to which the DB has been pruned. `None` if the DB has never been pruned,
+    /// in which case all versions are available.
+    ///
+    /// Versions older than this that were kept when pruning are available too.
+    fn oldest_version(&self) -> Option<u64>;
+
     /// Return the Merkle root hash at the specified version.
     /// If version is unspecified, return that of the latest committed version.
     /// `None` if the Merkle tree is empty at that version, or if that version
@@ -70,8 +78,15 @@ pub trait Db {
     /// them in the memory, but do not persist to disk yet; also, increment the
     /// version.
     ///
+    /// The ranges are removed from the state before the ops are applied. This
+    /// allows the DB to remove them natively, rather than record by record.
+    ///
     /// This is typically invoked in the ABCI `FinalizeBlock` call.
-    fn flush_but_not_commit(&self, batch: Batch) -> Result<(u64, Option<Hash>), Self::Error>;
+    fn flush_but_not_commit(
+        &self,
+        removed_ranges: Vec<RemovedRange>,
+        batch: Batch,
+    ) -> Result<(u64, Option<Hash>), Self::Error>;
 
     /// Persist pending data added in the `flush` method to disk.
     ///
@@ -81,11 +96,36 @@ pub trait Db {
     /// Flush and commit in one go.
     ///
     /// This is typically only invoked in the ABCI `InitChain` call.
-    fn flush_and_commit(&self, batch: Batch) -> Result<(u64, Option<Hash>), Self::Error> {
-        let (new_version, root_hash) = self.flush_but_not_commit(batch)?;
+    fn flush_and_commit(
+        &self,
+        removed_ranges: Vec<RemovedRange>,
+        batch: Batch,
+    ) -> Result<(u64, Option<Hash>), Self::Error> {
+        let (new_version, root_hash) = self.flush_but_not_commit(removed_ranges, batch)?;
         self.commit()?;
         Ok((new_version, root_hash))
     }
+
+    /// Accept the complete state storage at the given version, e.g. as
+    /// received from a state sync snapshot, and build the state commitment
+    /// from it. Like `flush_but_not_commit`, keep the data in memory without
+    /// persisting it, and return the new root hash, so that the caller can
+    /// verify it before calling `commit`.
+    ///
+    /// The DB must be empty. Pending data from a previous restore attempt, if
+    /// any, should be discarded.
+    ///
+    /// This is typically invoked in the ABCI `ApplySnapshotChunk` call.
+    fn restore(&self, version: u64, batch: Batch) -> Result<Option<Hash>, Self::Error>;
+
+    /// Delete data that is only needed to access versions older than
+    /// `up_to_version`, i.e. the state storage's older values and the state
+    /// commitment's orphaned nodes. Afterwards, these versions can no longer
+    /// be accessed, except those that are multiples of `keep_every`, if given.
+    ///
+    /// This is typically invoked in the ABCI `Commit` call, according to the
+    /// node's pruning strategy.
+    fn prune(&self, up_to_version: u64, keep_every: Option<u64>) -> Result<(), Self::Error>;
 }
 
 // ------------------------------------ vm -------------------------------------
@@ -100,14 +140,25 @@ pub trait Vm: Sized {
     /// in such a mapping: hash(program) => program.
     type Program: BorshSerialize + BorshDeserialize;
 
-    /// Create an instance of the VM given a storage, a querier, and a guest
-    /// program.
+    /// Create an instance of the VM given a storage, a querier, a gas tracker,
+    /// the gas cost table, and a guest program.
+    ///
+    /// The VM should report the gas consumed by the program to the tracker, and
+    /// abort the execution if the tracker runs out of gas. Calls the program
+    /// makes to host functions should be charged according to the cost table.
     fn build_instance(
-        storage: PrefixStore,
-        querier: QueryProvider<Self>,
-        program: Self::Program,
+        storage:     PrefixStore,
+        querier:     QueryProvider<Self>,
+        gas_tracker: GasTracker,
+        gas_costs:   GasCosts,
+        program:     Self::Program,
     ) -> Result<Self, Self::Error>;
 
+    /// Statically check a program before it's saved to the chain's state, so
+    /// that invalid programs are rejected at upload time rather than when
+    /// they're first called.
+    fn validate_program(program: &Self::Program) -> Result<(), Self::Error>;
+
     // Note: A VM instance is intended to be "single-use", meaning an instance
     // is created, one call to the program is performed, then the instance is
     // dropped. For this reason, see each of the call_* methods below takes a
@@ -198,6 +249,71 @@ pub trait Vm: Sized {
         Ok(from_json_slice(res_bytes)?)
     }
 
+    fn call_cron_execute(self, ctx: &Context) -> Result<GenericResult<Response>, Self::Error> {
+        let res_bytes = self.call_in_0_out_1("cron_execute", ctx)?;
+        Ok(from_json_slice(res_bytes)?)
+    }
+
+    fn call_init_validators(
+        self,
+        ctx: &Context,
+        validators: &[ValidatorUpdate],
+    ) -> Result<GenericResult<Response>, Self::Error> {
+        let res_bytes =
+            self.call_in_1_out_1("init_validators", ctx, to_json_vec(&validators)?)?;
+        Ok(from_json_slice(res_bytes)?)
+    }
+
+    fn call_validator_updates(
+        self,
+        ctx: &Context,
+    ) -> Result<GenericResult<Vec<ValidatorUpdate>>, Self::Error> {
+        let res_bytes = self.call_in_0_out_1("validator_updates", ctx)?;
+        Ok(from_json_slice(res_bytes)?)
+    }
+
+    fn call_prepare_proposal(
+        self,
+        ctx: &Context,
+        msg: &PrepareProposalMsg,
+    ) -> Result<GenericResult<Vec<Binary>>, Self::Error> {
+        let res_bytes = self.call_in_1_out_1("prepare_proposal", ctx, to_json_vec(msg)?)?;
+        Ok(from_json_slice(res_bytes)?)
+    }
+
+    fn call_process_proposal(
+        self,
+        ctx: &Context,
+        msg: &ProcessProposalMsg,
+    ) -> Result<GenericResult<()>, Self::Error> {
+        let res_bytes = self.call_in_1_out_1("process_proposal", ctx, to_json_vec(msg)?)?;
+        Ok(from_json_slice(res_bytes)?)
+    }
+
+    fn call_extend_vote(self, ctx: &Context) -> Result<GenericResult<Binary>, Self::Error> {
+        let res_bytes = self.call_in_0_out_1("extend_vote", ctx)?;
+        Ok(from_json_slice(res_bytes)?)
+    }
+
+    fn call_verify_vote_extension(
+        self,
+        ctx: &Context,
+        msg: &VerifyVoteExtensionMsg,
+    ) -> Result<GenericResult<()>, Self::Error> {
+        let res_bytes = self.call_in_1_out_1("verify_vote_extension", ctx, to_json_vec(msg)?)?;
+        Ok(from_json_slice(res_bytes)?)
+    }
+
+    fn call_receive_vote_extensions(
+        self,
+        ctx: &Context,
+        extensions: &[VoteExtension],
+    ) -> Result<GenericResult<Response>, Self::Error> {
+        let res_bytes =
+            self.call_in_1_out_1("receive_vote_extensions", ctx, to_json_vec(&extensions)?)?;
+        Ok(from_json_slice(res_bytes)?)
+    }
+
     fn call_before_tx(
         self,
         ctx: &Context,
@@ -212,6 +328,15 @@ pub trait Vm: Sized {
         Ok(from_json_slice(res_bytes)?)
     }
 
+    fn call_handle_fee(
+        self,
+        ctx: &Context,
+        report: &GasReport,
+    ) -> Result<GenericResult<Response>, Self::Error> {
+        let res_bytes = self.call_in_1_out_1("handle_fee", ctx, to_json_vec(report)?)?;
+        Ok(from_json_slice(res_bytes)?)
+    }
+
     fn call_bank_transfer(
         self,
         ctx: &Context,
@@ -1,25 +1,29 @@
 use {
     crate::{
-        create_vm_instance, handle_submessages, load_program, new_receive_event,
-        new_transfer_event, AppError, AppResult, Vm, ACCOUNTS, CHAIN_ID, CONFIG,
+        assert_not_reentered, create_vm_instance, handle_submessages, load_program,
+        new_receive_event, new_transfer_event, AppError, AppResult, ContractResult, GasTracker, Vm,
+        ACCOUNTS, CHAIN_ID, CONFIG,
     },
     grug_types::{Addr, BlockInfo, Coins, Context, Event, Storage, TransferMsg},
     tracing::{info, warn},
 };
 
+#[allow(clippy::too_many_arguments)]
 pub fn do_transfer<VM>(
-    store:   Box<dyn Storage>,
-    block:   &BlockInfo,
-    from:    Addr,
-    to:      Addr,
-    coins:   Coins,
-    receive: bool,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    from:        Addr,
+    to:          Addr,
+    coins:       Coins,
+    receive:     bool,
 ) -> AppResult<Vec<Event>>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
-    match _do_transfer::<VM>(store, block, from, to, coins, receive) {
+    match _do_transfer::<VM>(store, block, gas_tracker, call_stack, from, to, coins, receive) {
         Ok((events, msg)) => {
             info!(
                 from  = msg.from.to_string(),
@@ -38,13 +42,16 @@ where
 
 // return the TransferMsg, which includes the sender, receiver, and amount, for
 // purpose of tracing/logging
+#[allow(clippy::too_many_arguments)]
 fn _do_transfer<VM>(
-    store:   Box<dyn Storage>,
-    block:   &BlockInfo,
-    from:    Addr,
-    to:      Addr,
-    coins:   Coins,
-    receive: bool,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    from:        Addr,
+    to:          Addr,
+    coins:       Coins,
+    receive:     bool,
 ) -> AppResult<(Vec<Event>, TransferMsg)>
 where
     VM: Vm + 'static,
@@ -53,9 +60,16 @@ where
     let chain_id = CHAIN_ID.load(&store)?;
     let cfg = CONFIG.load(&store)?;
     let account = ACCOUNTS.load(&store, &cfg.bank)?;
+    assert_not_reentered(call_stack, &cfg.bank, &account)?;
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), &cfg.bank, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        &cfg.bank,
+        program,
+    )?;
 
     // call transfer
     let ctx = Context {
@@ -73,16 +87,23 @@ where
         to,
         coins,
     };
-    let resp = instance.call_bank_transfer(&ctx, &msg)?.into_std_result()?;
+    let resp = instance.call_bank_transfer(&ctx, &msg)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     let mut events = vec![new_transfer_event(&ctx.contract, resp.attributes)];
-    events.extend(handle_submessages::<VM>(store.clone(), block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store.clone(),
+        block,
+        gas_tracker.clone(),
+        call_stack,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     if receive {
         // call the recipient contract's `receive` entry point to inform it of
         // this transfer. we do this when handing the Message::Transfer.
-        _do_receive::<VM>(store, block, msg, events)
+        _do_receive::<VM>(store, block, gas_tracker, call_stack, msg, events)
     } else {
         // do not call the `receive` entry point. we do this when handling
         // Message::Instantiate and Execute.
@@ -91,10 +112,12 @@ where
 }
 
 fn _do_receive<VM>(
-    store:      Box<dyn Storage>,
-    block:      &BlockInfo,
-    msg:        TransferMsg,
-    mut events: Vec<Event>,
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    call_stack:  &[Addr],
+    msg:         TransferMsg,
+    mut events:  Vec<Event>,
 ) -> AppResult<(Vec<Event>, TransferMsg)>
 where
     VM: Vm + 'static,
@@ -102,9 +125,16 @@ where
 {
     let chain_id = CHAIN_ID.load(&store)?;
     let account = ACCOUNTS.load(&store, &msg.to)?;
+    assert_not_reentered(call_stack, &msg.to, &account)?;
 
     let program = load_program::<VM>(&store, &account.code_hash)?;
-    let instance = create_vm_instance::<VM>(store.clone(), block.clone(), &msg.to, program)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        &msg.to,
+        program,
+    )?;
 
     // call the recipient contract's `receive` entry point
     let ctx = Context {
@@ -117,11 +147,18 @@ where
         funds:           Some(msg.coins.clone()),
         simulate:        None,
     };
-    let resp = instance.call_receive(&ctx)?.into_std_result()?;
+    let resp = instance.call_receive(&ctx)?.into_app_result(&ctx.contract)?;
 
     // handle submessages
     events.push(new_receive_event(&msg.to, resp.attributes));
-    events.extend(handle_submessages::<VM>(store, block, &ctx.contract, resp.submsgs)?);
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        call_stack,
+        &ctx.contract,
+        resp.submsgs,
+    )?);
 
     Ok((events, msg))
 }
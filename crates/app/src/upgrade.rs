@@ -0,0 +1,79 @@
+use {
+    crate::{
+        assert_owner, new_schedule_upgrade_event, new_upgrade_event, AppError, AppResult,
+        NEXT_UPGRADE,
+    },
+    grug_types::{Addr, BlockInfo, Event, Storage, UpgradePlan},
+    std::sync::Arc,
+    tracing::{info, warn},
+};
+
+/// A one-shot state migration that a node binary registers, via
+/// `App::with_upgrade_handler`, for an upgrade of the given name. It's run at
+/// the start of the block at the upgrade height, before anything else in the
+/// block.
+pub type UpgradeHandler = Arc<dyn Fn(&mut dyn Storage, &BlockInfo) -> AppResult<()> + Send + Sync>;
+
+pub fn do_schedule_upgrade(
+    store:  &mut dyn Storage,
+    block:  &BlockInfo,
+    sender: &Addr,
+    plan:   UpgradePlan,
+) -> AppResult<Vec<Event>> {
+    let name = plan.name.clone();
+    let height = plan.height.u64();
+
+    match _do_schedule_upgrade(store, block, sender, plan) {
+        Ok(events) => {
+            info!(name, height, "Scheduled upgrade");
+            Ok(events)
+        },
+        Err(err) => {
+            warn!(name, height, err = err.to_string(), "Failed to schedule upgrade");
+            Err(err)
+        },
+    }
+}
+
+fn _do_schedule_upgrade(
+    store:  &mut dyn Storage,
+    block:  &BlockInfo,
+    sender: &Addr,
+    plan:   UpgradePlan,
+) -> AppResult<Vec<Event>> {
+    assert_owner(store, sender)?;
+
+    // the upgrade can't take effect in the block it's scheduled in, as the
+    // block is already being processed by the old binary
+    if plan.height <= block.height {
+        return Err(AppError::upgrade_height_too_low(plan.height.u64(), block.height.u64()));
+    }
+
+    // if another upgrade was already scheduled, it's replaced
+    NEXT_UPGRADE.save(store, &plan)?;
+
+    Ok(vec![new_schedule_upgrade_event(sender, &plan)])
+}
+
+/// Run the handler of the given upgrade, and clear the upgrade from the state,
+/// so that the handler isn't run again.
+pub fn do_upgrade(
+    store:   &mut dyn Storage,
+    block:   &BlockInfo,
+    plan:    UpgradePlan,
+    handler: &UpgradeHandler,
+) -> AppResult<Vec<Event>> {
+    match handler(store, block) {
+        Ok(()) => {
+            info!(name = plan.name, height = plan.height.u64(), "Applied upgrade");
+        },
+        Err(err) => {
+            warn!(name = plan.name, err = err.to_string(), "Failed to apply upgrade");
+            return Err(err);
+        },
+    }
+
+    NEXT_UPGRADE.remove(store);
+
+    Ok(vec![new_upgrade_event(&plan)])
+}
@@ -1,11 +1,26 @@
 use {
-    crate::{has_permission, new_upload_event, AppError, AppResult, CODES, CONFIG},
-    grug_types::{hash, Addr, Event, Hash, Storage},
+    crate::{
+        has_permission, new_upload_event, AppError, AppResult, Vm, CODES, CODE_INFOS, CONFIG,
+    },
+    grug_types::{
+        from_borsh_slice, hash, Addr, BlockInfo, CodeInfo, Event, Hash, Permission, Storage,
+        Uint64,
+    },
     tracing::{info, warn},
 };
 
-pub fn do_upload(store: &mut dyn Storage, uploader: &Addr, code: Vec<u8>) -> AppResult<Vec<Event>> {
-    match _do_upload(store, uploader, code) {
+pub fn do_upload<VM>(
+    store:                  &mut dyn Storage,
+    block:                  &BlockInfo,
+    uploader:               &Addr,
+    code:                   Vec<u8>,
+    instantiate_permission: Option<Permission>,
+) -> AppResult<Vec<Event>>
+where
+    VM: Vm,
+    AppError: From<VM::Error>,
+{
+    match _do_upload::<VM>(store, block, uploader, code, instantiate_permission) {
         Ok((events, code_hash)) => {
             info!(code_hash = code_hash.to_string(), "Stored code");
             Ok(events)
@@ -18,11 +33,17 @@ pub fn do_upload(store: &mut dyn Storage, uploader: &Addr, code: Vec<u8>) -> App
 }
 
 // return the hash of the code that is stored, for purpose of tracing/logging
-fn _do_upload(
-    store: &mut dyn Storage,
-    uploader: &Addr,
-    code: Vec<u8>,
-) -> AppResult<(Vec<Event>, Hash)> {
+fn _do_upload<VM>(
+    store:                  &mut dyn Storage,
+    block:                  &BlockInfo,
+    uploader:               &Addr,
+    code:                   Vec<u8>,
+    instantiate_permission: Option<Permission>,
+) -> AppResult<(Vec<Event>, Hash)>
+where
+    VM: Vm,
+    AppError: From<VM::Error>,
+{
     // make sure the user has permission to store code
     let cfg = CONFIG.load(store)?;
     if !has_permission(&cfg.permissions.upload, cfg.owner.as_ref(), uploader) {
@@ -35,10 +56,19 @@ fn _do_upload(
         return Err(AppError::code_exists(code_hash));
     }
 
-    // TODO: deserialize the code to make sure it's a valid program?
+    // make sure the code is a valid program
+    let program = from_borsh_slice::<VM::Program>(&code)?;
+    VM::validate_program(&program)?;
 
-    // store the code
+    // store the code and its metadata
+    let code_info = CodeInfo {
+        uploader:               uploader.clone(),
+        uploaded_at_height:     block.height,
+        size:                   Uint64::new(code.len() as u64),
+        instantiate_permission: instantiate_permission.unwrap_or(Permission::Everybody),
+    };
     CODES.save(store, &code_hash, &code)?;
+    CODE_INFOS.save(store, &code_hash, &code_info)?;
 
     Ok((vec![new_upload_event(&code_hash, uploader)], code_hash))
 }
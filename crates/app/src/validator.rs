@@ -0,0 +1,204 @@
+use {
+    crate::{
+        create_vm_instance, handle_submessages, load_program, new_init_validators_event,
+        new_validator_updates_event, AppError, AppResult, ContractResult, GasTracker, Vm, ACCOUNTS,
+        CHAIN_ID, CONSENSUS_VALIDATORS,
+    },
+    grug_crypto::ripemd160,
+    grug_types::{
+        hash, Addr, Binary, BlockInfo, Context, Event, StdResult, Storage, ValidatorPubKey,
+        ValidatorUpdate,
+    },
+    tracing::{info, warn},
+};
+
+// ------------------------------ init validators ------------------------------
+
+pub fn do_init_validators<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+    validators:  &[ValidatorUpdate],
+) -> AppResult<Vec<Event>>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    match _do_init_validators::<VM>(store, block, gas_tracker, contract, validators) {
+        Ok(events) => {
+            info!(
+                contract = contract.to_string(),
+                num_validators = validators.len(),
+                "Initialized validator set"
+            );
+            Ok(events)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to initialize validator set");
+            Err(err)
+        },
+    }
+}
+
+fn _do_init_validators<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+    validators:  &[ValidatorUpdate],
+) -> AppResult<Vec<Event>>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let chain_id = CHAIN_ID.load(&store)?;
+    let account = ACCOUNTS.load(&store, contract)?;
+
+    let program = load_program::<VM>(&store, &account.code_hash)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        contract,
+        program,
+    )?;
+
+    // call the validator set contract's `init_validators` entry point
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        contract.clone(),
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+    };
+    let resp = instance
+        .call_init_validators(&ctx, validators)?
+        .into_app_result(&ctx.contract)?;
+
+    // handle submessages
+    let mut events = vec![new_init_validators_event(contract, resp.attributes)];
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        &[],
+        &ctx.contract,
+        resp.submsgs,
+    )?);
+
+    Ok(events)
+}
+
+// ----------------------------- validator updates -----------------------------
+
+/// Call the validator set contract's `validator_updates` entry point, which
+/// returns the changes to the validator set that should take effect, to be
+/// passed on to CometBFT.
+pub fn do_validator_updates<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+) -> AppResult<(Vec<ValidatorUpdate>, Vec<Event>)>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    match _do_validator_updates::<VM>(store, block, gas_tracker, contract) {
+        Ok((updates, events)) => {
+            info!(
+                contract = contract.to_string(),
+                num_updates = updates.len(),
+                "Queried validator updates"
+            );
+            Ok((updates, events))
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to query validator updates");
+            Err(err)
+        },
+    }
+}
+
+fn _do_validator_updates<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+) -> AppResult<(Vec<ValidatorUpdate>, Vec<Event>)>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let chain_id = CHAIN_ID.load(&store)?;
+    let account = ACCOUNTS.load(&store, contract)?;
+
+    let program = load_program::<VM>(&store, &account.code_hash)?;
+    let instance = create_vm_instance::<VM>(
+        store,
+        block.clone(),
+        gas_tracker,
+        contract,
+        program,
+    )?;
+
+    // the contract is given write access to its storage, so that it can clear
+    // the updates it has returned
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        contract.clone(),
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+    };
+    let updates = instance.call_validator_updates(&ctx)?.into_app_result(&ctx.contract)?;
+
+    // CometBFT represents voting power as a signed 64-bit integer
+    if let Some(update) = updates.iter().find(|update| update.power.u64() > i64::MAX as u64) {
+        return Err(AppError::invalid_validator_update(format!(
+            "voting power {} exceeds the maximum of {}",
+            update.power,
+            i64::MAX
+        )));
+    }
+
+    let events = vec![new_validator_updates_event(contract, updates.len())];
+
+    Ok((updates, events))
+}
+
+// ------------------------------- validator set -------------------------------
+
+/// Derive a validator's consensus address from its public key, the same way
+/// CometBFT does. Votes identify the validator by this address.
+pub fn consensus_address(pubkey: &ValidatorPubKey) -> Binary {
+    match pubkey {
+        ValidatorPubKey::Ed25519(bytes) => hash(bytes)[..20].to_vec().into(),
+        ValidatorPubKey::Secp256k1(bytes) => ripemd160(&hash(bytes)).to_vec().into(),
+    }
+}
+
+/// Apply updates to our copy of CometBFT's validator set. A validator whose
+/// power is updated to zero is removed from the set.
+pub fn apply_validator_updates(
+    store:   &mut dyn Storage,
+    updates: &[ValidatorUpdate],
+) -> StdResult<()> {
+    for update in updates {
+        let address = consensus_address(&update.pubkey);
+        if update.power.is_zero() {
+            CONSENSUS_VALIDATORS.remove(store, &address);
+        } else {
+            CONSENSUS_VALIDATORS.save(store, &address, update)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,8 @@
 use {
-    crate::{AppError, AppResult, PrefixStore, QueryProvider, Vm, CODES, CONTRACT_NAMESPACE},
+    crate::{
+        AppError, AppResult, GasTracker, PrefixStore, QueryProvider, Vm, CODES, CONTRACT_NAMESPACE,
+        GAS_COSTS,
+    },
     grug_types::{from_borsh_slice, Addr, BlockInfo, Hash, Storage},
 };
 
@@ -9,16 +12,18 @@ pub fn load_program<VM: Vm>(store: &dyn Storage, code_hash: &Hash) -> AppResult<
 }
 
 pub fn create_vm_instance<VM>(
-    store: Box<dyn Storage>,
-    block: BlockInfo,
-    address: &Addr,
-    program: VM::Program,
+    store:       Box<dyn Storage>,
+    block:       BlockInfo,
+    gas_tracker: GasTracker,
+    address:     &Addr,
+    program:     VM::Program,
 ) -> AppResult<VM>
 where
     VM: Vm + 'static,
     AppError: From<VM::Error>,
 {
+    let gas_costs = GAS_COSTS.load(&store)?;
     let storage = PrefixStore::new(store.clone(), &[CONTRACT_NAMESPACE, address]);
-    let querier = QueryProvider::new(store, block);
-    Ok(VM::build_instance(storage, querier, program)?)
+    let querier = QueryProvider::new(store, block, gas_tracker.clone());
+    Ok(VM::build_instance(storage, querier, gas_tracker, gas_costs, program)?)
 }
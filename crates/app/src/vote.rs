@@ -0,0 +1,407 @@
+use {
+    crate::{
+        create_vm_instance, handle_submessages, load_program, new_receive_vote_extensions_event,
+        AppError, AppResult, ContractResult, GasTracker, Vm, ACCOUNTS, CHAIN_ID,
+        CONSENSUS_PARAMS, CONSENSUS_VALIDATORS,
+    },
+    grug_crypto::{ed25519_verify, secp256k1_verify},
+    grug_types::{
+        hash, Addr, Binary, BlockIdFlag, BlockInfo, Context, Event, ExtendedCommitInfo, Order,
+        StdResult, Storage, ValidatorPubKey, VerifyVoteExtensionMsg, VoteExtension,
+    },
+    prost::encoding::{bytes, encode_varint, sfixed64, string},
+    std::collections::BTreeSet,
+    tracing::{info, warn},
+};
+
+// -------------------------------- extend vote --------------------------------
+
+pub fn do_extend_vote<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+) -> AppResult<Binary>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    match _do_extend_vote::<VM>(store, block, gas_tracker, contract) {
+        Ok(extension) => {
+            info!(size = extension.len(), "Extended vote");
+            Ok(extension)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to extend vote");
+            Err(err)
+        },
+    }
+}
+
+fn _do_extend_vote<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+) -> AppResult<Binary>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let chain_id = CHAIN_ID.load(&store)?;
+    let account = ACCOUNTS.load(&store, contract)?;
+
+    let program = load_program::<VM>(&store, &account.code_hash)?;
+    let instance = create_vm_instance::<VM>(
+        store,
+        block.clone(),
+        gas_tracker,
+        contract,
+        program,
+    )?;
+
+    // call the vote extension handler contract's `extend_vote` entry point
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        contract.clone(),
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+    };
+    instance.call_extend_vote(&ctx)?.into_app_result(&ctx.contract)
+}
+
+// --------------------------- verify vote extension ---------------------------
+
+pub fn do_verify_vote_extension<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+    msg:         VerifyVoteExtensionMsg,
+) -> AppResult<()>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    match _do_verify_vote_extension::<VM>(store, block, gas_tracker, contract, msg) {
+        Ok(()) => {
+            info!("Accepted vote extension");
+            Ok(())
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Rejected vote extension");
+            Err(err)
+        },
+    }
+}
+
+fn _do_verify_vote_extension<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+    msg:         VerifyVoteExtensionMsg,
+) -> AppResult<()>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let chain_id = CHAIN_ID.load(&store)?;
+    let account = ACCOUNTS.load(&store, contract)?;
+
+    let program = load_program::<VM>(&store, &account.code_hash)?;
+    let instance = create_vm_instance::<VM>(
+        store,
+        block.clone(),
+        gas_tracker,
+        contract,
+        program,
+    )?;
+
+    // call the vote extension handler contract's `verify_vote_extension` entry point
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        contract.clone(),
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+    };
+    instance.call_verify_vote_extension(&ctx, &msg)?.into_app_result(&ctx.contract)
+}
+
+// ------------------------------- verify votes --------------------------------
+
+/// Verify the votes for the previous block that the proposer included in its
+/// proposal, and return the vote extensions of the validators that voted for
+/// the block. CometBFT only verifies these in its own commit, so a proposer
+/// could otherwise make up extensions. Mirrors the Cosmos SDK's
+/// `ValidateVoteExtensions`:
+///
+/// - every vote must come from a validator in the set that signed the previous
+///   block, carry that validator's voting power, and appear only once;
+/// - votes for the block must be signed over their extension, while other
+///   votes must have neither an extension nor a signature;
+/// - the votes for the block must represent more than 2/3 of the voting power.
+///
+/// The extensions are not passed to the vote extension handler contract here;
+/// the caller is expected to do that.
+pub fn verify_extended_commit(
+    store:  &dyn Storage,
+    block:  &BlockInfo,
+    commit: &ExtendedCommitInfo,
+) -> AppResult<Vec<VoteExtension>> {
+    let chain_id = CHAIN_ID.load(store)?;
+    let enable_height = CONSENSUS_PARAMS
+        .load(store)?
+        .abci
+        .map(|abci| abci.vote_extensions_enable_height)
+        .unwrap_or(0);
+
+    // the votes being verified are for the previous block
+    let height = block.height.u64().saturating_sub(1);
+
+    // if vote extensions weren't enabled at the previous height, the votes must
+    // not carry any
+    if enable_height <= 0 || (height as i64) < enable_height {
+        if commit
+            .votes
+            .iter()
+            .any(|vote| !vote.extension.is_empty() || !vote.extension_signature.is_empty())
+        {
+            return Err(AppError::invalid_vote_extensions(
+                "vote extensions aren't enabled at the previous height",
+            ));
+        }
+
+        return Ok(vec![]);
+    }
+
+    let total_power = CONSENSUS_VALIDATORS
+        .range(store, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, validator)| validator.power.u64()))
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .sum::<u64>();
+
+    let mut seen = BTreeSet::new();
+    let mut commit_power = 0;
+    let mut extensions = vec![];
+
+    for vote in &commit.votes {
+        let Some(validator) = CONSENSUS_VALIDATORS.may_load(store, &vote.validator)? else {
+            return Err(AppError::invalid_vote_extensions(format!(
+                "validator {} is not in the validator set",
+                vote.validator
+            )));
+        };
+
+        if !seen.insert(vote.validator.clone()) {
+            return Err(AppError::invalid_vote_extensions(format!(
+                "validator {} voted more than once",
+                vote.validator
+            )));
+        }
+
+        if vote.power != validator.power {
+            return Err(AppError::invalid_vote_extensions(format!(
+                "incorrect voting power for validator {}! expecting: {}, actual: {}",
+                vote.validator, validator.power, vote.power
+            )));
+        }
+
+        if vote.block_id_flag != BlockIdFlag::Commit {
+            if !vote.extension.is_empty() || !vote.extension_signature.is_empty() {
+                return Err(AppError::invalid_vote_extensions(format!(
+                    "validator {} didn't vote for the block, but has a vote extension",
+                    vote.validator
+                )));
+            }
+
+            continue;
+        }
+
+        let sign_bytes =
+            vote_extension_sign_bytes(&vote.extension, height, commit.round, &chain_id);
+        if !verify_signature(&validator.pubkey, &sign_bytes, &vote.extension_signature) {
+            return Err(AppError::invalid_vote_extensions(format!(
+                "invalid vote extension signature from validator {}",
+                vote.validator
+            )));
+        }
+
+        commit_power += validator.power.u64();
+        extensions.push(VoteExtension {
+            validator: vote.validator.clone(),
+            power:     vote.power,
+            extension: vote.extension.clone(),
+        });
+    }
+
+    if commit_power <= total_power * 2 / 3 {
+        return Err(AppError::invalid_vote_extensions(format!(
+            "insufficient voting power! total: {total_power}, voted: {commit_power}"
+        )));
+    }
+
+    Ok(extensions)
+}
+
+/// The vote extensions of the validators that voted for the previous block,
+/// without verifying them. Used when finalizing a block, which the network has
+/// already accepted.
+pub fn committed_vote_extensions(commit: ExtendedCommitInfo) -> Vec<VoteExtension> {
+    commit
+        .votes
+        .into_iter()
+        .filter(|vote| vote.block_id_flag == BlockIdFlag::Commit)
+        .map(|vote| VoteExtension {
+            validator: vote.validator,
+            power:     vote.power,
+            extension: vote.extension,
+        })
+        .collect()
+}
+
+/// The bytes a validator signs for its vote extension: CometBFT's
+/// `CanonicalVoteExtension`, protobuf-encoded and length-prefixed.
+pub fn vote_extension_sign_bytes(
+    extension: &[u8],
+    height:    u64,
+    round:     u32,
+    chain_id:  &str,
+) -> Vec<u8> {
+    // fields with default values are omitted, as protobuf does
+    let mut msg = vec![];
+    if !extension.is_empty() {
+        bytes::encode(1, &extension.to_vec(), &mut msg);
+    }
+    if height != 0 {
+        sfixed64::encode(2, &(height as i64), &mut msg);
+    }
+    if round != 0 {
+        sfixed64::encode(3, &(round as i64), &mut msg);
+    }
+    if !chain_id.is_empty() {
+        string::encode(4, &chain_id.to_string(), &mut msg);
+    }
+
+    let mut sign_bytes = vec![];
+    encode_varint(msg.len() as u64, &mut sign_bytes);
+    sign_bytes.extend(msg);
+    sign_bytes
+}
+
+fn verify_signature(pubkey: &ValidatorPubKey, msg: &[u8], sig: &[u8]) -> bool {
+    match pubkey {
+        ValidatorPubKey::Ed25519(pk) => ed25519_verify(msg, sig, pk).is_ok(),
+        // CometBFT's secp256k1 keys sign the SHA-256 hash of the message
+        ValidatorPubKey::Secp256k1(pk) => secp256k1_verify(&hash(msg), sig, pk).is_ok(),
+    }
+}
+
+// -------------------------- receive vote extensions --------------------------
+
+pub fn do_receive_vote_extensions<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+    extensions:  &[VoteExtension],
+) -> AppResult<Vec<Event>>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    match _do_receive_vote_extensions::<VM>(store, block, gas_tracker, contract, extensions) {
+        Ok(events) => {
+            info!(num_extensions = extensions.len(), "Delivered vote extensions");
+            Ok(events)
+        },
+        Err(err) => {
+            warn!(err = err.to_string(), "Failed to deliver vote extensions");
+            Err(err)
+        },
+    }
+}
+
+fn _do_receive_vote_extensions<VM>(
+    store:       Box<dyn Storage>,
+    block:       &BlockInfo,
+    gas_tracker: GasTracker,
+    contract:    &Addr,
+    extensions:  &[VoteExtension],
+) -> AppResult<Vec<Event>>
+where
+    VM: Vm + 'static,
+    AppError: From<VM::Error>,
+{
+    let chain_id = CHAIN_ID.load(&store)?;
+    let account = ACCOUNTS.load(&store, contract)?;
+
+    let program = load_program::<VM>(&store, &account.code_hash)?;
+    let instance = create_vm_instance::<VM>(
+        store.clone(),
+        block.clone(),
+        gas_tracker.clone(),
+        contract,
+        program,
+    )?;
+
+    // call the vote extension handler contract's `receive_vote_extensions` entry point
+    let ctx = Context {
+        chain_id,
+        block_height:    block.height,
+        block_timestamp: block.timestamp,
+        block_hash:      block.hash.clone(),
+        contract:        contract.clone(),
+        sender:          None,
+        funds:           None,
+        simulate:        None,
+    };
+    let resp = instance
+        .call_receive_vote_extensions(&ctx, extensions)?
+        .into_app_result(&ctx.contract)?;
+
+    // handle submessages
+    let mut events = vec![new_receive_vote_extensions_event(contract, resp.attributes)];
+    events.extend(handle_submessages::<VM>(
+        store,
+        block,
+        gas_tracker,
+        &[],
+        &ctx.contract,
+        resp.submsgs,
+    )?);
+
+    Ok(events)
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding_vote_extension_sign_bytes_works() {
+        #[rustfmt::skip]
+        let expected = [
+            17,                                 // length prefix
+            0x0a, 3, b'e', b'x', b't',          // extension
+            0x11, 5, 0, 0, 0, 0, 0, 0, 0,       // height
+            0x22, 1, b'c',                      // chain id; round 0 is omitted
+        ];
+        assert_eq!(vote_extension_sign_bytes(b"ext", 5, 0, "c"), expected);
+
+        // round is encoded as sfixed64 too
+        let sign_bytes = vote_extension_sign_bytes(b"", 5, 2, "c");
+        assert_eq!(sign_bytes[10..19], [0x19, 2, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,51 @@
+use {
+    crate::{CryptoError, CryptoResult},
+    ed25519_dalek::{Signature, Verifier, VerifyingKey},
+};
+
+/// NOTE: Unlike the Secp256k1 and Secp256r1 functions, this function takes
+/// the prehash message, since Ed25519 hashes the message itself as part of the
+/// signing scheme.
+pub fn ed25519_verify(msg: &[u8], sig: &[u8], pk: &[u8]) -> CryptoResult<()> {
+    let Ok(sig) = <[u8; 64]>::try_from(sig) else {
+        return Err(CryptoError::incorrect_length(64, sig.len()));
+    };
+    let Ok(pk) = <[u8; 32]>::try_from(pk) else {
+        return Err(CryptoError::incorrect_length(32, pk.len()));
+    };
+    let vk = VerifyingKey::from_bytes(&pk)?;
+    vk.verify(msg, &Signature::from_bytes(&sig)).map_err(Into::into)
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    // test 2 from RFC 8032, section 7.1
+    const PK: [u8; 32] = hex!("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c");
+    const MSG: [u8; 1] = hex!("72");
+    const SIG: [u8; 64] = hex!(
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da"
+        "085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+    );
+
+    #[test]
+    fn verifying_ed25519() {
+        // valid signature
+        assert!(ed25519_verify(&MSG, &SIG, &PK).is_ok());
+
+        // incorrect message
+        assert!(ed25519_verify(b"Larry", &SIG, &PK).is_err());
+
+        // incorrect signature
+        let mut false_sig = SIG;
+        false_sig[0] ^= 1;
+        assert!(ed25519_verify(&MSG, &false_sig, &PK).is_err());
+
+        // signature or public key of incorrect length
+        assert!(ed25519_verify(&MSG, &SIG[..63], &PK).is_err());
+        assert!(ed25519_verify(&MSG, &SIG, &PK[..31]).is_err());
+    }
+}
@@ -1,11 +1,15 @@
+mod ed25519;
 mod error;
 mod identity_digest;
+mod ripemd160;
 mod secp256k1;
 mod secp256r1;
 
 pub use crate::{
+    ed25519::ed25519_verify,
     error::{CryptoError, CryptoResult},
     identity_digest::Identity256,
+    ripemd160::ripemd160,
     secp256k1::secp256k1_verify,
     secp256r1::secp256r1_verify,
 };
@@ -0,0 +1,20 @@
+use ripemd::{Digest, Ripemd160};
+
+/// Hash the data with RIPEMD-160. This is used in deriving the CometBFT
+/// consensus address of a Secp256k1 validator key.
+pub fn ripemd160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(data).into()
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    #[test]
+    fn hashing_ripemd160() {
+        assert_eq!(ripemd160(b""), hex!("9c1185a5c5e9fc54612808977ee8f548b2258d31"));
+        assert_eq!(ripemd160(b"abc"), hex!("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"));
+    }
+}
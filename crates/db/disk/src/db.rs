@@ -1,6 +1,6 @@
 use {
     crate::{DbError, DbResult, U64Comparator, U64Timestamp},
-    grug_app::{CacheStore, Db},
+    grug_app::{CacheStore, Db, RemovedRange},
     grug_jmt::{MerkleTree, Proof},
     grug_types::{hash, Batch, Hash, Op, Order, Record, Storage},
     rocksdb::{
@@ -13,9 +13,10 @@ use {
     },
 };
 
-/// We use three column families (CFs) for storing data.
-/// The default family is used for metadata. Currently the only metadata we have
-/// is the latest version.
+/// We use four column families (CFs) for storing data.
+/// The default family is used for metadata. Currently the metadata we have are
+/// the latest version, the oldest version that hasn't been pruned, and the
+/// interval at which versions are archived, if any.
 const CF_NAME_DEFAULT: &str = "default";
 
 /// The state commitment (SC) family stores Merkle tree nodes, which hold hashed
@@ -35,9 +36,26 @@ const CF_NAME_STATE_COMMITMENT: &str = "state_commitment";
 /// https://github.com/left-curve/rust-rocksdb/tree/v0.21.0-cw
 const CF_NAME_STATE_STORAGE: &str = "state_storage";
 
+/// The archive family keeps a copy of the state storage at every version that
+/// is a multiple of an interval, if the DB is opened with one. This is needed
+/// because when pruning, RocksDB can only drop the values older than a given
+/// timestamp, not those at individual timestamps.
+///
+/// Each write is timestamped with the next multiple of the interval, so reading
+/// at a multiple gives the state at that version.
+const CF_NAME_ARCHIVE: &str = "archive";
+
 /// Storage key for the latest version.
 const LATEST_VERSION_KEY: &[u8] = b"latest_version";
 
+/// Storage key for the oldest version, i.e. the one up to which the DB has been
+/// pruned. Not set if the DB has never been pruned.
+const OLDEST_VERSION_KEY: &[u8] = b"oldest_version";
+
+/// Storage key for the interval at which versions are archived. Not set if the
+/// DB doesn't archive any.
+const ARCHIVE_INTERVAL_KEY: &[u8] = b"archive_interval";
+
 /// Jellyfish Merkle tree (JMT) using default namespaces.
 const MERKLE_TREE: MerkleTree = MerkleTree::new_default();
 
@@ -74,6 +92,8 @@ pub struct DiskDb {
 
 struct DiskDbInner {
     db: DBWithThreadMode<MultiThreaded>,
+    // the interval at which versions are archived, if any
+    archive_interval: Option<u64>,
     // data that are ready to be persisted to the physical database.
     // ideally we want to just use a rocksdb::WriteBatch here, but it's not
     // thread-safe.
@@ -83,14 +103,35 @@ struct DiskDbInner {
 pub(crate) struct PendingData {
     version:          u64,
     state_commitment: Batch,
+    // ranges to be removed from state storage before the batch is written, as
+    // the minimum (inclusive) and maximum (exclusive) keys
+    removed_ranges:   Vec<(Vec<u8>, Vec<u8>)>,
     state_storage:    Batch,
 }
 
 impl DiskDb {
     /// Create a DiskDb instance by opening a physical RocksDB instance.
+    ///
+    /// If the DB has been archiving versions, it keeps doing so.
     pub fn open(data_dir: impl AsRef<Path>) -> DbResult<Self> {
+        Self::open_with_archive_interval(data_dir, None)
+    }
+
+    /// Create a DiskDb instance that archives the state storage at every
+    /// version that is a multiple of `interval`, so that these versions can be
+    /// kept when pruning.
+    ///
+    /// The DB must have been archiving at the same interval since it's created.
+    pub fn open_with_archive(data_dir: impl AsRef<Path>, interval: u64) -> DbResult<Self> {
+        Self::open_with_archive_interval(data_dir, Some(interval))
+    }
+
+    fn open_with_archive_interval(
+        data_dir:         impl AsRef<Path>,
+        archive_interval: Option<u64>,
+    ) -> DbResult<Self> {
         // note: for default and state commitment CFs, don't enable timestamping;
-        // for state storage column family, enable timestamping.
+        // for state storage and archive column families, enable timestamping.
         let db = DBWithThreadMode::open_cf_with_opts(
             &new_db_options(),
             data_dir,
@@ -98,12 +139,36 @@ impl DiskDb {
                 (CF_NAME_DEFAULT, Options::default()),
                 (CF_NAME_STATE_COMMITMENT, Options::default()),
                 (CF_NAME_STATE_STORAGE, new_cf_options_with_ts()),
+                (CF_NAME_ARCHIVE, new_cf_options_with_ts()),
             ],
         )?;
 
+        // an empty DB takes the given interval. otherwise, it keeps archiving
+        // at the interval it has been, if any. a different one can't be given,
+        // or versions would be missing from the archive.
+        let archive_interval = if read_version(&db, LATEST_VERSION_KEY).is_none() {
+            let cf = cf_default(&db);
+            if let Some(interval) = archive_interval {
+                db.put_cf(&cf, ARCHIVE_INTERVAL_KEY, interval.to_le_bytes())?;
+            } else {
+                db.delete_cf(&cf, ARCHIVE_INTERVAL_KEY)?;
+            }
+            archive_interval
+        } else {
+            let stored = read_version(&db, ARCHIVE_INTERVAL_KEY);
+            if archive_interval.is_some() && archive_interval != stored {
+                return Err(DbError::ArchiveIntervalMismatch {
+                    stored,
+                    given: archive_interval,
+                });
+            }
+            stored
+        };
+
         Ok(Self {
             inner: Arc::new(DiskDbInner {
                 db,
+                archive_interval,
                 pending_data: RwLock::new(None),
             }),
         })
@@ -129,21 +194,26 @@ impl Db for DiskDb {
     }
 
     fn state_storage(&self, version: Option<u64>) -> impl Storage + Clone + 'static {
+        let version = version.unwrap_or_else(|| self.latest_version().unwrap_or(0));
+
+        // versions that have been pruned from the state storage are read from
+        // the archive, if they're archived
+        let archived = self.oldest_version().is_some_and(|oldest| version < oldest)
+            && self.inner.archive_interval.is_some_and(|interval| version % interval == 0);
+
         StateStorage {
             inner: Arc::clone(&self.inner),
-            version: version.unwrap_or_else(|| self.latest_version().unwrap_or(0)),
+            version,
+            archived,
         }
     }
 
     fn latest_version(&self) -> Option<u64> {
-        let cf = cf_default(&self.inner.db);
-        let bytes = self.inner.db.get_cf(&cf, LATEST_VERSION_KEY).unwrap_or_else(|err| {
-            panic!("failed to read from default column family: {err}");
-        })?;
-        let array = bytes.try_into().unwrap_or_else(|bytes: Vec<u8>| {
-            panic!("latest version is of incorrect byte length: {}", bytes.len());
-        });
-        Some(u64::from_le_bytes(array))
+        read_version(&self.inner.db, LATEST_VERSION_KEY)
+    }
+
+    fn oldest_version(&self) -> Option<u64> {
+        read_version(&self.inner.db, OLDEST_VERSION_KEY)
     }
 
     fn root_hash(&self, version: Option<u64>) -> DbResult<Option<Hash>> {
@@ -156,7 +226,11 @@ impl Db for DiskDb {
         Ok(MERKLE_TREE.prove(&self.state_commitment(), &hash(key), version)?)
     }
 
-    fn flush_but_not_commit(&self, batch: Batch) -> DbResult<(u64, Option<Hash>)> {
+    fn flush_but_not_commit(
+        &self,
+        removed_ranges: Vec<RemovedRange>,
+        batch: Batch,
+    ) -> DbResult<(u64, Option<Hash>)> {
         // a write batch must not already exist. if it does, it means a batch
         // has been flushed, but not committed, then a next batch is flusehd,
         // which indicates some error in the ABCI app's logic.
@@ -175,15 +249,45 @@ impl Db for DiskDb {
             None => (0, 0),
         };
 
+        // the Merkle tree needs to know every key that's deleted, so the records
+        // in the removed ranges are added to the batch it's applied with as
+        // deletions. a pending op on a key in a range takes precedence, as it's
+        // made after the range is removed.
+        //
+        // the state storage removes the ranges natively instead. RocksDB needs
+        // both bounds, so an unbounded range is bounded right after its last key.
+        let state_storage = self.state_storage(None);
+        let mut merkle_batch = batch.clone();
+        let mut bounded_ranges = Vec::with_capacity(removed_ranges.len());
+        for (min, max) in removed_ranges {
+            let mut last_key = None;
+            for (key, _) in state_storage.scan(min.as_deref(), max.as_deref(), Order::Ascending) {
+                merkle_batch.entry(key.clone()).or_insert(Op::Delete);
+                last_key = Some(key);
+            }
+
+            // nothing to remove in this range
+            let Some(mut last_key) = last_key else {
+                continue;
+            };
+
+            let max = max.unwrap_or_else(|| {
+                last_key.push(0);
+                last_key
+            });
+            bounded_ranges.push((min.unwrap_or_default(), max));
+        }
+
         // commit hashed KVs to state commitment
         // the DB writes here are kept in the in-memory PendingData
         let mut cache = CacheStore::new(self.state_commitment(), None);
-        let root_hash = MERKLE_TREE.apply_raw(&mut cache, old_version, new_version, &batch)?;
+        let root_hash = MERKLE_TREE.apply_raw(&mut cache, old_version, new_version, &merkle_batch)?;
         let (_, pending) = cache.disassemble();
 
         *(self.inner.pending_data.write()?) = Some(PendingData {
             version:          new_version,
             state_commitment: pending,
+            removed_ranges:   bounded_ranges,
             state_storage:    batch,
         });
 
@@ -208,10 +312,14 @@ impl Db for DiskDb {
             }
         }
 
-        // writes in state storage (note: don't forget timestamping)
+        // writes in state storage (note: don't forget timestamping). the ranges
+        // are removed first, so that the writes in them take precedence.
         let cf = cf_state_storage(&self.inner.db);
         let ts = U64Timestamp::from(pending.version);
-        for (key, op) in pending.state_storage {
+        for (min, max) in &pending.removed_ranges {
+            batch.delete_range_cf_with_ts(&cf, min, max, ts);
+        }
+        for (key, op) in &pending.state_storage {
             if let Op::Insert(value) = op {
                 batch.put_cf_with_ts(&cf, key, ts, value);
             } else {
@@ -219,8 +327,100 @@ impl Db for DiskDb {
             }
         }
 
+        // writes in archive, timestamped with the next archived version
+        if let Some(interval) = self.inner.archive_interval {
+            let cf = cf_archive(&self.inner.db);
+            let ts = U64Timestamp::from(pending.version.div_ceil(interval) * interval);
+            for (min, max) in &pending.removed_ranges {
+                batch.delete_range_cf_with_ts(&cf, min, max, ts);
+            }
+            for (key, op) in pending.state_storage {
+                if let Op::Insert(value) = op {
+                    batch.put_cf_with_ts(&cf, key, ts, value);
+                } else {
+                    batch.delete_cf_with_ts(&cf, key, ts);
+                }
+            }
+        }
+
         Ok(self.inner.db.write(batch)?)
     }
+
+    fn restore(&self, version: u64, batch: Batch) -> DbResult<Option<Hash>> {
+        if self.latest_version().is_some() {
+            return Err(DbError::NotEmpty);
+        }
+
+        // the tree is empty, so the old version doesn't matter. the tree is
+        // built from scratch at the new version.
+        let mut cache = CacheStore::new(self.state_commitment(), None);
+        let root_hash = MERKLE_TREE.apply_raw(&mut cache, 0, version, &batch)?;
+        let (_, pending) = cache.disassemble();
+
+        // overwrite the pending data of a previous restore attempt, if any.
+        // once committed, the latest version is set to the snapshot's version,
+        // and the state storage is timestamped with it.
+        *(self.inner.pending_data.write()?) = Some(PendingData {
+            version,
+            state_commitment: pending,
+            removed_ranges:   vec![],
+            state_storage:    batch,
+        });
+
+        Ok(root_hash)
+    }
+
+    fn prune(&self, up_to_version: u64, keep_every: Option<u64>) -> DbResult<()> {
+        // versions can only be kept if they're archived
+        if let Some(interval) = keep_every {
+            if self.inner.archive_interval != Some(interval) {
+                return Err(DbError::ArchiveIntervalMismatch {
+                    stored: self.inner.archive_interval,
+                    given:  keep_every,
+                });
+            }
+        }
+
+        let mut batch = WriteBatch::default();
+
+        // record the version pruned up to (note: use little endian)
+        let cf = cf_default(&self.inner.db);
+        batch.put_cf(&cf, OLDEST_VERSION_KEY, up_to_version.to_le_bytes());
+
+        // delete the orphaned Merkle tree nodes from state commitment. the
+        // orphan records are removed as a range, which RocksDB does without
+        // having to iterate them.
+        let mut cache = CacheStore::new(self.state_commitment(), None);
+        MERKLE_TREE.prune(&mut cache, Some(up_to_version), keep_every)?;
+        let (_, removed_ranges, pending) = cache.disassemble_with_ranges();
+
+        let cf = cf_state_commitment(&self.inner.db);
+        for (min, max) in removed_ranges {
+            // RocksDB requires both bounds. the Merkle tree only removes ranges
+            // within a namespace, which are always bounded.
+            let (Some(min), Some(max)) = (min, max) else {
+                unreachable!("unbounded range removed from state commitment");
+            };
+            batch.delete_range_cf(&cf, min, max);
+        }
+        for (key, op) in pending {
+            if let Op::Insert(value) = op {
+                batch.put_cf(&cf, key, value);
+            } else {
+                batch.delete_cf(&cf, key);
+            }
+        }
+
+        self.inner.db.write(batch)?;
+
+        // for state storage, let RocksDB know it no longer needs to keep values
+        // older than this timestamp. they're deleted during compaction. the
+        // versions being kept are read from the archive instead.
+        let cf = cf_state_storage(&self.inner.db);
+        self.inner.db.increase_full_history_ts_low(&cf, U64Timestamp::from(up_to_version))?;
+
+        Ok(())
+    }
 }
 
 // ----------------------------- state commitment ------------------------------
@@ -244,13 +444,26 @@ impl Storage for StateCommitment {
         })
     }
 
+    // this is used by the Merkle tree to iterate orphaned nodes when pruning
     fn scan<'a>(
         &'a self,
-        _min:   Option<&[u8]>,
-        _max:   Option<&[u8]>,
-        _order: Order,
+        min:   Option<&[u8]>,
+        max:   Option<&[u8]>,
+        order: Order,
     ) -> Box<dyn Iterator<Item = Record> + 'a> {
-        unimplemented!("this isn't used by the Merkle tree");
+        let opts = new_read_options(None, min, max);
+        let mode = match order {
+            Order::Ascending => IteratorMode::Start,
+            Order::Descending => IteratorMode::End,
+        };
+        let cf = cf_state_commitment(&self.inner.db);
+        let iter = self.inner.db.iterator_cf_opt(&cf, opts, mode).map(|item| {
+            let (k, v) = item.unwrap_or_else(|err| {
+                panic!("failed to iterate in state commitment: {err}");
+            });
+            (k.to_vec(), v.to_vec())
+        });
+        Box::new(iter)
     }
 
     fn write(&mut self, _key: &[u8], _value: &[u8]) {
@@ -260,6 +473,10 @@ impl Storage for StateCommitment {
     fn remove(&mut self, _key: &[u8]) {
         unreachable!("write function called on read-only storage");
     }
+
+    fn remove_range(&mut self, _min: Option<&[u8]>, _max: Option<&[u8]>) {
+        unreachable!("write function called on read-only storage");
+    }
 }
 
 // ------------------------------- state storage -------------------------------
@@ -268,12 +485,24 @@ impl Storage for StateCommitment {
 pub struct StateStorage {
     inner: Arc<DiskDbInner>,
     version: u64,
+    // whether to read from the archive instead of the state storage
+    archived: bool,
+}
+
+impl StateStorage {
+    fn cf(&self) -> Arc<BoundColumnFamily> {
+        if self.archived {
+            cf_archive(&self.inner.db)
+        } else {
+            cf_state_storage(&self.inner.db)
+        }
+    }
 }
 
 impl Storage for StateStorage {
     fn read(&self, key: &[u8]) -> Option<Vec<u8>> {
         let opts = new_read_options(Some(self.version), None, None);
-        self.inner.db.get_cf_opt(&cf_state_storage(&self.inner.db), key, &opts).unwrap_or_else(|err| {
+        self.inner.db.get_cf_opt(&self.cf(), key, &opts).unwrap_or_else(|err| {
             panic!("failed to read from state storage: {err}");
         })
     }
@@ -289,7 +518,7 @@ impl Storage for StateStorage {
             Order::Ascending => IteratorMode::Start,
             Order::Descending => IteratorMode::End,
         };
-        let iter = self.inner.db.iterator_cf_opt(&cf_state_storage(&self.inner.db), opts, mode).map(|item| {
+        let iter = self.inner.db.iterator_cf_opt(&self.cf(), opts, mode).map(|item| {
             let (k, v) = item.unwrap_or_else(|err| {
                 panic!("failed to iterate in state storage: {err}");
             });
@@ -306,6 +535,10 @@ impl Storage for StateStorage {
         unreachable!("write function called on read-only storage");
     }
 
+    fn remove_range(&mut self, _min: Option<&[u8]>, _max: Option<&[u8]>) {
+        unreachable!("write function called on read-only storage");
+    }
+
     fn flush(&mut self, _batch: Batch) {
         unreachable!("write function called on read-only storage");
     }
@@ -354,6 +587,16 @@ fn new_read_options(
     opts
 }
 
+fn read_version(db: &DBWithThreadMode<MultiThreaded>, key: &[u8]) -> Option<u64> {
+    let bytes = db.get_cf(&cf_default(db), key).unwrap_or_else(|err| {
+        panic!("failed to read from default column family: {err}");
+    })?;
+    let array = bytes.try_into().unwrap_or_else(|bytes: Vec<u8>| {
+        panic!("version is of incorrect byte length: {}", bytes.len());
+    });
+    Some(u64::from_le_bytes(array))
+}
+
 fn cf_default(db: &DBWithThreadMode<MultiThreaded>) -> Arc<BoundColumnFamily> {
     db.cf_handle(CF_NAME_DEFAULT).unwrap_or_else(|| {
         panic!("failed to find default column family");
@@ -372,6 +615,12 @@ fn cf_state_commitment(db: &DBWithThreadMode<MultiThreaded>) -> Arc<BoundColumnF
     })
 }
 
+fn cf_archive(db: &DBWithThreadMode<MultiThreaded>) -> Arc<BoundColumnFamily> {
+    db.cf_handle(CF_NAME_ARCHIVE).unwrap_or_else(|| {
+        panic!("failed to find archive column family");
+    })
+}
+
 // ----------------------------------- test ------------------------------------
 
 #[cfg(test)]
@@ -511,7 +760,7 @@ mod tests {
             (b"joe".to_vec(),    Op::Insert(b"biden".to_vec())),
             (b"larry".to_vec(),  Op::Insert(b"engineer".to_vec())),
         ]);
-        let (version, root_hash) = store.flush_and_commit(batch).unwrap();
+        let (version, root_hash) = store.flush_and_commit(vec![], batch).unwrap();
         assert_eq!(version, 0);
         assert_eq!(root_hash, Some(v0::ROOT_HASH));
 
@@ -521,7 +770,7 @@ mod tests {
             (b"joe".to_vec(),     Op::Delete),
             (b"pumpkin".to_vec(), Op::Insert(b"cat".to_vec())),
         ]);
-        let (version, root_hash) = store.flush_and_commit(batch).unwrap();
+        let (version, root_hash) = store.flush_and_commit(vec![], batch).unwrap();
         assert_eq!(version, 1);
         assert_eq!(root_hash, Some(v1::ROOT_HASH));
 
@@ -652,4 +901,87 @@ mod tests {
             .is_ok());
         }
     }
+
+    #[test]
+    fn pruning_keeps_archived_versions() {
+        let path = TempDataDir::new("_grug_db_pruning_keeps_archived_versions");
+        let store = DiskDb::open_with_archive(&path, 2).unwrap();
+
+        // versions 0 to 3, each overwriting the same key
+        let mut root_hashes = vec![];
+        for value in ["zero", "one", "two", "three"] {
+            let batch = Batch::from([(b"larry".to_vec(), Op::Insert(value.as_bytes().to_vec()))]);
+            let (_, root_hash) = store.flush_and_commit(vec![], batch).unwrap();
+            root_hashes.push(root_hash);
+        }
+
+        store.prune(3, Some(2)).unwrap();
+        assert_eq!(store.oldest_version(), Some(3));
+
+        // versions 0 and 2 are kept, as multiples of the interval, and so is 3
+        for (version, value) in [(0, "zero"), (2, "two"), (3, "three")] {
+            let found_value = store.state_storage(Some(version)).read(b"larry");
+            assert_eq!(found_value.as_deref(), Some(value.as_bytes()));
+            assert_eq!(store.root_hash(Some(version)).unwrap(), root_hashes[version as usize]);
+        }
+
+        // version 1 isn't
+        assert_eq!(store.root_hash(Some(1)).unwrap(), None);
+
+        // reopening keeps the interval, but a different one is rejected
+        drop(store);
+        assert!(matches!(
+            DiskDb::open_with_archive(&path, 3),
+            Err(DbError::ArchiveIntervalMismatch { .. })
+        ));
+        let store = DiskDb::open(&path).unwrap();
+        let found_value = store.state_storage(Some(2)).read(b"larry");
+        assert_eq!(found_value.as_deref(), Some(b"two".as_slice()));
+    }
+
+    #[test]
+    fn removing_ranges_works() {
+        let path = TempDataDir::new("_grug_db_removing_ranges_works");
+        let store = DiskDb::open(&path).unwrap();
+
+        let batch = Batch::from([
+            (b"donald".to_vec(), Op::Insert(b"trump".to_vec())),
+            (b"jake".to_vec(),   Op::Insert(b"shepherd".to_vec())),
+            (b"joe".to_vec(),    Op::Insert(b"biden".to_vec())),
+            (b"larry".to_vec(),  Op::Insert(b"engineer".to_vec())),
+        ]);
+        store.flush_and_commit(vec![], batch).unwrap();
+
+        // remove everything from "jake" onwards, but write "larry" again
+        let removed_ranges = vec![(Some(b"jake".to_vec()), None)];
+        let batch = Batch::from([(b"larry".to_vec(), Op::Insert(b"founder".to_vec()))]);
+        store.flush_and_commit(removed_ranges, batch).unwrap();
+
+        for (version, items) in [
+            (0, vec![
+                ("donald", "trump"),
+                ("jake",   "shepherd"),
+                ("joe",    "biden"),
+                ("larry",  "engineer"),
+            ]),
+            (1, vec![
+                ("donald", "trump"),
+                ("larry",  "founder"),
+            ]),
+        ] {
+            let found_items = store
+                .state_storage(Some(version))
+                .scan(None, None, Order::Ascending)
+                .collect::<Vec<_>>();
+            let items = items
+                .into_iter()
+                .map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec()))
+                .collect::<Vec<_>>();
+            assert_eq!(found_items, items);
+        }
+
+        // the Merkle tree no longer contains the removed keys either
+        assert!(matches!(store.prove(b"jake", Some(1)).unwrap(), Proof::NonMembership(_)));
+        assert!(matches!(store.prove(b"joe", Some(1)).unwrap(), Proof::NonMembership(_)));
+    }
 }
@@ -22,6 +22,15 @@ pub enum DbError {
 
     #[error("rwlock for the write batch is poisoned")]
     PendingDataPoisoned,
+
+    #[error("cannot restore a snapshot when the DB is not empty")]
+    NotEmpty,
+
+    #[error("archive interval mismatch! stored: {stored:?}, given: {given:?}")]
+    ArchiveIntervalMismatch {
+        stored: Option<u64>,
+        given:  Option<u64>,
+    },
 }
 
 impl<'a> From<PoisonError<RwLockReadGuard<'a, Option<PendingData>>>> for DbError {
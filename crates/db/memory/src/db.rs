@@ -1,10 +1,10 @@
 use {
     crate::{DbError, DbResult, VersionedMap},
-    grug_app::{CacheStore, Db},
+    grug_app::{CacheStore, Db, RemovedRange},
     grug_jmt::{MerkleTree, Proof},
     grug_types::{hash, Batch, Hash, Op, Order, Record, Storage},
     std::{
-        collections::HashMap,
+        collections::BTreeMap,
         ops::Bound,
         sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
     },
@@ -25,9 +25,9 @@ struct MemDbInner {
     latest_version: Option<u64>,
     /// A key-value store backing the Merkle tree.
     ///
-    /// A HashMap is chosen over BTreeMap because our Merkle tree implementation
-    /// does not need to iterate raw keys in this store.
-    state_commitment: HashMap<Vec<u8>, Vec<u8>>,
+    /// A BTreeMap is needed, as the Merkle tree iterates the orphaned nodes in
+    /// this store when pruning.
+    state_commitment: BTreeMap<Vec<u8>, Vec<u8>>,
     /// A versioned key-value store: key => (version => value)
     state_storage: VersionedMap<Vec<u8>, Vec<u8>>,
     /// Uncommitted changes
@@ -43,7 +43,7 @@ impl MemDb {
         Self {
             inner: Arc::new(RwLock::new(MemDbInner {
                 latest_version: None,
-                state_commitment: HashMap::new(),
+                state_commitment: BTreeMap::new(),
                 state_storage: VersionedMap::new(),
                 changeset: None,
             })),
@@ -106,6 +106,10 @@ impl Db for MemDb {
         self.with_read(|inner| inner.latest_version)
     }
 
+    fn oldest_version(&self) -> Option<u64> {
+        self.with_read(|inner| inner.state_storage.oldest_version)
+    }
+
     fn root_hash(&self, version: Option<u64>) -> DbResult<Option<Hash>> {
         let version = version.unwrap_or_else(|| self.latest_version().unwrap_or(0));
         Ok(MERKLE_TREE.root_hash(&self.state_commitment(), version)?)
@@ -125,7 +129,21 @@ impl Db for MemDb {
     // The best way to avoid this is to do everything that requires a read lock
     // first (using a `with_read` callback) and do everything that requires a
     // write lock in the end (using a `with_write` callback).
-    fn flush_but_not_commit(&self, batch: Batch) -> DbResult<(u64, Option<Hash>)> {
+    fn flush_but_not_commit(
+        &self,
+        removed_ranges: Vec<RemovedRange>,
+        mut batch: Batch,
+    ) -> DbResult<(u64, Option<Hash>)> {
+        // the versioned map has no notion of ranges, so the records in the
+        // removed ranges are deleted one by one. a pending op on a key in a
+        // range takes precedence, as it's made after the range is removed.
+        let state_storage = self.state_storage(None);
+        for (min, max) in &removed_ranges {
+            for (key, _) in state_storage.scan(min.as_deref(), max.as_deref(), Order::Ascending) {
+                batch.entry(key).or_insert(Op::Delete);
+            }
+        }
+
         let (new_version, root_hash, changeset) = self.with_read(|inner| {
             if inner.changeset.is_some() {
                 return Err(DbError::ChangeSetAlreadySet);
@@ -171,15 +189,89 @@ impl Db for MemDb {
             }
 
             // write changes to state storage
-            inner.state_storage.write_batch(changeset.state_storage);
+            inner.state_storage.write_batch_at(changeset.version, changeset.state_storage);
 
             Ok(())
         })
     }
 
+    fn restore(&self, version: u64, batch: Batch) -> DbResult<Option<Hash>> {
+        let (root_hash, changeset) = self.with_read(|inner| {
+            if inner.latest_version.is_some() {
+                return Err(DbError::NotEmpty);
+            }
+
+            // the tree is empty, so the old version doesn't matter. the tree is
+            // built from scratch at the new version.
+            let mut cache = CacheStore::new(self.state_commitment(), None);
+            let root_hash = MERKLE_TREE.apply_raw(&mut cache, 0, version, &batch)?;
+            let (_, changeset) = cache.disassemble();
+
+            Ok((root_hash, changeset))
+        })?;
+
+        // overwrite the changeset of a previous restore attempt, if any
+        self.with_write(|mut inner| {
+            inner.changeset = Some(ChangeSet {
+                version,
+                state_commitment: changeset,
+                state_storage: batch,
+            });
+        });
+
+        Ok(root_hash)
+    }
+
+    fn prune(&self, up_to_version: u64, keep_every: Option<u64>) -> DbResult<()> {
+        // same as in `flush_but_not_commit`, the reads must be done before we
+        // acquire the write lock
+        let mut cache = CacheStore::new(self.state_commitment(), None);
+        MERKLE_TREE.prune(&mut cache, Some(up_to_version), keep_every)?;
+        let (_, removed_ranges, changeset) = cache.disassemble_with_ranges();
+
+        self.with_write(|mut inner| {
+            // the orphans are removed as ranges directly from the map, rather
+            // than having the cache scan them into a batch of deletions
+            for (min, max) in removed_ranges {
+                remove_range(&mut inner.state_commitment, min, max);
+            }
+
+            for (key, op) in changeset {
+                if let Op::Insert(value) = op {
+                    inner.state_commitment.insert(key, value);
+                } else {
+                    inner.state_commitment.remove(&key);
+                }
+            }
+
+            inner.state_storage.prune(up_to_version, keep_every);
+        });
+
+        Ok(())
+    }
+
     // TODO: add a more performant implementation of `flush_and_commit`
 }
 
+fn remove_range(
+    map: &mut BTreeMap<Vec<u8>, Vec<u8>>,
+    min: Option<Vec<u8>>,
+    max: Option<Vec<u8>>,
+) {
+    if let (Some(min), Some(max)) = (&min, &max) {
+        if min > max {
+            return;
+        }
+    }
+
+    let min = min.map_or(Bound::Unbounded, Bound::Included);
+    let max = max.map_or(Bound::Unbounded, Bound::Excluded);
+    let keys = map.range((min, max)).map(|(k, _)| k.clone()).collect::<Vec<_>>();
+    for key in keys {
+        map.remove(&key);
+    }
+}
+
 // ----------------------------- state commitment ------------------------------
 
 #[derive(Clone)]
@@ -194,11 +286,23 @@ impl Storage for StateCommitment {
 
     fn scan<'a>(
         &'a self,
-        _min: Option<&[u8]>,
-        _max: Option<&[u8]>,
-        _order: Order,
+        min: Option<&[u8]>,
+        max: Option<&[u8]>,
+        order: Order,
     ) -> Box<dyn Iterator<Item = Record> + 'a> {
-        unimplemented!("this isn't used by the Merkle tree");
+        let min = min.map_or(Bound::Unbounded, Bound::Included);
+        let max = max.map_or(Bound::Unbounded, Bound::Excluded);
+        let vec = self.db.with_read(|inner| {
+            inner
+                .state_commitment
+                .range::<[u8], _>((min, max))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<Vec<_>>()
+        });
+        match order {
+            Order::Ascending => Box::new(vec.into_iter()),
+            Order::Descending => Box::new(vec.into_iter().rev()),
+        }
     }
 
     fn write(&mut self, _key: &[u8], _value: &[u8]) {
@@ -208,6 +312,10 @@ impl Storage for StateCommitment {
     fn remove(&mut self, _key: &[u8]) {
         unreachable!("write function called on read-only storage");
     }
+
+    fn remove_range(&mut self, _min: Option<&[u8]>, _max: Option<&[u8]>) {
+        unreachable!("write function called on read-only storage");
+    }
 }
 
 // ------------------------------- state storage -------------------------------
@@ -254,4 +362,8 @@ impl Storage for StateStorage {
     fn remove(&mut self, _key: &[u8]) {
         unreachable!("write function called on read-only storage");
     }
+
+    fn remove_range(&mut self, _min: Option<&[u8]>, _max: Option<&[u8]>) {
+        unreachable!("write function called on read-only storage");
+    }
 }
@@ -10,6 +10,9 @@ pub enum DbError {
 
     #[error("cannot commit when changeset is not yet set")]
     ChangeSetNotSet,
+
+    #[error("cannot restore a snapshot when the DB is not empty")]
+    NotEmpty,
 }
 
 impl From<DbError> for AppError {
@@ -4,6 +4,7 @@ use {
         borrow::Borrow,
         collections::BTreeMap,
         marker::PhantomData,
+        mem,
         ops::{Bound, RangeBounds},
     },
 };
@@ -13,6 +14,12 @@ pub struct VersionedMap<K, V> {
     // set to 0 the first time a batch is written
     // incremented by 1 each following batch write
     pub latest_version: Option<u64>,
+    // initialized to None
+    // set to the version up to which the map is pruned, if it has been
+    pub oldest_version: Option<u64>,
+    // initialized to None
+    // set to the interval at which older versions are kept, if they are
+    keep_every: Option<u64>,
     // key => (version => op)
     nested_map: BTreeMap<K, BTreeMap<u64, Op<V>>>,
 }
@@ -21,6 +28,8 @@ impl<K, V> VersionedMap<K, V> {
     pub fn new() -> Self {
         Self {
             latest_version: None,
+            oldest_version: None,
+            keep_every: None,
             nested_map: BTreeMap::new(),
         }
     }
@@ -40,22 +49,64 @@ where
     where
         B: IntoIterator<Item = (K, Op<V>)>,
     {
-        let version = match self.latest_version.as_mut() {
-            None => {
-                self.latest_version = Some(0);
-                0
-            },
-            Some(version) => {
-                *version += 1;
-                *version
-            },
-        };
+        let version = self.latest_version.map_or(0, |version| version + 1);
+        self.write_batch_at(version, batch);
+    }
+
+    /// Write a batch at the given version, which must be newer than the latest
+    /// one. Used when restoring from a snapshot, where versions don't start
+    /// from zero.
+    pub fn write_batch_at<B>(&mut self, version: u64, batch: B)
+    where
+        B: IntoIterator<Item = (K, Op<V>)>,
+    {
+        if let Some(latest_version) = self.latest_version {
+            if version <= latest_version {
+                panic!("version that isn't newer than the latest ({version} <= {latest_version})");
+            }
+        }
+
+        self.latest_version = Some(version);
 
         for (key, op) in batch {
             self.nested_map.entry(key).or_default().insert(version, op);
         }
     }
 
+    /// Delete the ops that are only needed to read at versions older than
+    /// `up_to_version`, except those that are multiples of `keep_every`, if
+    /// given. Afterwards, reading at the deleted versions panics.
+    pub fn prune(&mut self, up_to_version: u64, keep_every: Option<u64>) {
+        self.nested_map.retain(|_, inner_map| {
+            let newer = inner_map.split_off(&up_to_version);
+            let older = mem::replace(inner_map, newer);
+            let has_op_at_up_to_version = inner_map.contains_key(&up_to_version);
+
+            let mut older = older.into_iter().peekable();
+            while let Some((version, op)) = older.next() {
+                // an older op gives the value at versions up to the next op. it's
+                // needed if any of them is kept, or if it's the last one, as it
+                // gives the value as of `up_to_version`, unless there's an op at
+                // exactly that version.
+                let until = older.peek().map_or(up_to_version, |(next_version, _)| *next_version);
+                let is_kept = keep_every.is_some_and(|n| has_multiple_in(n, version, until));
+                let is_last = until == up_to_version && !has_op_at_up_to_version;
+
+                // a deletion doesn't need to be kept either, unless there's an
+                // older op being kept that it overrides.
+                let overrides = inner_map.range(..version).next().is_some();
+                if (is_kept || is_last) && (matches!(op, Op::Insert(_)) || overrides) {
+                    inner_map.insert(version, op);
+                }
+            }
+
+            !inner_map.is_empty()
+        });
+
+        self.oldest_version = Some(up_to_version);
+        self.keep_every = keep_every;
+    }
+
     pub fn get<T>(&self, key: &T, version: u64) -> Option<&V>
     where
         T: Ord + ?Sized,
@@ -65,6 +116,7 @@ where
         if version > latest_version {
             panic!("version that is newer than the latest ({version} > {latest_version})");
         }
+        self.assert_not_pruned(version);
         self.nested_map
             .get(key)?
             .range(0..=version)
@@ -87,6 +139,7 @@ where
                 panic!("version that is newer than the latest ({version} > {latest_version})");
             }
         };
+        self.assert_not_pruned(version);
         VersionedIterator {
             nested_map: &self.nested_map,
             range,
@@ -95,6 +148,15 @@ where
             phantom: PhantomData,
         }
     }
+
+    fn assert_not_pruned(&self, version: u64) {
+        if let Some(oldest_version) = self.oldest_version {
+            let kept = self.keep_every.is_some_and(|interval| version % interval == 0);
+            if version < oldest_version && !kept {
+                panic!("version that has been pruned ({version} < {oldest_version})");
+            }
+        }
+    }
 }
 
 pub struct VersionedIterator<'a, K, V, R, T: ?Sized> {
@@ -153,15 +215,22 @@ where
     }
 }
 
+/// Whether any multiple of `interval` lies in the range `[start, end)`.
+fn has_multiple_in(interval: u64, start: u64, end: u64) -> bool {
+    start
+        .div_ceil(interval)
+        .checked_mul(interval)
+        .is_some_and(|multiple| multiple < end)
+}
+
 // ----------------------------------- tests -----------------------------------
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn iterating() {
-        let mut map = VersionedMap::<&str, &str>::new();
+    fn build_test_case() -> VersionedMap<&'static str, &'static str> {
+        let mut map = VersionedMap::new();
         // apply some batches
         for batch in [
             // version: 0
@@ -187,6 +256,12 @@ mod tests {
         ] {
             map.write_batch(batch);
         }
+        map
+    }
+
+    #[test]
+    fn iterating() {
+        let map = build_test_case();
 
         assert!(map.range::<_, str>(.., 0).map(|(k, v)| (*k, *v)).eq([
             ("donald", "trump"),
@@ -210,4 +285,55 @@ mod tests {
             ("ulfric", "stormcloak"),
         ]));
     }
+
+    #[test]
+    fn pruning() {
+        let mut map = build_test_case();
+        map.prune(1, None);
+
+        // reading at versions that haven't been pruned gives the same results
+        assert!(map.range::<_, str>(.., 1).map(|(k, v)| (*k, *v)).eq([
+            ("donald", "duck"),
+            ("joe", "biden"),
+            ("larry", "engineer"),
+            ("satoshi", "nakamoto"),
+            ("ulfric", "stormcloak"),
+        ]));
+        assert_eq!(map.get("larry", 2), Some(&"founder"));
+        assert_eq!(map.get("joe", 2), None);
+
+        // ops that are only needed at version 0 are deleted
+        assert_eq!(map.nested_map["donald"].len(), 1);
+        assert_eq!(map.nested_map["satoshi"].keys().collect::<Vec<_>>(), [&0]);
+    }
+
+    #[test]
+    fn pruning_keeping_every_nth_version() {
+        let mut map = build_test_case();
+        // version: 3
+        map.write_batch([("larry", Op::Insert("ceo"))]);
+        // version: 4
+        map.write_batch([("larry", Op::Insert("retired"))]);
+        map.prune(4, Some(2));
+
+        // reading at the kept versions gives the same results
+        assert!(map.range::<_, str>(.., 0).map(|(k, v)| (*k, *v)).eq([
+            ("donald", "trump"),
+            ("joe", "biden"),
+            ("larry", "engineer"),
+            ("pumpkin", "cat"),
+            ("satoshi", "nakamoto")
+        ]));
+        assert!(map.range::<_, str>(.., 2).map(|(k, v)| (*k, *v)).eq([
+            ("donald", "duck"),
+            ("jake", "shepherd"),
+            ("larry", "founder"),
+            ("satoshi", "nakamoto"),
+            ("ulfric", "stormcloak"),
+        ]));
+        assert_eq!(map.get("larry", 4), Some(&"retired"));
+
+        // the op that is only needed at version 3 is deleted
+        assert_eq!(map.nested_map["larry"].keys().collect::<Vec<_>>(), [&0, &2, &4]);
+    }
 }
@@ -3,7 +3,7 @@ use {
         BitArray, Child, InternalNode, LeafNode, MembershipProof, Node, NonMembershipProof, Proof,
         ProofNode,
     },
-    grug_storage::{Map, Set},
+    grug_storage::{Bound, Map, Set},
     grug_types::{hash, Batch, Hash, Op, Order, StdResult, Storage},
     tracing::trace,
 };
@@ -478,9 +478,52 @@ impl<'a> MerkleTree<'a> {
 
     /// Delete nodes that are no longer part of the tree as of `up_to_version`.
     /// If no `up_to_version` is provided then delete all orphans.
-    pub fn prune(&self, _store: &mut dyn Storage, _up_to_version: Option<u64>) -> StdResult<()> {
-        // we should first implement a `range_remove` method on Storage trait
-        todo!()
+    ///
+    /// Afterwards, the tree can no longer be accessed at versions older than
+    /// `up_to_version`, except those that are multiples of `keep_every`, if
+    /// provided. Nodes that are part of the tree at these versions are kept.
+    pub fn prune(
+        &self,
+        store:         &mut dyn Storage,
+        up_to_version: Option<u64>,
+        keep_every:    Option<u64>,
+    ) -> StdResult<()> {
+        // a node orphaned since version v is only part of the tree at versions
+        // up to v - 1. orphans are ordered by the version since which they're
+        // orphaned, so we can stop once we find one that's still needed.
+        //
+        // we need to collect the orphans first, because we can't write to the
+        // store while iterating it.
+        let orphans = self
+            .orphans
+            .range(store, None, None, Order::Ascending)
+            .take_while(|res| match (res, up_to_version) {
+                (Ok((orphaned_since_version, ..)), Some(up_to_version)) => {
+                    *orphaned_since_version <= up_to_version
+                },
+                _ => true,
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        for (orphaned_since_version, version, bits) in orphans {
+            // the node is part of the tree from the version it's created at, up
+            // to the one before it's orphaned. keep it if any of these is kept.
+            if let Some(interval) = keep_every {
+                if has_multiple_in(interval, version, orphaned_since_version) {
+                    continue;
+                }
+            }
+
+            trace!(orphaned_since_version, version, ?bits, "Pruning orphaned node");
+            self.nodes.remove(store, (version, &bits));
+        }
+
+        // the orphans we've just collected are a contiguous range at the start
+        // of the set, so they can be removed altogether
+        let max = up_to_version
+            .and_then(|up_to_version| up_to_version.checked_add(1))
+            .map(|next_version| Bound::Exclusive((next_version, 0, ROOT_BITS)));
+        self.orphans.clear(store, None, max, None)
     }
 
     #[inline]
@@ -621,6 +664,15 @@ fn into_child(version: u64, outcome: Outcome) -> Option<Child> {
     }
 }
 
+/// Whether any multiple of `interval` lies in the range `[start, end)`.
+#[inline]
+fn has_multiple_in(interval: u64, start: u64, end: u64) -> bool {
+    start
+        .div_ceil(interval)
+        .checked_mul(interval)
+        .is_some_and(|multiple| multiple < end)
+}
+
 // ----------------------------------- tests -----------------------------------
 
 // we use the following very simple merkle tree in these tests:
@@ -776,6 +828,61 @@ mod tests {
         }
     }
 
+    // after pruning up to version 2, the tree can't be accessed at version 1,
+    // but can still be at version 2, and all the orphans are deleted.
+    #[test]
+    #[traced_test]
+    fn pruning() {
+        let (mut store, _) = build_test_case().unwrap();
+        let new_root_hash = TREE.apply_raw(&mut store, 1, 2, &Batch::from([
+            (b"r".to_vec(), Op::Delete),
+            (b"m".to_vec(), Op::Delete),
+        ]))
+        .unwrap();
+
+        // pruning up to version 1 doesn't delete anything, because the orphans
+        // are only orphaned since version 2
+        TREE.prune(&mut store, Some(1), None).unwrap();
+        assert_eq!(TREE.root_hash(&store, 1).unwrap(), Some(HASH_ROOT));
+
+        TREE.prune(&mut store, Some(2), None).unwrap();
+        assert_eq!(TREE.root_hash(&store, 1).unwrap(), None);
+        assert_eq!(TREE.root_hash(&store, 2).unwrap(), new_root_hash);
+        assert!(TREE.orphans.is_empty(&store));
+
+        // nodes that are still part of the tree at version 2 are kept
+        for item in TREE.nodes.keys(&store, None, None, Order::Ascending) {
+            let (version, bits) = item.unwrap();
+            assert!(version == 2 || bits.num_bits > 0);
+        }
+        assert!(TREE.prove(&store, &hash(b"a"), 2).is_ok());
+    }
+
+    // when keeping every 2nd version, pruning up to version 3 deletes the nodes
+    // only needed at version 1, but keeps those needed at version 2.
+    #[test]
+    #[traced_test]
+    fn pruning_keeping_every_nth_version() {
+        let (mut store, _) = build_test_case().unwrap();
+        let root_hash_2 = TREE.apply_raw(&mut store, 1, 2, &Batch::from([
+            (b"r".to_vec(), Op::Delete),
+            (b"m".to_vec(), Op::Delete),
+        ]))
+        .unwrap();
+        let root_hash_3 = TREE.apply_raw(&mut store, 2, 3, &Batch::from([
+            (b"a".to_vec(), Op::Insert(b"fizz".to_vec())),
+        ]))
+        .unwrap();
+
+        TREE.prune(&mut store, Some(3), Some(2)).unwrap();
+        assert_eq!(TREE.root_hash(&store, 1).unwrap(), None);
+        assert_eq!(TREE.root_hash(&store, 2).unwrap(), root_hash_2);
+        assert_eq!(TREE.root_hash(&store, 3).unwrap(), root_hash_3);
+        assert!(TREE.orphans.is_empty(&store));
+        assert!(TREE.prove(&store, &hash(b"a"), 2).is_ok());
+        assert!(TREE.prove(&store, &hash(b"a"), 3).is_ok());
+    }
+
     // no-op is when the batch contains entirely of overwrites of values by the
     // same value, or deletes of non-existing keys. the version number shouldn't
     // be incremented and root hash shouldn't be changed.
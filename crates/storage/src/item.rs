@@ -18,6 +18,11 @@ impl<'a, T> Item<'a, T> {
         }
     }
 
+    /// Return the raw key under which the item is stored.
+    pub fn storage_key(&self) -> &[u8] {
+        self.storage_key
+    }
+
     fn path(&self) -> Path<T> {
         Path::from_raw(self.storage_key)
     }
@@ -33,6 +33,12 @@ where
         PathBuf::new(self.namespace, &raw_keys, last_raw_key.as_ref())
     }
 
+    /// Return the raw key under which the value corresponding to the given
+    /// map key is stored.
+    pub fn storage_key(&self, key: K) -> Vec<u8> {
+        self.path(key).into_storage_key()
+    }
+
     fn no_prefix(&self) -> Prefix<K, T> {
         Prefix::new(self.namespace, &[])
     }
@@ -110,3 +116,39 @@ where
         self.no_prefix().clear(store, min, max, limit)
     }
 }
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, grug_types::MockStorage};
+
+    const FOOS: Map<u32, u32> = Map::new("foo");
+    const BARS: Map<u32, u32> = Map::new("bar");
+
+    fn keys(map: &Map<u32, u32>, store: &dyn Storage) -> Vec<u32> {
+        map.keys(store, None, None, Order::Ascending).collect::<StdResult<_>>().unwrap()
+    }
+
+    #[test]
+    fn clearing_works() {
+        let mut store = MockStorage::new();
+        for i in 1..=5 {
+            FOOS.save(&mut store, i, &i).unwrap();
+            BARS.save(&mut store, i, &i).unwrap();
+        }
+
+        FOOS.clear(&mut store, Some(Bound::Inclusive(2)), Some(Bound::Exclusive(4)), None)
+            .unwrap();
+        assert_eq!(keys(&FOOS, &store), [1, 4, 5]);
+
+        FOOS.clear(&mut store, None, None, Some(1)).unwrap();
+        assert_eq!(keys(&FOOS, &store), [4, 5]);
+
+        FOOS.clear(&mut store, None, None, None).unwrap();
+        assert!(FOOS.is_empty(&store));
+
+        // other maps are untouched
+        assert_eq!(keys(&BARS, &store), [1, 2, 3, 4, 5]);
+    }
+}
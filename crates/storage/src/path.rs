@@ -20,6 +20,10 @@ impl<T> PathBuf<T> {
         }
     }
 
+    pub(crate) fn into_storage_key(self) -> Vec<u8> {
+        self.storage_key
+    }
+
     pub fn as_path(&self) -> Path<'_, T> {
         Path {
             storage_key: self.storage_key.as_slice(),
@@ -75,14 +75,37 @@ where
         Box::new(iter)
     }
 
+    /// Remove the records within the given bounds. If a limit is given, only
+    /// remove up to this many records, starting from the smallest key.
     pub fn clear(
         &self,
-        _store: &mut dyn Storage,
-        _min:   Option<Bound<K>>,
-        _max:   Option<Bound<K>>,
-        _limit: Option<usize>,
+        store: &mut dyn Storage,
+        min:   Option<Bound<K>>,
+        max:   Option<Bound<K>>,
+        limit: Option<usize>,
     ) -> StdResult<()> {
-        todo!()
+        let (min, mut max) = range_bounds(&self.prefix, min, max);
+
+        // with a limit, we need to find the key of the last record to remove.
+        // the range is cut off right after it.
+        if let Some(limit) = limit {
+            if limit == 0 {
+                return Ok(());
+            }
+
+            let last_key = store
+                .scan(Some(&min), Some(&max), Order::Ascending)
+                .nth(limit - 1)
+                .map(|(k, _)| k);
+
+            if let Some(last_key) = last_key {
+                max = extend_one_byte(last_key);
+            }
+        }
+
+        store.remove_range(Some(&min), Some(&max));
+
+        Ok(())
     }
 }
 
@@ -104,3 +127,47 @@ fn range_bounds<K: MapKey>(
 
     (min, max)
 }
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{Bound, Map},
+        grug_types::{MockStorage, Order, StdResult, Storage},
+    };
+
+    const FOOS: Map<(u32, u32), u32> = Map::new("foo");
+
+    fn suffixes(store: &dyn Storage, prefix: u32) -> Vec<u32> {
+        FOOS.prefix(prefix)
+            .keys(store, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn clearing_with_limit_works() {
+        let mut store = MockStorage::new();
+        for prefix in 1..=2 {
+            for suffix in 1..=5 {
+                FOOS.save(&mut store, (prefix, suffix), &suffix).unwrap();
+            }
+        }
+
+        // a zero limit removes nothing
+        FOOS.prefix(1).clear(&mut store, None, None, Some(0)).unwrap();
+        assert_eq!(suffixes(&store, 1), [1, 2, 3, 4, 5]);
+
+        // the smallest keys within the bounds are removed first
+        FOOS.prefix(1).clear(&mut store, Some(Bound::Exclusive(1)), None, Some(2)).unwrap();
+        assert_eq!(suffixes(&store, 1), [1, 4, 5]);
+
+        // a limit larger than the number of records removes all of them
+        FOOS.prefix(1).clear(&mut store, None, None, Some(10)).unwrap();
+        assert!(suffixes(&store, 1).is_empty());
+
+        // records under other prefixes are untouched
+        assert_eq!(suffixes(&store, 2), [1, 2, 3, 4, 5]);
+    }
+}
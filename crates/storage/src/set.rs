@@ -74,3 +74,39 @@ where
         self.no_prefix().clear(store, min, max, limit)
     }
 }
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, grug_types::MockStorage};
+
+    const FOOS: Set<u32> = Set::new("foo");
+    const BARS: Set<u32> = Set::new("bar");
+
+    fn items(set: &Set<u32>, store: &dyn Storage) -> Vec<u32> {
+        set.range(store, None, None, Order::Ascending).collect::<StdResult<_>>().unwrap()
+    }
+
+    #[test]
+    fn clearing_works() {
+        let mut store = MockStorage::new();
+        for i in 1..=5 {
+            FOOS.insert(&mut store, i).unwrap();
+            BARS.insert(&mut store, i).unwrap();
+        }
+
+        FOOS.clear(&mut store, Some(Bound::Exclusive(1)), Some(Bound::Inclusive(3)), None)
+            .unwrap();
+        assert_eq!(items(&FOOS, &store), [1, 4, 5]);
+
+        FOOS.clear(&mut store, None, None, Some(2)).unwrap();
+        assert_eq!(items(&FOOS, &store), [5]);
+
+        FOOS.clear(&mut store, None, None, None).unwrap();
+        assert!(FOOS.is_empty(&store));
+
+        // other sets are untouched
+        assert_eq!(items(&BARS, &store), [1, 2, 3, 4, 5]);
+    }
+}
@@ -1,7 +1,10 @@
 use {
-    grug_app::App,
+    grug_app::{App, AppResult, BlockOutcome, TxOutcome},
     grug_db_memory::MemDb,
-    grug_types::{BlockInfo, GenesisState, Hash, QueryRequest, QueryResponse, Timestamp, Uint64},
+    grug_types::{
+        hash, to_json_vec, Binary, BlockInfo, ConsensusParams, GenesisState, Hash, QueryRequest,
+        QueryResponse, Timestamp, Tx, Uint64, VerifyVoteExtensionMsg, VoteExtension,
+    },
     grug_vm_rust::RustVm,
     std::time::{SystemTime, UNIX_EPOCH},
 };
@@ -41,11 +44,53 @@ impl MockApp {
             timestamp: current_time(),
             hash: Hash::ZERO,
         };
-        self.inner.do_init_chain(chain_id.to_string(), block, genesis_state).unwrap();
+        self.inner
+            .do_init_chain(
+                chain_id.to_string(),
+                block,
+                genesis_state,
+                ConsensusParams::default(),
+                vec![],
+            )
+            .unwrap();
+    }
+
+    /// Finalize and commit a block following the last finalized one, which
+    /// delivers the given vote extensions and contains the given transactions.
+    pub fn finalize_block(
+        &mut self,
+        vote_extensions: Vec<VoteExtension>,
+        txs: Vec<Tx>,
+    ) -> BlockOutcome {
+        let last_block = self.query(QueryRequest::Info {}).as_info().last_finalized_block;
+        let block = BlockInfo {
+            height: Uint64::new(last_block.height.u64() + 1),
+            timestamp: current_time(),
+            hash: Hash::ZERO,
+        };
+        let txs = txs.into_iter().map(|tx| (hash(to_json_vec(&tx).unwrap()), tx)).collect();
+        let outcome = self.inner.do_finalize_block(block, vote_extensions, txs).unwrap();
+        self.inner.do_commit().unwrap();
+        outcome
+    }
+
+    pub fn check_tx(&self, tx: Tx) -> TxOutcome {
+        self.inner.do_check_tx(tx)
+    }
+
+    pub fn extend_vote(&self) -> AppResult<Binary> {
+        self.inner.do_extend_vote()
+    }
+
+    pub fn verify_vote_extension(&self, validator: Binary, extension: Binary) -> AppResult<()> {
+        self.inner.do_verify_vote_extension(VerifyVoteExtensionMsg {
+            validator,
+            extension,
+        })
     }
 
     pub fn query(&self, req: QueryRequest) -> QueryResponse {
-        self.inner.do_query_app(req, 0, false).unwrap()
+        self.inner.do_query_app(req, 0, false).unwrap().0
     }
 }
 
@@ -56,14 +101,86 @@ mod tests {
     use {
         super::*,
         grug_types::{
-            hash, to_borsh_vec, to_json_value, Addr, Coins, Config, Empty, Message, Permission,
-            Permissions, Response, StdResult,
+            concat, from_borsh_slice, from_json_slice, from_json_value, to_borsh_vec,
+            to_json_value, AbciParams, Addr, Batch, BlockIdFlag, BlockerFailurePolicy, Coins,
+            Config, Duration, Empty, ExtendedCommitInfo, ExtendedVote, GasCosts, GasReport, Json,
+            Message, Op, Permission, Permissions, PrepareProposalMsg, ProcessProposalMsg, Response,
+            StdError, StdResult, SubMessage, SubMsgResult, ValidatorPubKey, ValidatorUpdate,
+            DEFAULT_HANDLER_GAS_LIMIT, DEFAULT_MAX_CALL_DEPTH, GENESIS_SENDER,
         },
-        grug_vm_rust::{ContractWrapper, ExecuteFn, MigrateFn, QueryFn, ReceiveFn, ReplyFn},
-        grug_wasm::MutableCtx,
-        std::collections::BTreeSet,
+        grug_vm_rust::{ContractBuilder, ContractWrapper},
+        grug_app::{
+            consensus_address, save_config, vote_extension_sign_bytes, AppError, Db, Snapshot,
+            SnapshotStore, CONFIG, CONSENSUS_PARAMS, CONSENSUS_VALIDATORS, CONTRACT_NAMESPACE,
+            SNAPSHOT_FORMAT,
+        },
+        grug_jmt::{verify_proof, Proof},
+        grug_wasm::{AuthCtx, ImmutableCtx, MutableCtx, SudoCtx},
+        k256::ecdsa::{signature::Signer, Signature, SigningKey},
+        std::collections::{BTreeMap, BTreeSet},
     };
 
+    /// A config with no contract wired up, in which everybody may upload codes
+    /// and instantiate contracts. Tests override the fields they need.
+    fn mock_config() -> Config {
+        Config {
+            owner: None,
+            bank: Addr::mock(1),
+            taxman: Addr::mock(2),
+            begin_blockers: vec![],
+            end_blockers: vec![],
+            blocker_failure_policies: BTreeMap::new(),
+            cronjobs: BTreeMap::new(),
+            permissions: Permissions {
+                upload: Permission::Everybody,
+                instantiate: Permission::Everybody,
+                create_client: Permission::Everybody,
+                create_connection: Permission::Everybody,
+                create_channel: Permission::Everybody,
+            },
+            allowed_clients: BTreeSet::new(),
+            gas_costs: GasCosts::default(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            validator_set: None,
+            proposal_handler: None,
+            vote_extension_handler: None,
+            handler_gas_limit: DEFAULT_HANDLER_GAS_LIMIT,
+        }
+    }
+
+    /// Genesis messages that upload the contract and instantiate it with an
+    /// empty message, along with the code hash and the contract's address.
+    fn deploy_at_genesis(contract: ContractWrapper, salt: &str) -> (Vec<Message>, Hash, Addr) {
+        let code = to_borsh_vec(&contract).unwrap();
+        let code_hash = hash(&code);
+        let salt = Binary::from(salt.as_bytes().to_vec());
+        let address = Addr::compute(&GENESIS_SENDER, &code_hash, &salt);
+        let msgs = vec![
+            Message::Upload {
+                code: code.into(),
+                instantiate_permission: None,
+            },
+            Message::Instantiate {
+                code_hash: code_hash.clone(),
+                msg: to_json_value(&Empty {}).unwrap(),
+                salt,
+                funds: Coins::new_empty(),
+                admin: None,
+                non_reentrant: false,
+            },
+        ];
+        (msgs, code_hash, address)
+    }
+
+    fn query_raw(app: &MockApp, contract: &Addr, key: &[u8]) -> Option<Binary> {
+        app.query(QueryRequest::WasmRaw {
+            contract: contract.clone(),
+            key: key.to_vec().into(),
+        })
+        .as_wasm_raw()
+        .value
+    }
+
     fn bank_instantiate(_ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
         Ok(Response::new().add_attribute("action", "bank_instantiate"))
     }
@@ -71,34 +188,15 @@ mod tests {
     #[test]
     fn init_chain_works() {
         let mut app = MockApp::new();
-        let bank_contract = ContractWrapper::new(
-            Box::new(bank_instantiate),
-            None::<ExecuteFn>,
-            None::<MigrateFn>,
-            None::<ReceiveFn>,
-            None::<ReplyFn>,
-            None::<QueryFn>,
-        );
+        let bank_contract = ContractBuilder::new(Box::new(bank_instantiate)).build();
         let bank_code = to_borsh_vec(&bank_contract).unwrap();
         let bank_code_hash = hash(&bank_code);
         let genesis_state = GenesisState {
-            config: Config {
-                owner: None,
-                bank: Addr::mock(1),
-                begin_blockers: vec![],
-                end_blockers: vec![],
-                permissions: Permissions {
-                    upload: Permission::Everybody,
-                    instantiate: Permission::Everybody,
-                    create_client: Permission::Everybody,
-                    create_connection: Permission::Everybody,
-                    create_channel: Permission::Everybody,
-                },
-                allowed_clients: BTreeSet::new(),
-            },
+            config: mock_config(),
             msgs: vec![
                 Message::Upload {
                     code: bank_code.into(),
+                    instantiate_permission: None,
                 },
                 Message::Instantiate {
                     code_hash: bank_code_hash,
@@ -106,8 +204,10 @@ mod tests {
                     salt: b"bank".to_vec().into(),
                     funds: Coins::new_empty(),
                     admin: None,
+                    non_reentrant: false,
                 },
             ],
+            exported: None,
         };
         app.init_chain("dev-1", genesis_state);
 
@@ -130,4 +230,1692 @@ mod tests {
             .as_accounts();
         dbg!(&accounts);
     }
+
+    fn noop_instantiate(_ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    // a mock account, whose "signature" is the sequence number that the tx is
+    // signed with. a tx can't be authenticated twice.
+    fn account_before_tx(ctx: AuthCtx, tx: Tx) -> StdResult<Response> {
+        let sequence: u32 = match ctx.store.read(b"sequence") {
+            Some(bytes) => from_json_slice(bytes)?,
+            None => 0,
+        };
+        if *tx.credential != to_json_vec(&sequence)? {
+            return Err(StdError::Generic("invalid signature".into()));
+        }
+        ctx.store.write(b"sequence", &to_json_vec(&(sequence + 1))?);
+        Ok(Response::new())
+    }
+
+    fn account_after_tx(_ctx: AuthCtx, _tx: Tx) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn account_execute(ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        ctx.store.write(b"executed", b"true");
+        Ok(Response::new())
+    }
+
+    fn account_contract() -> ContractWrapper {
+        ContractBuilder::new(Box::new(noop_instantiate))
+            .with_execute(Box::new(account_execute))
+            .with_before_tx(Box::new(account_before_tx))
+            .with_after_tx(Box::new(account_after_tx))
+            .build()
+    }
+
+    fn account_tx(account: &Addr, sequence: u32) -> Tx {
+        Tx {
+            sender: account.clone(),
+            msgs: vec![Message::Execute {
+                contract: account.clone(),
+                msg: to_json_value(&Empty {}).unwrap(),
+                funds: Coins::new_empty(),
+            }],
+            gas_limit: Uint64::new(1_000_000),
+            expiry_height: None,
+            expiry_timestamp: None,
+            credential: to_json_vec(&sequence).unwrap().into(),
+        }
+    }
+
+    // a mock taxman that counts the number of times it's asked to charge fees
+    fn taxman_handle_fee(ctx: SudoCtx, _report: GasReport) -> StdResult<Response> {
+        let charged: u32 = match ctx.store.read(b"charged") {
+            Some(bytes) => from_json_slice(bytes)?,
+            None => 0,
+        };
+        ctx.store.write(b"charged", &to_json_vec(&(charged + 1))?);
+        Ok(Response::new())
+    }
+
+    fn failing_taxman_handle_fee(_ctx: SudoCtx, _report: GasReport) -> StdResult<Response> {
+        Err(StdError::Generic("insufficient balance".into()))
+    }
+
+    /// How `init_with_account` starts a chain, besides deploying the mock
+    /// account and the taxman.
+    struct TestGenesis {
+        /// The taxman's `handle_fee` entry point.
+        handle_fee:       fn(SudoCtx, GasReport) -> StdResult<Response>,
+        /// Whether the account is made the chain's owner.
+        account_is_owner: bool,
+        /// The config, in which the taxman and the owner are filled in.
+        config:           Config,
+        /// Genesis messages that deploy other contracts.
+        msgs:             Vec<Message>,
+    }
+
+    impl Default for TestGenesis {
+        fn default() -> Self {
+            Self {
+                handle_fee:       taxman_handle_fee,
+                account_is_owner: false,
+                config:           mock_config(),
+                msgs:             vec![],
+            }
+        }
+    }
+
+    /// Start a chain with a mock account and a taxman, along with the other
+    /// contracts of the given genesis. Returns the addresses of the account and
+    /// the taxman.
+    fn init_with_account(app: &mut MockApp, genesis: TestGenesis) -> (Addr, Addr) {
+        let taxman_contract = ContractBuilder::new(Box::new(noop_instantiate))
+            .with_handle_fee(Box::new(genesis.handle_fee))
+            .build();
+        let (mut msgs, _, taxman) = deploy_at_genesis(taxman_contract, "taxman");
+        let (account_msgs, _, account) = deploy_at_genesis(account_contract(), "account");
+        msgs.extend(account_msgs);
+        msgs.extend(genesis.msgs);
+        app.init_chain("dev-1", GenesisState {
+            config: Config {
+                owner: genesis.account_is_owner.then(|| account.clone()),
+                taxman: taxman.clone(),
+                ..genesis.config
+            },
+            msgs,
+            exported: None,
+        });
+        (account, taxman)
+    }
+
+    #[test]
+    fn fee_is_not_handled_for_unauthenticated_txs() {
+        let mut app = MockApp::new();
+        let (account, taxman) = init_with_account(&mut app, TestGenesis::default());
+
+        // someone other than the account submits a tx on its behalf with an
+        // invalid signature. the taxman must not be called, otherwise it would
+        // take a fee from the account
+        let outcome = app.finalize_block(vec![], vec![account_tx(&account, 5)]);
+        assert!(outcome.tx_results[0].result.is_err());
+        assert_eq!(query_raw(&app, &taxman, b"charged"), None);
+        assert_eq!(query_raw(&app, &account, b"sequence"), None);
+        assert_eq!(query_raw(&app, &account, b"executed"), None);
+
+        // a properly signed tx is charged
+        let outcome = app.finalize_block(vec![], vec![account_tx(&account, 0)]);
+        assert!(outcome.tx_results[0].result.is_ok());
+        assert_eq!(query_raw(&app, &taxman, b"charged"), Some(b"1".to_vec().into()));
+        assert_eq!(query_raw(&app, &account, b"sequence"), Some(b"1".to_vec().into()));
+        assert_eq!(query_raw(&app, &account, b"executed"), Some(b"true".to_vec().into()));
+    }
+
+    #[test]
+    fn failed_fee_handling_keeps_authentication_state() {
+        let mut app = MockApp::new();
+        let (account, _) = init_with_account(&mut app, TestGenesis {
+            handle_fee: failing_taxman_handle_fee,
+            ..Default::default()
+        });
+
+        // the fee can't be handled, so the tx fails and its messages are
+        // reverted, but the sequence number is still incremented
+        let tx = account_tx(&account, 0);
+        let outcome = app.finalize_block(vec![], vec![tx.clone()]);
+        assert!(outcome.tx_results[0].result.is_err());
+        assert_eq!(query_raw(&app, &account, b"sequence"), Some(b"1".to_vec().into()));
+        assert_eq!(query_raw(&app, &account, b"executed"), None);
+
+        // so the tx can't be replayed
+        let outcome = app.finalize_block(vec![], vec![tx]);
+        assert!(matches!(
+            &outcome.tx_results[0].result,
+            Err(err) if err.to_string().contains("invalid signature")
+        ));
+        assert_eq!(query_raw(&app, &account, b"sequence"), Some(b"1".to_vec().into()));
+    }
+
+    #[test]
+    fn fee_handling_is_gas_limited() {
+        let mut app = MockApp::new();
+        let (account, taxman) = init_with_account(&mut app, TestGenesis {
+            config: Config {
+                // a storage read alone costs more than the limit
+                handler_gas_limit: 100,
+                ..mock_config()
+            },
+            ..Default::default()
+        });
+
+        // the taxman runs out of gas, so the tx fails, regardless of its own
+        // gas limit. like any failed fee handling, the sequence is incremented.
+        let outcome = app.finalize_block(vec![], vec![account_tx(&account, 0)]);
+        assert!(matches!(outcome.tx_results[0].result, Err(AppError::OutOfGas { .. })));
+        assert_eq!(query_raw(&app, &taxman, b"charged"), None);
+        assert_eq!(query_raw(&app, &account, b"sequence"), Some(b"1".to_vec().into()));
+        assert_eq!(query_raw(&app, &account, b"executed"), None);
+    }
+
+    #[test]
+    fn expired_txs_are_rejected() {
+        let mut app = MockApp::new();
+        let (account, _) = init_with_account(&mut app, TestGenesis::default());
+
+        // the last finalized block is the genesis block, so a tx that expires
+        // at height 0 can no longer be included. it's rejected by CheckTx
+        // without being authenticated, so the sequence can still be used.
+        let tx = Tx {
+            expiry_height: Some(Uint64::ZERO),
+            ..account_tx(&account, 0)
+        };
+        let outcome = app.check_tx(tx);
+        assert!(matches!(outcome.result, Err(AppError::TxExpired { block_height: 1, .. })));
+        assert_eq!(outcome.gas_used, 0);
+
+        let tx = Tx {
+            expiry_height: Some(Uint64::ONE),
+            ..account_tx(&account, 0)
+        };
+        assert!(app.check_tx(tx.clone()).result.is_ok());
+        let outcome = app.finalize_block(vec![], vec![tx]);
+        assert!(outcome.tx_results[0].result.is_ok());
+        assert_eq!(query_raw(&app, &account, b"sequence"), Some(b"1".to_vec().into()));
+
+        // a tx that expired by time is rejected when the block is finalized,
+        // without any state change
+        let tx = Tx {
+            expiry_timestamp: Some(Timestamp::from_seconds(1)),
+            ..account_tx(&account, 1)
+        };
+        let outcome = app.finalize_block(vec![], vec![tx]);
+        assert!(matches!(outcome.tx_results[0].result, Err(AppError::TxExpired { .. })));
+        assert_eq!(query_raw(&app, &account, b"sequence"), Some(b"1".to_vec().into()));
+    }
+
+    #[test]
+    fn check_tx_rejects_txs_whose_fee_cant_be_paid() {
+        let mut app = MockApp::new();
+        let (account, _) = init_with_account(&mut app, TestGenesis {
+            handle_fee: failing_taxman_handle_fee,
+            ..Default::default()
+        });
+
+        // the tx is authenticated, but the fee can't be handled, so it's kept
+        // out of the mempool
+        let outcome = app.check_tx(account_tx(&account, 0));
+        assert!(matches!(
+            outcome.result,
+            Err(err) if err.to_string().contains("insufficient balance")
+        ));
+    }
+
+    #[test]
+    fn check_tx_sequence_resets_after_commit() {
+        let mut app = MockApp::new();
+        let (account, _) = init_with_account(&mut app, TestGenesis::default());
+
+        // successive CheckTx calls see each other's sequence increments, so a
+        // tx can't be replayed in the mempool
+        assert!(app.check_tx(account_tx(&account, 0)).result.is_ok());
+        assert!(app.check_tx(account_tx(&account, 0)).result.is_err());
+        assert!(app.check_tx(account_tx(&account, 1)).result.is_ok());
+        assert_eq!(query_raw(&app, &account, b"sequence"), None);
+
+        // the txs weren't included in the block, so their increments are
+        // discarded once it's committed
+        app.finalize_block(vec![], vec![]);
+        assert!(app.check_tx(account_tx(&account, 1)).result.is_err());
+        assert!(app.check_tx(account_tx(&account, 0)).result.is_ok());
+
+        // CheckTx carries on from the committed state
+        let outcome = app.finalize_block(vec![], vec![account_tx(&account, 0)]);
+        assert!(outcome.tx_results[0].result.is_ok());
+        assert!(app.check_tx(account_tx(&account, 0)).result.is_err());
+        assert!(app.check_tx(account_tx(&account, 1)).result.is_ok());
+    }
+
+    // a mock taxman that reports the gas it's asked to charge for
+    fn reporting_taxman_handle_fee(_ctx: SudoCtx, report: GasReport) -> StdResult<Response> {
+        Ok(Response::new().add_attribute("limit", report.limit).add_attribute("used", report.used))
+    }
+
+    #[test]
+    fn simulating_tx_works() {
+        let mut app = MockApp::new();
+        let (account, taxman) = init_with_account(&mut app, TestGenesis {
+            handle_fee: reporting_taxman_handle_fee,
+            ..Default::default()
+        });
+
+        // the gas limit isn't enforced in simulation
+        let tx = Tx {
+            gas_limit: Uint64::ZERO,
+            ..account_tx(&account, 0)
+        };
+        let res = app.inner.do_simulate(tx, true).unwrap();
+        assert!(res.gas_used > 0);
+
+        // the taxman is given the same gas report as it would be in a block
+        let events = res.result.as_ok();
+        let fee_event = events.iter().find(|event| event.r#type == "handle_fee").unwrap();
+        let attr = |key: &str| {
+            fee_event.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone()
+        };
+        assert_eq!(attr("_contract_address"), taxman.to_string());
+        assert_eq!(attr("limit"), "0");
+        assert_eq!(attr("used"), res.gas_used.to_string());
+
+        // the written keys are reported, but not persisted
+        let key = [concat(CONTRACT_NAMESPACE, &account), b"executed".to_vec()].concat();
+        assert!(res.written_keys.unwrap().contains(&Binary::from(key)));
+        assert_eq!(query_raw(&app, &account, b"sequence"), None);
+        assert_eq!(query_raw(&app, &account, b"executed"), None);
+        assert!(app.check_tx(account_tx(&account, 0)).result.is_ok());
+    }
+
+    #[test]
+    fn check_tx_charges_for_the_gas_limit() {
+        let mut app = MockApp::new();
+        let (account, _) = init_with_account(&mut app, TestGenesis {
+            handle_fee: reporting_taxman_handle_fee,
+            ..Default::default()
+        });
+
+        // the gas the tx will use isn't known yet, so the taxman is asked to
+        // charge for all of it
+        let outcome = app.check_tx(account_tx(&account, 0));
+        let events = outcome.result.unwrap();
+        let fee_event = events.iter().find(|event| event.r#type == "handle_fee").unwrap();
+        let attr = |key: &str| {
+            fee_event.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone()
+        };
+        assert_eq!(attr("limit"), "1000000");
+        assert_eq!(attr("used"), "1000000");
+    }
+
+    fn range_remover_instantiate(ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        ctx.store.write(b"a", b"1");
+        ctx.store.write(b"b", b"2");
+        Ok(Response::new())
+    }
+
+    fn range_remover_execute(ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        ctx.store.remove_range(Some(b"a"), Some(b"c"));
+        Ok(Response::new())
+    }
+
+    #[test]
+    fn simulating_tx_reports_removed_ranges() {
+        let mut app = MockApp::new();
+        let remover_contract = ContractBuilder::new(Box::new(range_remover_instantiate))
+            .with_execute(Box::new(range_remover_execute))
+            .build();
+        let (msgs, _, remover) = deploy_at_genesis(remover_contract, "remover");
+        let (account, _) = init_with_account(&mut app, TestGenesis {
+            msgs,
+            ..Default::default()
+        });
+
+        // the range is reported under the contract's namespace, and the keys
+        // of the records in it as written
+        let tx = Tx {
+            msgs: vec![Message::Execute {
+                contract: remover.clone(),
+                msg: to_json_value(&Empty {}).unwrap(),
+                funds: Coins::new_empty(),
+            }],
+            ..account_tx(&account, 0)
+        };
+        let res = app.inner.do_simulate(tx.clone(), true).unwrap();
+        let prefix = concat(CONTRACT_NAMESPACE, &remover);
+        let min = Binary::from([prefix.as_slice(), b"a"].concat());
+        let max = Binary::from([prefix.as_slice(), b"c"].concat());
+        assert_eq!(res.removed_ranges, Some(vec![(Some(min.clone()), Some(max))]));
+        assert!(res.written_keys.unwrap().contains(&min));
+
+        // they're only reported if requested, like the written keys
+        let res = app.inner.do_simulate(tx, false).unwrap();
+        assert_eq!(res.removed_ranges, None);
+    }
+
+    #[test]
+    fn simulation_is_gas_limited() {
+        let mut app = MockApp {
+            inner: App::new(MemDb::new()).with_simulate_gas_limit(100),
+        };
+        let (account, _) = init_with_account(&mut app, TestGenesis::default());
+
+        // the tx's own gas limit is higher, but the node's limit applies. a
+        // storage read alone costs more than that.
+        let res = app.inner.do_simulate(account_tx(&account, 0), false).unwrap();
+        assert!(res.result.as_err().contains("Out of gas"));
+        assert!(res.gas_used > 100);
+    }
+
+    #[test]
+    fn queries_can_be_proved() {
+        let mut app = MockApp::new();
+        let (account, _) = init_with_account(&mut app, TestGenesis::default());
+        let outcome = app.finalize_block(vec![], vec![account_tx(&account, 0)]);
+        let code_hash = app
+            .query(QueryRequest::Account {
+                address: account.clone(),
+            })
+            .as_account()
+            .code_hash;
+
+        // each key the response is derived from is proved against the app hash,
+        // either to hold its current value, or to not exist
+        let assert_proved = |req: QueryRequest, num_keys: usize| {
+            let raw_req = to_json_vec(&req).unwrap();
+            let (_, proofs) = app.inner.do_query_app_raw(&raw_req, 0, true).unwrap();
+            let proofs = proofs.unwrap();
+            assert_eq!(proofs.len(), num_keys);
+            for (key, proof) in proofs {
+                let proof: Proof = from_json_slice(proof).unwrap();
+                let (value, _) = app.inner.do_query_store(&key, 0, false).unwrap();
+                let value_hash = value.map(hash);
+                verify_proof(&outcome.app_hash, &hash(&key), value_hash.as_ref(), &proof).unwrap();
+            }
+        };
+
+        assert_proved(QueryRequest::Info {}, 5);
+        assert_proved(
+            QueryRequest::Account {
+                address: account.clone(),
+            },
+            1,
+        );
+        assert_proved(
+            QueryRequest::Code {
+                hash: code_hash.clone(),
+            },
+            1,
+        );
+        assert_proved(
+            QueryRequest::CodeInfo {
+                hash: code_hash.clone(),
+            },
+            1,
+        );
+        assert_proved(
+            QueryRequest::WasmRaw {
+                contract: account.clone(),
+                key:      b"sequence".to_vec().into(),
+            },
+            1,
+        );
+        assert_proved(
+            QueryRequest::WasmRaw {
+                contract: account.clone(),
+                key:      b"nonexistent".to_vec().into(),
+            },
+            1,
+        );
+        assert_proved(
+            QueryRequest::Multi(vec![QueryRequest::Info {}, QueryRequest::CodeInfo {
+                hash: code_hash,
+            }]),
+            6,
+        );
+
+        // queries that call contracts or iterate over ranges can't be proved,
+        // and neither can batches that include one
+        let accounts = QueryRequest::Accounts {
+            start_after: None,
+            limit:       None,
+        };
+        for req in [
+            QueryRequest::WasmSmart {
+                contract: account,
+                msg:      to_json_value(&Empty {}).unwrap(),
+            },
+            accounts.clone(),
+            QueryRequest::Multi(vec![QueryRequest::Info {}, accounts]),
+        ] {
+            assert!(matches!(
+                app.inner.do_query_app(req, 0, true),
+                Err(AppError::ProofNotSupported)
+            ));
+        }
+    }
+
+    #[test]
+    fn code_info_is_recorded() {
+        let mut app = MockApp::new();
+        let (account, _) = init_with_account(&mut app, TestGenesis::default());
+        let code_hash = app
+            .query(QueryRequest::Account {
+                address: account,
+            })
+            .as_account()
+            .code_hash;
+        let code = app
+            .query(QueryRequest::Code {
+                hash: code_hash.clone(),
+            })
+            .as_code();
+
+        // code uploaded at genesis is attributed to the genesis sender, and
+        // can be instantiated by anyone unless restricted on upload
+        let code_info = app
+            .query(QueryRequest::CodeInfo {
+                hash: code_hash.clone(),
+            })
+            .as_code_info();
+        assert_eq!(code_info.hash, code_hash);
+        assert_eq!(code_info.uploader, GENESIS_SENDER);
+        assert_eq!(code_info.uploaded_at_height, Uint64::new(0));
+        assert_eq!(code_info.size, Uint64::new(code.len() as u64));
+        assert_eq!(code_info.instantiate_permission, Permission::Everybody);
+    }
+
+    #[test]
+    fn query_multi_works() {
+        let mut app = MockApp::new();
+        let (account, _) = init_with_account(&mut app, TestGenesis::default());
+        app.finalize_block(vec![], vec![account_tx(&account, 0)]);
+
+        // a batch query returns the same responses as the individual queries,
+        // in the same order
+        let reqs = vec![
+            QueryRequest::Info {},
+            QueryRequest::Account {
+                address: account.clone(),
+            },
+            QueryRequest::WasmRaw {
+                contract: account,
+                key:      b"sequence".to_vec().into(),
+            },
+        ];
+        let responses = reqs.iter().cloned().map(|req| app.query(req)).collect::<Vec<_>>();
+        assert_eq!(app.query(QueryRequest::Multi(reqs)).as_multi(), responses);
+
+        // an empty batch returns no responses
+        assert!(app.query(QueryRequest::Multi(vec![])).as_multi().is_empty());
+    }
+
+    #[test]
+    fn only_admin_can_update_or_clear_admin() {
+        let mut app = MockApp::new();
+        let (mut msgs, _, alice) = deploy_at_genesis(account_contract(), "alice");
+        let (mut target_msgs, _, target) =
+            deploy_at_genesis(ContractBuilder::new(Box::new(noop_instantiate)).build(), "target");
+        if let Message::Instantiate { admin, .. } = &mut target_msgs[1] {
+            *admin = Some(alice.clone());
+        }
+        msgs.extend(target_msgs);
+        let (bob, _) = init_with_account(&mut app, TestGenesis {
+            msgs,
+            ..Default::default()
+        });
+
+        let admin_tx = |sender: &Addr, sequence: u32, msg: Message| Tx {
+            msgs: vec![msg],
+            ..account_tx(sender, sequence)
+        };
+        let update_admin = |new_admin: &Addr| Message::UpdateAdmin {
+            contract: target.clone(),
+            new_admin: new_admin.clone(),
+        };
+        let clear_admin = || Message::ClearAdmin {
+            contract: target.clone(),
+        };
+        let admin = |app: &MockApp| {
+            app.query(QueryRequest::Account {
+                address: target.clone(),
+            })
+            .as_account()
+            .admin
+        };
+
+        // someone other than the admin can neither update nor clear it
+        let outcome = app.finalize_block(vec![], vec![
+            admin_tx(&bob, 0, update_admin(&bob)),
+            admin_tx(&bob, 1, clear_admin()),
+        ]);
+        assert!(matches!(outcome.tx_results[0].result, Err(AppError::NotAdmin { .. })));
+        assert!(matches!(outcome.tx_results[1].result, Err(AppError::NotAdmin { .. })));
+        assert_eq!(admin(&app), Some(alice.clone()));
+
+        // the admin hands the rights over, and no longer has them
+        let outcome = app.finalize_block(vec![], vec![
+            admin_tx(&alice, 0, update_admin(&bob)),
+            admin_tx(&alice, 1, clear_admin()),
+        ]);
+        assert!(outcome.tx_results[0].result.is_ok());
+        assert!(matches!(outcome.tx_results[1].result, Err(AppError::NotAdmin { .. })));
+        assert_eq!(admin(&app), Some(bob.clone()));
+
+        // once cleared, the admin can't be set again, not even by the former one
+        let outcome = app.finalize_block(vec![], vec![
+            admin_tx(&bob, 2, clear_admin()),
+            admin_tx(&bob, 3, update_admin(&bob)),
+        ]);
+        assert!(outcome.tx_results[0].result.is_ok());
+        assert!(matches!(outcome.tx_results[1].result, Err(AppError::AdminNotSet)));
+        assert_eq!(admin(&app), None);
+    }
+
+    // a contract that executes the first contract in the route, passing on the
+    // rest of it, and records that it has received a reply
+    fn relay_execute(_ctx: MutableCtx, route: Vec<Addr>) -> StdResult<Response> {
+        let Some((next, rest)) = route.split_first() else {
+            return Ok(Response::new());
+        };
+        let msg = Message::Execute {
+            contract: next.clone(),
+            msg: to_json_value(&rest)?,
+            funds: Coins::new_empty(),
+        };
+        Ok(Response::new().add_submessage(SubMessage::reply_on_success(msg, &Empty {})?))
+    }
+
+    fn relay_reply(ctx: SudoCtx, _payload: Empty, _res: SubMsgResult) -> StdResult<Response> {
+        ctx.store.write(b"replied", b"true");
+        Ok(Response::new())
+    }
+
+    /// Start a chain with a mock account, which is the chain's owner, and two
+    /// relays, the first of which is guarded against reentrancy. Returns the
+    /// addresses of the account and the relays.
+    fn init_with_relays(app: &mut MockApp, max_call_depth: u32) -> (Addr, Addr, Addr) {
+        let relay_contract = || {
+            ContractBuilder::new(Box::new(noop_instantiate))
+                .with_execute(Box::new(relay_execute))
+                .with_reply(Box::new(relay_reply))
+                .build()
+        };
+        let (mut msgs, _, guarded) = deploy_at_genesis(relay_contract(), "guarded");
+        let (relay_msgs, _, relay) = deploy_at_genesis(relay_contract(), "relay");
+        if let Message::Instantiate { non_reentrant, .. } = &mut msgs[1] {
+            *non_reentrant = true;
+        }
+        msgs.extend(relay_msgs);
+        let (account, _) = init_with_account(app, TestGenesis {
+            config: Config {
+                max_call_depth,
+                ..mock_config()
+            },
+            msgs,
+            account_is_owner: true,
+            ..Default::default()
+        });
+        (account, guarded, relay)
+    }
+
+    fn relay_tx(account: &Addr, sequence: u32, route: &[&Addr]) -> Tx {
+        Tx {
+            msgs: vec![Message::Execute {
+                contract: route[0].clone(),
+                msg: to_json_value(&route[1..].to_vec()).unwrap(),
+                funds: Coins::new_empty(),
+            }],
+            ..account_tx(account, sequence)
+        }
+    }
+
+    #[test]
+    fn call_depth_is_limited() {
+        let mut app = MockApp::new();
+        let (account, _, relay) = init_with_relays(&mut app, 3);
+
+        // each contract that emits a submessage adds a layer to the call stack.
+        // the last contract in the route doesn't emit any.
+        let outcome = app.finalize_block(vec![], vec![
+            relay_tx(&account, 0, &[&relay; 4]),
+            relay_tx(&account, 1, &[&relay; 5]),
+        ]);
+        assert!(outcome.tx_results[0].result.is_ok());
+        assert!(matches!(
+            outcome.tx_results[1].result,
+            Err(AppError::ExceedMaxCallDepth { max_depth: 3 })
+        ));
+    }
+
+    #[test]
+    fn call_depth_follows_config_updates() {
+        let mut app = MockApp::new();
+        let (account, _, relay) = init_with_relays(&mut app, 3);
+
+        let cfg = app.query(QueryRequest::Info {}).as_info().config;
+        let tx = Tx {
+            msgs: vec![Message::SetConfig {
+                new_cfg: Box::new(Config {
+                    max_call_depth: 4,
+                    ..cfg
+                }),
+            }],
+            ..account_tx(&account, 0)
+        };
+        let outcome = app.finalize_block(vec![], vec![tx, relay_tx(&account, 1, &[&relay; 5])]);
+        assert!(outcome.tx_results[0].result.is_ok());
+        assert!(outcome.tx_results[1].result.is_ok());
+    }
+
+    #[test]
+    fn reentrancy_is_rejected() {
+        let mut app = MockApp::new();
+        let (account, guarded, relay) = init_with_relays(&mut app, DEFAULT_MAX_CALL_DEPTH);
+
+        let outcome = app.finalize_block(vec![], vec![
+            // a guarded contract can't be re-entered, directly or not
+            relay_tx(&account, 0, &[&guarded, &guarded]),
+            relay_tx(&account, 1, &[&guarded, &relay, &guarded]),
+            // but it's given replies while it's on the call stack
+            relay_tx(&account, 2, &[&guarded, &relay]),
+            // and a contract without the guard can be re-entered
+            relay_tx(&account, 3, &[&relay, &relay]),
+        ]);
+        assert!(matches!(outcome.tx_results[0].result, Err(AppError::Reentrancy { .. })));
+        assert!(matches!(outcome.tx_results[1].result, Err(AppError::Reentrancy { .. })));
+        assert!(outcome.tx_results[2].result.is_ok());
+        assert!(outcome.tx_results[3].result.is_ok());
+        assert_eq!(query_raw(&app, &guarded, b"replied"), Some(b"true".to_vec().into()));
+    }
+
+    // a blocker that counts the number of times it's called. it fails in the
+    // first two blocks, after having written to its storage.
+    fn failing_blocker(ctx: SudoCtx) -> StdResult<Response> {
+        let count: u32 = match ctx.store.read(b"count") {
+            Some(bytes) => from_json_slice(bytes)?,
+            None => 0,
+        };
+        ctx.store.write(b"count", &to_json_vec(&(count + 1))?);
+        if ctx.block_height.u64() <= 2 {
+            return Err(StdError::Generic("not yet".into()));
+        }
+        Ok(Response::new())
+    }
+
+    // the `failures` and `disabled` attributes of the blocker failure events
+    // emitted in the block
+    fn blocker_failures(outcome: &BlockOutcome) -> Vec<(String, String)> {
+        outcome
+            .events
+            .iter()
+            .filter(|event| event.r#type == "blocker_failure")
+            .map(|event| {
+                let attr = |key: &str| {
+                    event.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone()
+                };
+                (attr("failures"), attr("disabled"))
+            })
+            .collect()
+    }
+
+    fn failures(count: &str, disabled: bool) -> Vec<(String, String)> {
+        vec![(count.to_string(), disabled.to_string())]
+    }
+
+    #[test]
+    fn skipped_blocker_failures_are_rolled_back() {
+        let mut app = MockApp::new();
+        let blocker_contract = ContractBuilder::new(Box::new(noop_instantiate))
+            .with_before_block(Box::new(failing_blocker))
+            .build();
+        let (msgs, _, blocker) = deploy_at_genesis(blocker_contract, "blocker");
+        init_with_account(&mut app, TestGenesis {
+            config: Config {
+                begin_blockers: vec![blocker.clone()],
+                blocker_failure_policies: BTreeMap::from([(
+                    blocker.clone(),
+                    BlockerFailurePolicy::Skip,
+                )]),
+                ..mock_config()
+            },
+            msgs,
+            ..Default::default()
+        });
+
+        // the failures are recorded, and the blocker's writes are discarded
+        let outcome = app.finalize_block(vec![], vec![]);
+        assert_eq!(blocker_failures(&outcome), failures("1", false));
+        let outcome = app.finalize_block(vec![], vec![]);
+        assert_eq!(blocker_failures(&outcome), failures("2", false));
+        assert_eq!(query_raw(&app, &blocker, b"count"), None);
+
+        let outcome = app.finalize_block(vec![], vec![]);
+        assert!(blocker_failures(&outcome).is_empty());
+        assert_eq!(query_raw(&app, &blocker, b"count"), Some(b"1".to_vec().into()));
+    }
+
+    #[test]
+    fn disabled_blocker_is_enabled_by_config_update() {
+        let mut app = MockApp::new();
+        let blocker_contract = ContractBuilder::new(Box::new(noop_instantiate))
+            .with_before_block(Box::new(failing_blocker))
+            .build();
+        let (msgs, _, blocker) = deploy_at_genesis(blocker_contract, "blocker");
+        let (owner, _) = init_with_account(&mut app, TestGenesis {
+            account_is_owner: true,
+            config: Config {
+                begin_blockers: vec![blocker.clone()],
+                blocker_failure_policies: BTreeMap::from([(
+                    blocker.clone(),
+                    BlockerFailurePolicy::DisableAfter(2),
+                )]),
+                ..mock_config()
+            },
+            msgs,
+            ..Default::default()
+        });
+
+        let outcome = app.finalize_block(vec![], vec![]);
+        assert_eq!(blocker_failures(&outcome), failures("1", false));
+        let outcome = app.finalize_block(vec![], vec![]);
+        assert_eq!(blocker_failures(&outcome), failures("2", true));
+
+        // the blocker is no longer called, even though it would succeed now
+        let outcome = app.finalize_block(vec![], vec![]);
+        assert!(blocker_failures(&outcome).is_empty());
+        assert_eq!(query_raw(&app, &blocker, b"count"), None);
+
+        // the owner updates the config, which enables the blocker again from
+        // the next block on
+        let cfg = app.query(QueryRequest::Info {}).as_info().config;
+        let tx = Tx {
+            msgs: vec![Message::SetConfig {
+                new_cfg: Box::new(cfg),
+            }],
+            ..account_tx(&owner, 0)
+        };
+        let outcome = app.finalize_block(vec![], vec![tx]);
+        assert!(outcome.tx_results[0].result.is_ok());
+        assert_eq!(query_raw(&app, &blocker, b"count"), None);
+
+        app.finalize_block(vec![], vec![]);
+        assert_eq!(query_raw(&app, &blocker, b"count"), Some(b"1".to_vec().into()));
+    }
+
+    #[test]
+    fn blocker_running_out_of_gas_is_skipped() {
+        let mut app = MockApp::new();
+        let blocker_contract = ContractBuilder::new(Box::new(noop_instantiate))
+            .with_after_block(Box::new(failing_blocker))
+            .build();
+        let (msgs, _, blocker) = deploy_at_genesis(blocker_contract, "blocker");
+        init_with_account(&mut app, TestGenesis {
+            config: Config {
+                end_blockers: vec![blocker.clone()],
+                blocker_failure_policies: BTreeMap::from([(
+                    blocker.clone(),
+                    BlockerFailurePolicy::Skip,
+                )]),
+                // a storage read alone costs more than the limit
+                handler_gas_limit: 100,
+                ..mock_config()
+            },
+            msgs,
+            ..Default::default()
+        });
+
+        // the blocker runs out of gas in every block, even once it would
+        // otherwise succeed, but the chain carries on
+        for count in ["1", "2", "3"] {
+            let outcome = app.finalize_block(vec![], vec![]);
+            assert_eq!(blocker_failures(&outcome), failures(count, false));
+        }
+        assert_eq!(query_raw(&app, &blocker, b"count"), None);
+    }
+
+    // a cronjob that counts the number of times it's called
+    fn counting_cronjob(ctx: SudoCtx) -> StdResult<Response> {
+        let count: u32 = match ctx.store.read(b"count") {
+            Some(bytes) => from_json_slice(bytes)?,
+            None => 0,
+        };
+        ctx.store.write(b"count", &to_json_vec(&(count + 1))?);
+        Ok(Response::new())
+    }
+
+    fn cron_contract() -> ContractWrapper {
+        ContractBuilder::new(Box::new(noop_instantiate))
+            .with_cron_execute(Box::new(counting_cronjob))
+            .build()
+    }
+
+    fn next_cronjobs(app: &MockApp, start_after: Option<Addr>, limit: Option<u32>) -> Vec<Addr> {
+        app.query(QueryRequest::NextCronjobs {
+            start_after,
+            limit,
+        })
+        .as_next_cronjobs()
+        .into_iter()
+        .map(|res| res.contract)
+        .collect()
+    }
+
+    #[test]
+    fn new_cronjob_is_not_called_in_first_block() {
+        let mut app = MockApp::new();
+        let (msgs, _, cron) = deploy_at_genesis(cron_contract(), "cron");
+        init_with_account(&mut app, TestGenesis {
+            config: Config {
+                cronjobs: BTreeMap::from([(cron.clone(), Duration::from_nanos(0))]),
+                ..mock_config()
+            },
+            msgs,
+            ..Default::default()
+        });
+
+        // the cronjob is only scheduled in the first block
+        app.finalize_block(vec![], vec![]);
+        assert_eq!(next_cronjobs(&app, None, None), vec![cron.clone()]);
+        assert_eq!(query_raw(&app, &cron, b"count"), None);
+
+        app.finalize_block(vec![], vec![]);
+        assert_eq!(query_raw(&app, &cron, b"count"), Some(b"1".to_vec().into()));
+        app.finalize_block(vec![], vec![]);
+        assert_eq!(query_raw(&app, &cron, b"count"), Some(b"2".to_vec().into()));
+    }
+
+    #[test]
+    fn removed_cronjob_is_unscheduled() {
+        let mut app = MockApp::new();
+        let (msgs, _, cron) = deploy_at_genesis(cron_contract(), "cron");
+        let (owner, _) = init_with_account(&mut app, TestGenesis {
+            account_is_owner: true,
+            config: Config {
+                cronjobs: BTreeMap::from([(cron.clone(), Duration::from_nanos(0))]),
+                ..mock_config()
+            },
+            msgs,
+            ..Default::default()
+        });
+
+        app.finalize_block(vec![], vec![]);
+        assert_eq!(next_cronjobs(&app, None, None), vec![cron.clone()]);
+
+        let cfg = app.query(QueryRequest::Info {}).as_info().config;
+        let tx = Tx {
+            msgs: vec![Message::SetConfig {
+                new_cfg: Box::new(Config {
+                    cronjobs: BTreeMap::new(),
+                    ..cfg
+                }),
+            }],
+            ..account_tx(&owner, 0)
+        };
+        let outcome = app.finalize_block(vec![], vec![tx]);
+        assert!(outcome.tx_results[0].result.is_ok());
+
+        // the schedule is deleted, and the cronjob is no longer called
+        app.finalize_block(vec![], vec![]);
+        assert!(next_cronjobs(&app, None, None).is_empty());
+        let count = query_raw(&app, &cron, b"count");
+        app.finalize_block(vec![], vec![]);
+        assert_eq!(query_raw(&app, &cron, b"count"), count);
+    }
+
+    #[test]
+    fn cronjob_with_overflowing_interval_does_not_halt_chain() {
+        let mut app = MockApp::new();
+        let (msgs, _, cron) = deploy_at_genesis(cron_contract(), "cron");
+        init_with_account(&mut app, TestGenesis {
+            config: Config {
+                cronjobs: BTreeMap::from([(cron.clone(), Duration::from_nanos(u64::MAX))]),
+                ..mock_config()
+            },
+            msgs,
+            ..Default::default()
+        });
+
+        // the next run is capped at the latest representable time, so the
+        // cronjob is scheduled, but never called
+        app.finalize_block(vec![], vec![]);
+        assert_eq!(next_cronjobs(&app, None, None), vec![cron.clone()]);
+        app.finalize_block(vec![], vec![]);
+        assert_eq!(query_raw(&app, &cron, b"count"), None);
+    }
+
+    #[test]
+    fn querying_next_cronjobs_works() {
+        let mut app = MockApp::new();
+        let mut msgs = vec![];
+        let mut crons = vec![];
+        for salt in ["cron1", "cron2", "cron3"] {
+            let (cron_msgs, _, cron) = deploy_at_genesis(cron_contract(), salt);
+            msgs.extend(cron_msgs);
+            crons.push(cron);
+        }
+        crons.sort();
+        let interval = Duration::from_seconds(60).unwrap();
+        init_with_account(&mut app, TestGenesis {
+            config: Config {
+                cronjobs: crons.iter().map(|cron| (cron.clone(), interval)).collect(),
+                ..mock_config()
+            },
+            msgs,
+            ..Default::default()
+        });
+        app.finalize_block(vec![], vec![]);
+
+        assert_eq!(next_cronjobs(&app, None, Some(2)), crons[..2]);
+        assert_eq!(next_cronjobs(&app, Some(crons[1].clone()), Some(2)), crons[2..]);
+        assert_eq!(next_cronjobs(&app, None, None), crons);
+    }
+
+    // a proposal handler that drops spam, and proposes the other txs in reverse
+    // order. it rejects proposals that contain spam.
+    fn proposal_prepare(_ctx: ImmutableCtx, msg: PrepareProposalMsg) -> StdResult<Vec<Binary>> {
+        Ok(msg.txs.into_iter().rev().filter(|tx| tx.as_ref() != b"spam").collect())
+    }
+
+    fn proposal_process(_ctx: ImmutableCtx, msg: ProcessProposalMsg) -> StdResult<()> {
+        if msg.txs.iter().any(|tx| tx.as_ref() == b"spam") {
+            return Err(StdError::Generic("proposal contains spam".into()));
+        }
+        Ok(())
+    }
+
+    /// Start a chain with a proposal handler, and return the block following
+    /// the genesis block.
+    fn init_with_proposal_handler(app: &mut MockApp) -> BlockInfo {
+        let contract = ContractBuilder::new(Box::new(noop_instantiate))
+            .with_prepare_proposal(Box::new(proposal_prepare))
+            .with_process_proposal(Box::new(proposal_process))
+            .build();
+        let (msgs, _, handler) = deploy_at_genesis(contract, "handler");
+        app.init_chain("dev-1", GenesisState {
+            config: Config {
+                proposal_handler: Some(handler),
+                ..mock_config()
+            },
+            msgs,
+            exported: None,
+        });
+        BlockInfo {
+            height:    Uint64::new(1),
+            timestamp: current_time(),
+            hash:      Hash::ZERO,
+        }
+    }
+
+    #[test]
+    fn proposal_handler_prepares_proposal() {
+        let mut app = MockApp::new();
+        let block = init_with_proposal_handler(&mut app);
+        let prepare = |max_tx_bytes: u64| {
+            let txs = [b"tx1".as_slice(), b"spam", b"tx2"];
+            app.inner
+                .do_prepare_proposal_raw(
+                    block.clone(),
+                    txs.to_vec(),
+                    max_tx_bytes,
+                    ExtendedCommitInfo::default(),
+                )
+                .unwrap()
+        };
+        let tx1 = Binary::from(b"tx1".to_vec());
+        let tx2 = Binary::from(b"tx2".to_vec());
+
+        // the handler drops and reorders the txs
+        assert_eq!(prepare(1_000_000), vec![tx2.clone(), tx1]);
+
+        // the txs it returns are truncated to `max_tx_bytes`. each tx takes
+        // up two bytes more than its length.
+        assert_eq!(prepare(9), vec![tx2]);
+        assert!(prepare(4).is_empty());
+    }
+
+    #[test]
+    fn proposal_handler_processes_proposal() {
+        let mut app = MockApp::new();
+        let block = init_with_proposal_handler(&mut app);
+        let process =
+            |txs: &[&[u8]]| app.inner.do_process_proposal_raw(block.clone(), txs.to_vec());
+
+        assert!(process(&[b"tx1", b"tx2"]).is_ok());
+        assert!(process(&[]).is_ok());
+        assert!(matches!(
+            process(&[b"tx1", b"spam"]),
+            Err(AppError::Contract { msg, .. }) if msg.contains("proposal contains spam")
+        ));
+    }
+
+    fn oracle_query(ctx: ImmutableCtx, _msg: Empty) -> StdResult<Json> {
+        let extensions = ctx.store.read(b"extensions").unwrap_or(b"[]".to_vec());
+        from_json_slice(extensions)
+    }
+
+    fn oracle_extend_vote(_ctx: ImmutableCtx) -> StdResult<Binary> {
+        Ok(b"price:100".to_vec().into())
+    }
+
+    fn oracle_verify_vote_extension(
+        _ctx: ImmutableCtx,
+        msg: VerifyVoteExtensionMsg,
+    ) -> StdResult<()> {
+        if !msg.extension.starts_with(b"price:") {
+            return Err(StdError::Generic("malformed price".into()));
+        }
+        Ok(())
+    }
+
+    fn oracle_receive_vote_extensions(
+        ctx: SudoCtx,
+        extensions: Vec<VoteExtension>,
+    ) -> StdResult<Response> {
+        ctx.store.write(b"extensions", &to_json_vec(&extensions)?);
+        Ok(Response::new().add_attribute("num_extensions", extensions.len()))
+    }
+
+    fn oracle_contract() -> ContractWrapper {
+        ContractBuilder::new(Box::new(noop_instantiate))
+            .with_query(Box::new(oracle_query))
+            .with_extend_vote(Box::new(oracle_extend_vote))
+            .with_verify_vote_extension(Box::new(oracle_verify_vote_extension))
+            .with_receive_vote_extensions(Box::new(oracle_receive_vote_extensions))
+            .build()
+    }
+
+    #[test]
+    fn smart_query_is_gas_limited() {
+        let mut app = MockApp {
+            inner: App::new(MemDb::new()).with_query_gas_limit(100),
+        };
+        let (msgs, _, oracle) = deploy_at_genesis(oracle_contract(), "oracle");
+        app.init_chain("dev-1", GenesisState {
+            config: mock_config(),
+            msgs,
+            exported: None,
+        });
+
+        // a storage read alone costs more than the limit
+        let req = QueryRequest::WasmSmart {
+            contract: oracle,
+            msg: to_json_value(&Empty {}).unwrap(),
+        };
+        assert!(matches!(app.inner.do_query_app(req, 0, false), Err(AppError::OutOfGas { .. })));
+    }
+
+    #[test]
+    fn vote_extensions_work() {
+        let mut app = MockApp::new();
+        let (msgs, _, oracle) = deploy_at_genesis(oracle_contract(), "oracle");
+        let genesis_state = GenesisState {
+            config: Config {
+                vote_extension_handler: Some(oracle.clone()),
+                ..mock_config()
+            },
+            msgs,
+            exported: None,
+        };
+        app.init_chain("dev-1", genesis_state);
+
+        // the extension is produced by the contract, and verified by it
+        let extension = app.extend_vote().unwrap();
+        assert_eq!(extension, Binary::from(b"price:100".to_vec()));
+        app.verify_vote_extension(b"val1".to_vec().into(), extension.clone()).unwrap();
+        assert!(app
+            .verify_vote_extension(b"val2".to_vec().into(), b"junk".to_vec().into())
+            .is_err());
+
+        // the extensions are delivered to the contract at the start of the block
+        let vote_extensions = vec![VoteExtension {
+            validator: b"val1".to_vec().into(),
+            power: Uint64::new(10),
+            extension,
+        }];
+        let outcome = app.finalize_block(vote_extensions.clone(), vec![]);
+        assert_eq!(outcome.events[0].r#type, "receive_vote_extensions");
+
+        let received = app
+            .query(QueryRequest::WasmSmart {
+                contract: oracle,
+                msg: to_json_value(&Empty {}).unwrap(),
+            })
+            .as_wasm_smart()
+            .data;
+        assert_eq!(from_json_value::<Vec<VoteExtension>>(received).unwrap(), vote_extensions);
+    }
+
+    #[test]
+    fn vote_extension_handler_is_gas_limited() {
+        let mut app = MockApp::new();
+        let contract = ContractBuilder::new(Box::new(noop_instantiate))
+            .with_extend_vote(Box::new(|ctx: ImmutableCtx| {
+                Ok::<_, StdError>(ctx.store.read(b"price").unwrap_or_default().into())
+            }))
+            .build();
+        let (msgs, _, handler) = deploy_at_genesis(contract, "handler");
+        app.init_chain("dev-1", GenesisState {
+            config: Config {
+                vote_extension_handler: Some(handler),
+                handler_gas_limit: 100,
+                ..mock_config()
+            },
+            msgs,
+            exported: None,
+        });
+
+        // a storage read alone costs more than the limit
+        assert!(matches!(app.extend_vote(), Err(AppError::OutOfGas { .. })));
+    }
+
+    /// Validators with the given voting powers, along with their secp256k1
+    /// signing keys.
+    fn mock_validators(powers: &[u64]) -> Vec<(SigningKey, ValidatorUpdate)> {
+        powers
+            .iter()
+            .enumerate()
+            .map(|(idx, power)| {
+                let key = SigningKey::from_bytes(&[idx as u8 + 1; 32].into()).unwrap();
+                let pubkey = key.verifying_key().to_sec1_bytes().to_vec();
+                (key, ValidatorUpdate {
+                    pubkey: ValidatorPubKey::Secp256k1(pubkey.into()),
+                    power:  Uint64::new(*power),
+                })
+            })
+            .collect()
+    }
+
+    /// A vote for the block at height 1, round 0, with a signed extension.
+    fn signed_vote(
+        key: &SigningKey,
+        validator: &ValidatorUpdate,
+        extension: &[u8],
+    ) -> ExtendedVote {
+        let sign_bytes = vote_extension_sign_bytes(extension, 1, 0, "dev-1");
+        let signature: Signature = key.sign(&sign_bytes);
+        ExtendedVote {
+            validator:           consensus_address(&validator.pubkey),
+            power:               validator.power,
+            block_id_flag:       BlockIdFlag::Commit,
+            extension:           extension.to_vec().into(),
+            extension_signature: signature.to_bytes().to_vec().into(),
+        }
+    }
+
+    fn absent_vote(validator: &ValidatorUpdate) -> ExtendedVote {
+        ExtendedVote {
+            validator:           consensus_address(&validator.pubkey),
+            power:               validator.power,
+            block_id_flag:       BlockIdFlag::Absent,
+            extension:           Binary::empty(),
+            extension_signature: Binary::empty(),
+        }
+    }
+
+    #[test]
+    fn proposed_vote_extensions_are_verified() {
+        let validators = mock_validators(&[10, 20, 30]);
+        let (msgs, _, oracle) = deploy_at_genesis(oracle_contract(), "oracle");
+        let genesis_state = GenesisState {
+            config: Config {
+                vote_extension_handler: Some(oracle),
+                ..mock_config()
+            },
+            msgs,
+            exported: None,
+        };
+        let consensus_params = ConsensusParams {
+            abci: Some(AbciParams {
+                vote_extensions_enable_height: 1,
+            }),
+            ..Default::default()
+        };
+        let block = BlockInfo {
+            height:    Uint64::new(0),
+            timestamp: current_time(),
+            hash:      Hash::ZERO,
+        };
+
+        let mut app = MockApp::new();
+        app.inner
+            .do_init_chain(
+                "dev-1".to_string(),
+                block,
+                genesis_state,
+                consensus_params,
+                validators.iter().map(|(_, validator)| validator.clone()).collect(),
+            )
+            .unwrap();
+        app.finalize_block(vec![], vec![]);
+
+        let block = BlockInfo {
+            height:    Uint64::new(2),
+            timestamp: current_time(),
+            hash:      Hash::ZERO,
+        };
+        let vote = |idx: usize, extension: &[u8]| {
+            signed_vote(&validators[idx].0, &validators[idx].1, extension)
+        };
+        let process = |votes: Vec<ExtendedVote>| {
+            let commit = ExtendedCommitInfo {
+                round: 0,
+                votes,
+            };
+            app.inner.do_process_proposal_raw(block.clone(), vec![to_json_vec(&commit).unwrap()])
+        };
+
+        // the proposer puts the votes in front of the txs, and others accept them
+        let commit = ExtendedCommitInfo {
+            round: 0,
+            votes: vec![vote(0, b"price:100"), vote(1, b"price:101"), vote(2, b"price:99")],
+        };
+        let txs = app
+            .inner
+            .do_prepare_proposal_raw(block.clone(), Vec::<Binary>::new(), 1_000_000, commit)
+            .unwrap();
+        app.inner.do_process_proposal_raw(block.clone(), txs).unwrap();
+
+        // validators that didn't vote may be left out, as long as more than
+        // 2/3 of the voting power voted
+        process(vec![absent_vote(&validators[0].1), vote(1, b"price:101"), vote(2, b"price:99")])
+            .unwrap();
+        let absent = absent_vote(&validators[1].1);
+        assert!(matches!(
+            process(vec![vote(0, b"price:100"), absent, vote(2, b"price:99")]),
+            Err(AppError::InvalidVoteExtensions { .. })
+        ));
+
+        // the proposer can't tamper with an extension
+        let mut tampered = vote(0, b"price:100");
+        tampered.extension = b"price:1000".to_vec().into();
+        assert!(matches!(
+            process(vec![tampered, vote(1, b"price:101"), vote(2, b"price:99")]),
+            Err(AppError::InvalidVoteExtensions { .. })
+        ));
+
+        // or a validator's voting power
+        let mut inflated = vote(0, b"price:100");
+        inflated.power = Uint64::new(100);
+        assert!(matches!(
+            process(vec![inflated, vote(1, b"price:101"), vote(2, b"price:99")]),
+            Err(AppError::InvalidVoteExtensions { .. })
+        ));
+
+        // or make up votes from validators outside the set
+        let outsiders = mock_validators(&[10, 20, 30, 40]);
+        let outsider = signed_vote(&outsiders[3].0, &outsiders[3].1, b"price:100");
+        assert!(matches!(
+            process(vec![outsider, vote(1, b"price:101"), vote(2, b"price:99")]),
+            Err(AppError::InvalidVoteExtensions { .. })
+        ));
+
+        // or count a validator twice
+        assert!(matches!(
+            process(vec![vote(1, b"price:101"), vote(1, b"price:101"), vote(2, b"price:99")]),
+            Err(AppError::InvalidVoteExtensions { .. })
+        ));
+
+        // or attach an extension to a vote that isn't for the block
+        let mut absent = absent_vote(&validators[0].1);
+        absent.extension = b"price:100".to_vec().into();
+        assert!(matches!(
+            process(vec![absent, vote(1, b"price:101"), vote(2, b"price:99")]),
+            Err(AppError::InvalidVoteExtensions { .. })
+        ));
+
+        // a correctly signed extension must still pass the handler's verification
+        assert!(process(vec![vote(0, b"junk"), vote(1, b"price:101"), vote(2, b"price:99")])
+            .is_err());
+    }
+
+    fn validator_set_init(ctx: SudoCtx, validators: Vec<ValidatorUpdate>) -> StdResult<Response> {
+        ctx.store.write(b"validators", &to_json_vec(&validators)?);
+        Ok(Response::new())
+    }
+
+    /// A validator set contract that returns the given updates in the first
+    /// block, and none afterwards.
+    fn validator_set_contract(updates: Vec<ValidatorUpdate>) -> ContractWrapper {
+        ContractBuilder::new(Box::new(noop_instantiate))
+            .with_init_validators(Box::new(validator_set_init))
+            .with_validator_updates(Box::new(move |ctx: SudoCtx| {
+                if ctx.block_height.u64() == 1 {
+                    Ok::<_, StdError>(updates.clone())
+                } else {
+                    Ok(vec![])
+                }
+            }))
+            .build()
+    }
+
+    /// Start a chain with the given genesis validators, whose validator set
+    /// is managed by a contract returning the given updates.
+    fn init_with_validator_set(
+        validators: &[ValidatorUpdate],
+        updates: Vec<ValidatorUpdate>,
+    ) -> (MockApp, Addr) {
+        let (msgs, _, validator_set) =
+            deploy_at_genesis(validator_set_contract(updates), "validator_set");
+        let genesis_state = GenesisState {
+            config: Config {
+                validator_set: Some(validator_set.clone()),
+                ..mock_config()
+            },
+            msgs,
+            exported: None,
+        };
+        let block = BlockInfo {
+            height:    Uint64::new(0),
+            timestamp: current_time(),
+            hash:      Hash::ZERO,
+        };
+
+        let app = MockApp::new();
+        app.inner
+            .do_init_chain(
+                "dev-1".to_string(),
+                block,
+                genesis_state,
+                ConsensusParams::default(),
+                validators.to_vec(),
+            )
+            .unwrap();
+        (app, validator_set)
+    }
+
+    fn consensus_validator(app: &MockApp, validator: &ValidatorUpdate) -> Option<ValidatorUpdate> {
+        let key = CONSENSUS_VALIDATORS.storage_key(&consensus_address(&validator.pubkey));
+        let (value, _) = app.inner.do_query_store(&key, 0, false).unwrap();
+        value.map(|bytes| from_borsh_slice(bytes).unwrap())
+    }
+
+    #[test]
+    fn validator_set_is_initialized_with_genesis_validators() {
+        let validators = mock_validators(&[10, 20]);
+        let validators = validators.into_iter().map(|(_, validator)| validator).collect::<Vec<_>>();
+        let (app, validator_set) = init_with_validator_set(&validators, vec![]);
+
+        assert_eq!(
+            query_raw(&app, &validator_set, b"validators"),
+            Some(to_json_vec(&validators).unwrap().into())
+        );
+        for validator in &validators {
+            assert_eq!(consensus_validator(&app, validator).as_ref(), Some(validator));
+        }
+    }
+
+    #[test]
+    fn validator_updates_take_effect_two_blocks_later() {
+        let mut validators = mock_validators(&[10, 20, 30]);
+        let joining = validators.pop().unwrap().1;
+        let validators = validators.into_iter().map(|(_, validator)| validator).collect::<Vec<_>>();
+        let (mut app, _) = init_with_validator_set(&validators, vec![joining.clone()]);
+
+        // the updates are passed on to CometBFT in the block they're returned
+        let outcome = app.finalize_block(vec![], vec![]);
+        assert_eq!(outcome.validator_updates, vec![joining.clone()]);
+        assert_eq!(consensus_validator(&app, &joining), None);
+
+        let outcome = app.finalize_block(vec![], vec![]);
+        assert!(outcome.validator_updates.is_empty());
+        assert_eq!(consensus_validator(&app, &joining), None);
+
+        // but the validator only signs blocks from two heights later on
+        app.finalize_block(vec![], vec![]);
+        assert_eq!(consensus_validator(&app, &joining), Some(joining));
+    }
+
+    #[test]
+    fn validator_update_with_too_much_power_is_rejected() {
+        let mut validators = mock_validators(&[10, i64::MAX as u64 + 1]);
+        let joining = validators.pop().unwrap().1;
+        let (app, _) = init_with_validator_set(&[validators.remove(0).1], vec![joining]);
+
+        let block = BlockInfo {
+            height:    Uint64::new(1),
+            timestamp: current_time(),
+            hash:      Hash::ZERO,
+        };
+        assert!(matches!(
+            app.inner.do_finalize_block(block, vec![], vec![]),
+            Err(AppError::InvalidValidatorUpdate { .. })
+        ));
+    }
+
+    #[test]
+    fn exporting_state_works() {
+        let mut app = MockApp::new();
+        let (msgs, oracle_code_hash, oracle) = deploy_at_genesis(oracle_contract(), "oracle");
+        let genesis_state = GenesisState {
+            config: Config {
+                vote_extension_handler: Some(oracle.clone()),
+                ..mock_config()
+            },
+            msgs,
+            exported: None,
+        };
+        app.init_chain("dev-1", genesis_state);
+
+        // write something to the contract's storage
+        let vote_extensions = vec![VoteExtension {
+            validator: b"val1".to_vec().into(),
+            power: Uint64::new(10),
+            extension: app.extend_vote().unwrap(),
+        }];
+        app.finalize_block(vote_extensions, vec![]);
+
+        let genesis_state = app.inner.do_export(0).unwrap();
+        let exported = genesis_state.exported.as_ref().unwrap();
+        assert_eq!(exported.chain_id, "dev-1");
+        assert_eq!(exported.height, Uint64::new(1));
+        assert!(exported.codes.contains_key(&oracle_code_hash));
+        let storage = &exported.accounts[&oracle].storage;
+        assert!(storage.contains_key(&Binary::from(b"extensions".to_vec())));
+
+        // the exported state should survive a round trip through JSON, which
+        // is how it's included in `genesis.json`
+        let genesis_state: GenesisState =
+            from_json_slice(to_json_vec(&genesis_state).unwrap()).unwrap();
+
+        // start a new chain from the exported state. the contract is restored
+        // along with its storage, without being instantiated again
+        let mut other = MockApp::new();
+        other.init_chain("dev-2", genesis_state);
+
+        for req in [
+            QueryRequest::Account {
+                address: oracle.clone(),
+            },
+            QueryRequest::CodeInfo {
+                hash: oracle_code_hash,
+            },
+            QueryRequest::WasmSmart {
+                contract: oracle,
+                msg: to_json_value(&Empty {}).unwrap(),
+            },
+        ] {
+            assert_eq!(other.query(req.clone()), app.query(req));
+        }
+    }
+
+    #[test]
+    fn importing_state_keeps_validator_set() {
+        let validators = mock_validators(&[10, 20]);
+        let validators = validators.into_iter().map(|(_, validator)| validator).collect::<Vec<_>>();
+        let (mut app, validator_set) = init_with_validator_set(&validators, vec![]);
+        app.finalize_block(vec![], vec![]);
+        let genesis_state = app.inner.do_export(0).unwrap();
+
+        // the contract isn't initialized again with the genesis validators of
+        // the new chain, but CometBFT's set is taken from them
+        let other = MockApp::new();
+        let block = BlockInfo {
+            height:    Uint64::new(0),
+            timestamp: current_time(),
+            hash:      Hash::ZERO,
+        };
+        other
+            .inner
+            .do_init_chain(
+                "dev-2".to_string(),
+                block,
+                genesis_state,
+                ConsensusParams::default(),
+                validators[..1].to_vec(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            query_raw(&other, &validator_set, b"validators"),
+            Some(to_json_vec(&validators).unwrap().into())
+        );
+        assert_eq!(consensus_validator(&other, &validators[0]).as_ref(), Some(&validators[0]));
+        assert_eq!(consensus_validator(&other, &validators[1]), None);
+    }
+
+    #[test]
+    fn info_query_works_without_consensus_params() {
+        let db = MemDb::new();
+        let mut app = MockApp {
+            inner: App::new(db.clone()),
+        };
+        app.init_chain("dev-1", GenesisState {
+            config: mock_config(),
+            msgs: vec![],
+            exported: None,
+        });
+
+        // a chain started before the consensus params were recorded doesn't
+        // have them, so they're reported as unknown
+        let key = CONSENSUS_PARAMS.storage_key().to_vec();
+        db.flush_and_commit(vec![], Batch::from([(key, Op::Delete)])).unwrap();
+        let info = app.query(QueryRequest::Info {}).as_info();
+        assert_eq!(info.consensus_params, ConsensusParams::default());
+    }
+
+    #[test]
+    fn scheduled_upgrade_works() {
+        let db = MemDb::new();
+        let mut app = MockApp {
+            inner: App::new(db.clone()),
+        };
+        let genesis_state = GenesisState {
+            config: Config {
+                owner: Some(GENESIS_SENDER),
+                ..mock_config()
+            },
+            msgs: vec![Message::ScheduleUpgrade {
+                height: Uint64::new(2),
+                name: "v2".to_string(),
+                info: None,
+            }],
+            exported: None,
+        };
+        app.init_chain("dev-1", genesis_state);
+
+        let next_upgrade = app.query(QueryRequest::Info {}).as_info().next_upgrade.unwrap();
+        assert_eq!(next_upgrade.height, Uint64::new(2));
+
+        // blocks before the upgrade height are processed as usual
+        app.finalize_block(vec![], vec![]);
+
+        // the block at the upgrade height is refused by a binary that doesn't
+        // know about the upgrade
+        let block = BlockInfo {
+            height: Uint64::new(2),
+            timestamp: current_time(),
+            hash: Hash::ZERO,
+        };
+        assert!(matches!(
+            app.inner.do_finalize_block(block, vec![], vec![]),
+            Err(AppError::UpgradeNeeded { .. })
+        ));
+
+        // switch to a binary that does. the handler is run at the upgrade
+        // height, and only then
+        let mut app = MockApp {
+            inner: App::new(db).with_upgrade_handler("v2", |store, _block| {
+                let mut cfg = CONFIG.load(store)?;
+                cfg.max_call_depth += 1;
+                save_config(store, &cfg)?;
+                Ok(())
+            }),
+        };
+        let outcome = app.finalize_block(vec![], vec![]);
+        assert_eq!(outcome.events[0].r#type, "upgrade");
+        app.finalize_block(vec![], vec![]);
+
+        let info = app.query(QueryRequest::Info {}).as_info();
+        assert_eq!(info.config.max_call_depth, DEFAULT_MAX_CALL_DEPTH + 1);
+        assert_eq!(info.next_upgrade, None);
+    }
+
+    #[test]
+    fn state_sync_works() {
+        let snapshot_dir = tempfile::tempdir().unwrap();
+        let snapshot_store = SnapshotStore::new(snapshot_dir.path().into(), 1, 0).unwrap();
+        let mut app = MockApp {
+            inner: App::new(MemDb::new()).with_snapshot_store(snapshot_store),
+        };
+        let genesis_state = GenesisState {
+            config: mock_config(),
+            msgs: vec![],
+            exported: None,
+        };
+        app.init_chain("dev-1", genesis_state);
+
+        // a snapshot is taken in the background when block 1 is committed
+        app.finalize_block(vec![], vec![]);
+        app.inner.wait_for_snapshot();
+        let snapshots = app.inner.do_list_snapshots().unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].height, 1);
+
+        let snapshot = snapshots[0].clone();
+        let (height, app_hash) = app.inner.do_info().unwrap();
+        let chunks = (0..snapshot.chunk_hashes.len() as u32)
+            .map(|index| app.inner.do_load_snapshot_chunk(1, SNAPSHOT_FORMAT, index).unwrap())
+            .collect::<Vec<_>>();
+
+        let other = App::<MemDb, RustVm>::new(MemDb::new());
+
+        // a chunk that doesn't match its hash is rejected
+        other.do_offer_snapshot(snapshot.clone(), app_hash.clone()).unwrap();
+        assert!(matches!(
+            other.do_apply_snapshot_chunk(0, b"junk"),
+            Err(AppError::SnapshotChunkMismatch { index: 0 })
+        ));
+
+        // a chunk that matches its hash, but isn't a valid chunk, rejects the
+        // whole snapshot
+        let malformed = Snapshot {
+            chunk_hashes: vec![hash(b"junk")],
+            ..snapshot.clone()
+        };
+        other.do_offer_snapshot(malformed, app_hash.clone()).unwrap();
+        assert!(matches!(
+            other.do_apply_snapshot_chunk(0, b"junk"),
+            Err(AppError::MalformedSnapshotChunk { index: 0, .. })
+        ));
+
+        // the restored state must match the trusted app hash
+        other.do_offer_snapshot(snapshot.clone(), Hash::ZERO).unwrap();
+        let mut res = Ok(false);
+        for (index, chunk) in chunks.iter().enumerate() {
+            res = other.do_apply_snapshot_chunk(index as u32, chunk);
+        }
+        assert!(matches!(res, Err(AppError::AppHashMismatch { .. })));
+
+        // restore the snapshot. the node ends up with the same state
+        other.do_offer_snapshot(snapshot, app_hash.clone()).unwrap();
+        for (index, chunk) in chunks.iter().enumerate() {
+            let completed = other.do_apply_snapshot_chunk(index as u32, chunk).unwrap();
+            assert_eq!(completed, index == chunks.len() - 1);
+        }
+        assert_eq!(other.do_info().unwrap(), (height, app_hash));
+        assert_eq!(
+            other.do_query_app(QueryRequest::Info {}, 0, false).unwrap().0,
+            app.query(QueryRequest::Info {}),
+        );
+    }
 }
@@ -1,10 +1,10 @@
 use {
-    crate::{Addr, Hash, Message, Timestamp, Uint64},
+    crate::{Addr, Binary, Duration, GasCosts, Hash, Message, Timestamp, Uint64},
     borsh::{BorshDeserialize, BorshSerialize},
     hex_literal::hex,
     serde::{Deserialize, Serialize},
     serde_with::skip_serializing_none,
-    std::collections::BTreeSet,
+    std::collections::{BTreeMap, BTreeSet},
 };
 
 /// Genesis messages don't have senders, so we use this mock up hash as the
@@ -17,11 +17,46 @@ pub const GENESIS_BLOCK_HASH: Hash = Hash(hex!("d04b98f48e8f8bcc15c6ae5ac050801c
 
 /// The chain's genesis state. To be included in the `app_state` field of
 /// CometBFT's `genesis.json`.
+#[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct GenesisState {
-    pub config: Config,
-    pub msgs:   Vec<Message>,
+    pub config:   Config,
+    pub msgs:     Vec<Message>,
+    /// State exported from an existing chain, e.g. for a chain upgrade or a
+    /// testnet fork. It's restored as is, before the genesis messages (if any)
+    /// are executed.
+    pub exported: Option<ExportedState>,
+}
+
+/// The state of a chain at a given height, as exported by `grug export`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ExportedState {
+    /// The ID of the chain the state was exported from. The new chain uses the
+    /// ID in CometBFT's `genesis.json`, which may or may not be the same.
+    pub chain_id: String,
+    /// The height at which the state was exported.
+    pub height:   Uint64,
+    /// Wasm byte codes and their metadata, indexed by code hashes.
+    pub codes:    BTreeMap<Hash, ExportedCode>,
+    /// Accounts and their contract storage, indexed by addresses.
+    pub accounts: BTreeMap<Addr, ExportedAccount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ExportedCode {
+    pub code: Binary,
+    pub info: CodeInfo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ExportedAccount {
+    pub account: Account,
+    /// The records in the contract's substore, with the keys relative to it.
+    pub storage: BTreeMap<Binary, Binary>,
 }
 
 /// Chain-level configurations. Not to be confused with contract-level configs.
@@ -42,6 +77,10 @@ pub struct Config {
     /// using an approach similar to Solana's Metaplex standard:
     /// https://twitter.com/octalmage/status/1695165358955487426
     pub bank: Addr,
+    /// A contract that handles transaction fees. It's called after each
+    /// transaction, via the `handle_fee` entry point, with a report of how
+    /// much gas the transaction has consumed.
+    pub taxman: Addr,
     /// A list of contracts that will be called at the beginning of each block,
     /// before any transaction, in order. Each of them must implement the `before_block`
     /// entry point.
@@ -50,10 +89,91 @@ pub struct Config {
     /// all transactions have been processed, in order. Each of them must
     /// implement the `after_block` entry point.
     pub end_blockers: Vec<Addr>,
+    /// What to do if a begin or end blocker fails. Blockers not listed here
+    /// default to halting the chain.
+    #[serde(default)]
+    pub blocker_failure_policies: BTreeMap<Addr, BlockerFailurePolicy>,
+    /// Contracts that will be called at regular intervals, via the `cron_execute`
+    /// entry point. A contract is called in the first block whose timestamp is
+    /// at least its interval after the last time it was called. Cronjobs are
+    /// subject to the same failure policies as begin and end blockers.
+    #[serde(default)]
+    pub cronjobs: BTreeMap<Addr, Duration>,
     /// Permissions for certain gated actions.
     pub permissions: Permissions,
     /// Code hashes that are allowed as IBC light clients.
     pub allowed_clients: BTreeSet<Hash>,
+    /// Gas costs of the host functions available to contracts. Uses the
+    /// default costs if not specified in the genesis state.
+    #[serde(default)]
+    pub gas_costs: GasCosts,
+    /// The maximum depth of nested submessages and replies. Each contract that
+    /// emits submessages adds one layer to the call stack, until all of them
+    /// have been handled. Defaults to `DEFAULT_MAX_CALL_DEPTH`.
+    #[serde(default = "default_max_call_depth")]
+    pub max_call_depth: u32,
+    /// A contract that manages the validator set. If set, it's called via the
+    /// `init_validators` entry point during genesis, with the validators in
+    /// CometBFT's `genesis.json` (unless its storage is restored from exported
+    /// state instead), and via the `validator_updates` entry point at the end
+    /// of each block, after the end blockers, to return changes to the
+    /// validator set.
+    #[serde(default)]
+    pub validator_set: Option<Addr>,
+    /// A contract that decides the transactions to include in block proposals.
+    /// If set, it's called via the `prepare_proposal` entry point when this
+    /// node proposes a block, and may reorder, drop, or inject transactions;
+    /// and via the `process_proposal` entry point when another validator
+    /// proposes one, to accept or reject it. Otherwise, proposals include the
+    /// transactions in the order they were reaped from the mempool, and are
+    /// always accepted.
+    #[serde(default)]
+    pub proposal_handler: Option<Addr>,
+    /// A contract that produces and verifies vote extensions, e.g. for a price
+    /// oracle. If set, it's called via the `extend_vote` entry point for data
+    /// to attach to this node's precommits, and via `verify_vote_extension` to
+    /// check that of other validators. The extensions are then delivered to it
+    /// via the `receive_vote_extensions` entry point at the start of the next
+    /// block.
+    ///
+    /// Vote extensions must also be enabled in CometBFT's consensus params.
+    #[serde(default)]
+    pub vote_extension_handler: Option<Addr>,
+    /// The maximum amount of gas that the proposal and vote extension handlers,
+    /// begin and end blockers, cronjobs, and the taxman may consume in each
+    /// call. Nobody pays for these calls; this keeps a contract from stalling
+    /// consensus. Defaults to `DEFAULT_HANDLER_GAS_LIMIT`.
+    #[serde(default = "default_handler_gas_limit")]
+    pub handler_gas_limit: u64,
+}
+
+/// The default maximum call depth, if not specified in the genesis state.
+pub const DEFAULT_MAX_CALL_DEPTH: u32 = 32;
+
+fn default_max_call_depth() -> u32 {
+    DEFAULT_MAX_CALL_DEPTH
+}
+
+/// The default gas limit of the proposal and vote extension handlers, blockers,
+/// cronjobs, and the taxman, if not specified in the genesis state.
+pub const DEFAULT_HANDLER_GAS_LIMIT: u64 = 100_000_000;
+
+fn default_handler_gas_limit() -> u64 {
+    DEFAULT_HANDLER_GAS_LIMIT
+}
+
+/// What to do if a begin or end blocker fails.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub enum BlockerFailurePolicy {
+    /// Abort the block, which halts the chain.
+    Halt,
+    /// Discard the blocker's state changes, emit an event, and move on.
+    Skip,
+    /// Same as `Skip`, but stop calling the blocker once it has failed this
+    /// many times in a row. Updating the config resets the failure counts of
+    /// all blockers, which enables the disabled ones again.
+    DisableAfter(u32),
 }
 
 #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
@@ -86,10 +206,43 @@ pub struct BlockInfo {
     pub hash:      Hash,
 }
 
+/// A chain upgrade scheduled by the owner.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradePlan {
+    /// The height at which the upgrade takes effect.
+    pub height: Uint64,
+    /// The name under which the new binary registers its upgrade handler.
+    pub name:   String,
+    /// Additional information for node operators, e.g. where to download the
+    /// new binary.
+    pub info:   Option<String>,
+}
+
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct Account {
-    pub code_hash: Hash,
-    pub admin:     Option<Addr>,
+    pub code_hash:     Hash,
+    pub admin:         Option<Addr>,
+    /// If true, the contract can't be called while it's already on the call
+    /// stack, i.e. while the submessages it has emitted are being handled.
+    #[serde(default)]
+    pub non_reentrant: bool,
+}
+
+/// Metadata of a Wasm byte code, saved alongside the code itself.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CodeInfo {
+    /// The account that uploaded the code.
+    pub uploader:               Addr,
+    /// The block height at which the code was uploaded.
+    pub uploaded_at_height:     Uint64,
+    /// Size of the code in bytes.
+    pub size:                   Uint64,
+    /// Who is allowed to instantiate contracts using this code. This is
+    /// enforced in addition to the chain-level instantiate permission.
+    pub instantiate_permission: Permission,
 }
@@ -0,0 +1,74 @@
+use {
+    crate::Duration,
+    borsh::{BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize},
+    serde_with::skip_serializing_none,
+};
+
+/// CometBFT consensus parameters that the chain can change.
+///
+/// Each section is optional. When used as an update, sections that are `None`
+/// are left unchanged.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ConsensusParams {
+    pub block:     Option<BlockParams>,
+    pub evidence:  Option<EvidenceParams>,
+    pub validator: Option<ValidatorParams>,
+    pub abci:      Option<AbciParams>,
+}
+
+impl ConsensusParams {
+    /// Apply the given updates, replacing the sections that are set in them.
+    pub fn merge(&mut self, updates: ConsensusParams) {
+        if updates.block.is_some() {
+            self.block = updates.block;
+        }
+        if updates.evidence.is_some() {
+            self.evidence = updates.evidence;
+        }
+        if updates.validator.is_some() {
+            self.validator = updates.validator;
+        }
+        if updates.abci.is_some() {
+            self.abci = updates.abci;
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BlockParams {
+    /// Maximum size of a block, in bytes. -1 means the maximum allowed by
+    /// CometBFT, which is 100 MiB.
+    pub max_bytes: i64,
+    /// Maximum total gas limit of the transactions in a block. -1 means no limit.
+    pub max_gas:   i64,
+}
+
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct EvidenceParams {
+    /// Evidence older than this many blocks *and* `max_age_duration` is
+    /// considered stale and rejected.
+    pub max_age_num_blocks: i64,
+    pub max_age_duration:   Duration,
+    /// Maximum total size of evidence in a block, in bytes.
+    pub max_bytes:          i64,
+}
+
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorParams {
+    /// Public key types that validators can use, e.g. `ed25519`.
+    pub pub_key_types: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AbciParams {
+    /// The height from which validators must attach vote extensions to their
+    /// precommits. Zero means vote extensions are disabled.
+    pub vote_extensions_enable_height: i64,
+}
@@ -0,0 +1,31 @@
+use {
+    crate::{StdResult, Uint64},
+    borsh::{BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize},
+};
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// A span of time in nanosecond precision.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duration(Uint64);
+
+impl Duration {
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(Uint64::new(nanos))
+    }
+
+    pub fn from_seconds(seconds: u64) -> StdResult<Self> {
+        Uint64::new(seconds).checked_mul(Uint64::new(NANOS_PER_SECOND)).map(Self)
+    }
+
+    #[inline]
+    pub fn nanos(&self) -> u64 {
+        self.0.u64()
+    }
+
+    #[inline]
+    pub fn seconds(&self) -> u64 {
+        self.0.u64() / NANOS_PER_SECOND
+    }
+}
@@ -124,6 +124,13 @@ pub enum StdError {
         ty:     &'static str,
         reason: String,
     },
+
+    #[error("Out of gas! limit: {limit}, used: {used}, comment: {comment}")]
+    OutOfGas {
+        limit:   u64,
+        used:    u64,
+        comment: &'static str,
+    },
 }
 
 impl StdError {
@@ -234,6 +241,48 @@ impl StdError {
             reason: reason.to_string(),
         }
     }
+
+    pub fn out_of_gas(limit: u64, used: u64, comment: &'static str) -> Self {
+        Self::OutOfGas {
+            limit,
+            used,
+            comment,
+        }
+    }
+}
+
+impl StdError {
+    /// The ABCI codespace under which standard errors are reported.
+    pub const CODESPACE: &'static str = "std";
+
+    /// Return the ABCI error code of this error. Each variant has a unique code
+    /// that must not change, as clients rely on them to identify errors.
+    pub fn code(&self) -> u32 {
+        match self {
+            StdError::FromHex(_) => 1,
+            StdError::TryFromSlice(_) => 2,
+            StdError::Generic(_) => 3,
+            StdError::VerificationFailed => 4,
+            StdError::ParseNumber { .. } => 5,
+            StdError::ParseCoins { .. } => 6,
+            StdError::Payment { .. } => 7,
+            StdError::DenomNotFound { .. } => 8,
+            StdError::DataNotFound { .. } => 9,
+            StdError::IteratorNotFound { .. } => 10,
+            StdError::OverflowConversion { .. } => 11,
+            StdError::OverflowAdd { .. } => 12,
+            StdError::OverflowSub { .. } => 13,
+            StdError::OverflowMul { .. } => 14,
+            StdError::OverflowPow { .. } => 15,
+            StdError::OverflowShl { .. } => 16,
+            StdError::OverflowShr { .. } => 17,
+            StdError::DivisionByZero { .. } => 18,
+            StdError::RemainderByZero { .. } => 19,
+            StdError::Serialize { .. } => 20,
+            StdError::Deserialize { .. } => 21,
+            StdError::OutOfGas { .. } => 22,
+        }
+    }
 }
 
 pub type StdResult<T> = std::result::Result<T, StdError>;
@@ -0,0 +1,102 @@
+use {
+    crate::{Addr, Uint64},
+    borsh::{BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize},
+};
+
+/// The gas cost of calling a host function. Consists of a flat fee charged on
+/// every call, plus a variable fee charged for each byte of data moved between
+/// the host and the contract.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct GasCost {
+    pub flat:     u64,
+    pub per_byte: u64,
+}
+
+impl GasCost {
+    pub const fn new(flat: u64, per_byte: u64) -> Self {
+        Self { flat, per_byte }
+    }
+
+    /// Calculate the cost of a call that moves the given number of bytes.
+    pub fn cost(&self, bytes: usize) -> u64 {
+        self.per_byte.saturating_mul(bytes as u64).saturating_add(self.flat)
+    }
+}
+
+/// Gas costs of the host functions available to contracts.
+///
+/// For comparison, each Wasm instruction costs 1 gas.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct GasCosts {
+    /// Charged per byte of the key and the value that is read.
+    pub db_read:          GasCost,
+    /// Charged per byte of the iteration bounds.
+    pub db_scan:          GasCost,
+    /// Charged per byte of the key and the value of the record that is
+    /// returned.
+    pub db_next:          GasCost,
+    /// Charged per byte of the key and the value that is written.
+    pub db_write:         GasCost,
+    /// Charged per byte of the key that is removed.
+    pub db_remove:        GasCost,
+    /// Charged per byte of the bounds of the range that is removed. Each record
+    /// in the range is additionally charged `db_remove`, since the cost of
+    /// removing a range grows with the number of records in it.
+    pub db_remove_range:  GasCost,
+    /// Charged per byte of the request and the response. This is in addition
+    /// to the gas consumed by the contract being queried, if any.
+    pub query_chain:      GasCost,
+    /// Charged per byte of the message hash, signature, and public key.
+    pub secp256k1_verify: GasCost,
+    /// Charged per byte of the message hash, signature, and public key.
+    pub secp256r1_verify: GasCost,
+}
+
+impl Default for GasCosts {
+    fn default() -> Self {
+        Self {
+            db_read:          GasCost::new(1000, 3),
+            db_scan:          GasCost::new(1000, 3),
+            db_next:          GasCost::new(30, 3),
+            db_write:         GasCost::new(2000, 30),
+            db_remove:        GasCost::new(1000, 0),
+            db_remove_range:  GasCost::new(1000, 3),
+            query_chain:      GasCost::new(1000, 3),
+            secp256k1_verify: GasCost::new(100_000, 0),
+            secp256r1_verify: GasCost::new(150_000, 0),
+        }
+    }
+}
+
+/// A report of the gas consumed by a transaction. Passed to the taxman
+/// contract's `handle_fee` entry point after each transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct GasReport {
+    /// The account that sent the transaction.
+    pub sender: Addr,
+    /// The gas limit that the sender requested for the transaction.
+    pub limit:  Uint64,
+    /// The amount of gas that was actually consumed. May exceed the limit if
+    /// the transaction ran out of gas.
+    pub used:   Uint64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calculating_gas_cost_works() {
+        let cost = GasCost::new(1000, 3);
+        assert_eq!(cost.cost(0), 1000);
+        assert_eq!(cost.cost(10), 1030);
+
+        // should saturate instead of overflowing
+        let cost = GasCost::new(1, u64::MAX);
+        assert_eq!(cost.cost(2), u64::MAX);
+    }
+}
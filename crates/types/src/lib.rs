@@ -3,18 +3,22 @@ mod app;
 mod bank;
 mod binary;
 mod coin;
+mod consensus;
 mod context;
 mod db;
 mod decimal;
 mod decimal256;
+mod duration;
 mod empty;
 mod error;
 mod event;
 mod forward_ref;
+mod gas;
 mod hash;
 mod ibc;
 #[cfg(not(target_arch = "wasm32"))]
 mod mocks;
+mod proposal;
 mod query;
 mod response;
 mod result;
@@ -27,12 +31,14 @@ mod uint256;
 mod uint512;
 mod uint64;
 mod utils;
+mod validator;
+mod vote;
 
 pub use {
-    address::*, app::*, bank::*, binary::*, coin::*, context::*, db::*, decimal::*, decimal256::*,
-    empty::*, error::*, event::*, hash::*, ibc::*, query::*, response::*, result::*,
-    serde::*, timestamp::*, traits::*, tx::*, uint128::*, uint256::*, uint512::*, uint64::*,
-    utils::*,
+    address::*, app::*, bank::*, binary::*, coin::*, consensus::*, context::*, db::*, decimal::*,
+    decimal256::*, duration::*, empty::*, error::*, event::*, gas::*, hash::*, ibc::*, proposal::*,
+    query::*, response::*, result::*, serde::*, timestamp::*, traits::*, tx::*, uint128::*,
+    uint256::*, uint512::*, uint64::*, utils::*, validator::*, vote::*,
 };
 
 // Mocks need to be excluded in Wasm builds because they depend on k256/p256
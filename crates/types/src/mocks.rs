@@ -59,6 +59,20 @@ impl Storage for MockStorage {
     fn remove(&mut self, key: &[u8]) {
         self.data.remove(key);
     }
+
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        // same as in `scan`, we don't want to panic if min > max
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                return;
+            }
+        }
+
+        self.data.retain(|key, _| {
+            min.is_some_and(|min| key.as_slice() < min)
+                || max.is_some_and(|max| key.as_slice() >= max)
+        });
+    }
 }
 
 // ------------------------------------ api ------------------------------------
@@ -0,0 +1,34 @@
+use {
+    crate::{Binary, Uint64, VoteExtension},
+    serde::{Deserialize, Serialize},
+};
+
+/// Sent to the proposal handler contract's `prepare_proposal` entry point when
+/// this node is the block proposer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PrepareProposalMsg {
+    /// Raw transactions from the mempool, in the order CometBFT reaped them.
+    pub txs:             Vec<Binary>,
+    /// The maximum total size, in bytes, of the transactions in the proposal.
+    /// Transactions in excess of this are dropped by the host.
+    pub max_tx_bytes:    Uint64,
+    /// The vote extensions from the previous height, if the config names a
+    /// vote extension handler contract. The host puts them in front of the
+    /// returned transactions.
+    pub vote_extensions: Vec<VoteExtension>,
+}
+
+/// Sent to the proposal handler contract's `process_proposal` entry point when
+/// a block is proposed by another validator.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ProcessProposalMsg {
+    /// Raw transactions in the proposed block, not including the vote
+    /// extensions.
+    pub txs:             Vec<Binary>,
+    /// The vote extensions from the previous height, if the config names a
+    /// vote extension handler contract. By the time the handler is called,
+    /// the host has verified them against the validator set.
+    pub vote_extensions: Vec<VoteExtension>,
+}
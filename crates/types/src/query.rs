@@ -1,5 +1,8 @@
 use {
-    crate::{Addr, Binary, BlockInfo, Coin, Coins, Config, Hash, Json},
+    crate::{
+        Addr, Binary, BlockInfo, Coin, Coins, Config, ConsensusParams, Hash, Json, Permission,
+        Timestamp, Uint64, UpgradePlan,
+    },
     serde::{Deserialize, Serialize},
     serde_with::skip_serializing_none,
 };
@@ -48,6 +51,19 @@ pub enum QueryRequest {
         start_after: Option<Hash>,
         limit: Option<u32>,
     },
+    /// Metadata of a single Wasm byte code.
+    /// Returns: CodeInfoResponse
+    CodeInfo {
+        hash: Hash,
+    },
+    /// Enumerate metadata of all Wasm byte codes, optionally only those
+    /// uploaded by the given account.
+    /// Returns: Vec<CodeInfoResponse>
+    CodeInfos {
+        start_after: Option<Hash>,
+        limit: Option<u32>,
+        uploader: Option<Addr>,
+    },
     /// Metadata of a single account.
     /// Returns: AccountResponse
     Account {
@@ -59,6 +75,12 @@ pub enum QueryRequest {
         start_after: Option<Addr>,
         limit: Option<u32>,
     },
+    /// Enumerate the cronjobs and the time at which each of them is next due.
+    /// Returns: Vec<CronjobResponse>
+    NextCronjobs {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
     /// A raw key-value pair in a contract's internal state.
     /// Returns: WasmRawResponse
     WasmRaw {
@@ -71,13 +93,29 @@ pub enum QueryRequest {
         contract: Addr,
         msg: Json,
     },
+    /// Perform multiple queries at once. All queries are evaluated against the
+    /// same state, i.e. at the same block height.
+    /// Returns: Vec<QueryResponse>, in the same order as the requests.
+    Multi(Vec<QueryRequest>),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct InfoResponse {
     pub chain_id: String,
     pub config: Config,
+    pub consensus_params: ConsensusParams,
     pub last_finalized_block: BlockInfo,
+    /// The chain upgrade that's scheduled, if any.
+    pub next_upgrade: Option<UpgradePlan>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CodeInfoResponse {
+    pub hash: Hash,
+    pub uploader: Addr,
+    pub uploaded_at_height: Uint64,
+    pub size: Uint64,
+    pub instantiate_permission: Permission,
 }
 
 #[skip_serializing_none]
@@ -88,6 +126,12 @@ pub struct AccountResponse {
     pub admin: Option<Addr>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CronjobResponse {
+    pub contract: Addr,
+    pub next_run: Timestamp,
+}
+
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct WasmRawResponse {
@@ -120,10 +164,14 @@ pub enum QueryResponse {
     Supplies(Coins),
     Code(Binary),
     Codes(Vec<Hash>),
+    CodeInfo(CodeInfoResponse),
+    CodeInfos(Vec<CodeInfoResponse>),
     Account(AccountResponse),
     Accounts(Vec<AccountResponse>),
+    NextCronjobs(Vec<CronjobResponse>),
     WasmRaw(WasmRawResponse),
     WasmSmart(WasmSmartResponse),
+    Multi(Vec<QueryResponse>),
 }
 
 // TODO: can we use a macro to implement these?
@@ -177,6 +225,20 @@ impl QueryResponse {
         hashes
     }
 
+    pub fn as_code_info(self) -> CodeInfoResponse {
+        let Self::CodeInfo(resp) = self else {
+            panic!("QueryResponse is not CodeInfo");
+        };
+        resp
+    }
+
+    pub fn as_code_infos(self) -> Vec<CodeInfoResponse> {
+        let Self::CodeInfos(resp) = self else {
+            panic!("QueryResponse is not CodeInfos");
+        };
+        resp
+    }
+
     pub fn as_account(self) -> AccountResponse {
         let Self::Account(resp) = self else {
             panic!("QueryResponse is not Account");
@@ -191,6 +253,13 @@ impl QueryResponse {
         resp
     }
 
+    pub fn as_next_cronjobs(self) -> Vec<CronjobResponse> {
+        let Self::NextCronjobs(resp) = self else {
+            panic!("QueryResponse is not NextCronjobs");
+        };
+        resp
+    }
+
     pub fn as_wasm_raw(self) -> WasmRawResponse {
         let Self::WasmRaw(resp) = self else {
             panic!("QueryResponse is not WasmRaw");
@@ -204,4 +273,11 @@ impl QueryResponse {
         };
         resp
     }
+
+    pub fn as_multi(self) -> Vec<QueryResponse> {
+        let Self::Multi(resps) = self else {
+            panic!("QueryResponse is not Multi");
+        };
+        resps
+    }
 }
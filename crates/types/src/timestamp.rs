@@ -1,5 +1,5 @@
 use {
-    crate::Uint64,
+    crate::{Duration, Uint64},
     borsh::{BorshDeserialize, BorshSerialize},
     serde::{Deserialize, Serialize},
 };
@@ -27,6 +27,15 @@ impl Timestamp {
         self.plus_nanos(seconds * NANOS_PER_SECOND)
     }
 
+    pub fn plus(&self, duration: Duration) -> Self {
+        self.plus_nanos(duration.nanos())
+    }
+
+    /// Add the duration, capping the result at the latest representable time.
+    pub fn saturating_plus(&self, duration: Duration) -> Self {
+        Self::from_nanos(self.nanos().saturating_add(duration.nanos()))
+    }
+
     // TODO: add more plus/minus methods
 
     #[inline]
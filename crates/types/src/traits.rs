@@ -33,6 +33,20 @@ pub trait Storage: DynClone + Send + Sync {
 
     fn remove(&mut self, key: &[u8]);
 
+    /// Remove all records whose keys are within the given bounds. Same as with
+    /// `scan`, the minimum bound is inclusive, the maximum bound is exclusive,
+    /// and nothing is removed if min > max.
+    ///
+    /// The default implementation here is to scan the keys and remove them one
+    /// by one. Overwrite this implementation if there are more efficient
+    /// approaches.
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        let keys = self.scan(min, max, Order::Ascending).map(|(k, _)| k).collect::<Vec<_>>();
+        for key in keys {
+            self.remove(&key);
+        }
+    }
+
     /// Perform a batch of writes and removes altogether, ideally atomically.
     ///
     /// The batch is provided by value instead of by reference (unlike other
@@ -78,6 +92,10 @@ impl Storage for Box<dyn Storage> {
         self.as_mut().remove(key)
     }
 
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        self.as_mut().remove_range(min, max)
+    }
+
     fn flush(&mut self, batch: Batch) {
         self.as_mut().flush(batch)
     }
@@ -1,16 +1,75 @@
 use {
-    crate::{Addr, Binary, Coins, Config, Hash, Json},
+    crate::{
+        Addr, Binary, BlockInfo, Coins, Config, ConsensusParams, Event, GenericResult, Hash, Json,
+        Permission, Timestamp, Uint64,
+    },
     serde::{Deserialize, Serialize},
     serde_with::skip_serializing_none,
 };
 
+#[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Tx {
     pub sender: Addr,
     pub msgs: Vec<Message>,
+    /// The maximum amount of gas this transaction is allowed to consume.
+    /// If this is exceeded, execution is aborted and the state changes made
+    /// by the messages are discarded.
+    pub gas_limit: Uint64,
+    /// The last block height at which this transaction can be included.
+    /// `None` means the transaction doesn't expire by height.
+    #[serde(default)]
+    pub expiry_height: Option<Uint64>,
+    /// The last block timestamp at which this transaction can be included.
+    /// `None` means the transaction doesn't expire by time.
+    #[serde(default)]
+    pub expiry_timestamp: Option<Timestamp>,
     pub credential: Binary,
 }
 
+impl Tx {
+    /// Return whether the transaction can no longer be included in the given
+    /// block, i.e. the block is later than either of the expiry height or
+    /// timestamp.
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.expiry_height.is_some_and(|height| block.height > height)
+            || self.expiry_timestamp.is_some_and(|timestamp| block.timestamp > timestamp)
+    }
+}
+
+/// A request to execute a transaction in simulation mode, without persisting
+/// any state changes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SimulateRequest {
+    pub tx: Tx,
+    /// Whether to include the keys written to or deleted by the transaction,
+    /// and the ranges it removed, in the response.
+    #[serde(default)]
+    pub with_written_keys: bool,
+}
+
+/// The outcome of a transaction simulation.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SimulateResponse {
+    /// The amount of gas consumed by the transaction. The transaction's gas
+    /// limit is not enforced during simulation; the node's own limit is.
+    pub gas_used: u64,
+    /// Events emitted, or the error if the transaction failed.
+    pub result: GenericResult<Vec<Event>>,
+    /// The keys written to or deleted by the transaction. `None` if not
+    /// requested.
+    ///
+    /// This includes the keys of existing records that fall in a range removed
+    /// by the transaction.
+    pub written_keys: Option<Vec<Binary>>,
+    /// The ranges removed by the transaction, as the minimum (inclusive) and
+    /// maximum (exclusive) bounds of each, e.g. when a map is cleared. Ranges
+    /// with no records in them aren't included. `None` if not requested.
+    pub removed_ranges: Option<Vec<(Option<Binary>, Option<Binary>)>>,
+}
+
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -20,7 +79,26 @@ pub enum Message {
     /// Only the `owner` is authorized to do this. If the owner is set to `None`,
     /// no one can update the config.
     SetConfig {
-        new_cfg: Config,
+        // boxed, since the config is much larger than the other variants
+        new_cfg: Box<Config>,
+    },
+    /// Schedule changes to CometBFT's consensus parameters, which are passed
+    /// on to CometBFT at the end of the block.
+    ///
+    /// Only the `owner` is authorized to do this.
+    UpdateConsensusParams {
+        updates: ConsensusParams,
+    },
+    /// Schedule a chain upgrade at the given height. From that height on, the
+    /// node refuses to process blocks, unless its binary has registered a
+    /// handler for the upgrade's name, which is run once at that height.
+    /// Replaces the upgrade that's currently scheduled, if any.
+    ///
+    /// Only the `owner` is authorized to do this.
+    ScheduleUpgrade {
+        height: Uint64,
+        name: String,
+        info: Option<String>,
     },
     /// Send coins to the given recipient address.
     Transfer {
@@ -30,6 +108,9 @@ pub enum Message {
     /// Upload a Wasm binary code and store it in the chain's state.
     Upload {
         code: Binary,
+        /// Who is allowed to instantiate contracts using this code.
+        /// Defaults to everybody if not provided.
+        instantiate_permission: Option<Permission>,
     },
     /// Register a new account.
     Instantiate {
@@ -38,6 +119,10 @@ pub enum Message {
         salt: Binary,
         funds: Coins,
         admin: Option<Addr>,
+        /// Whether the contract refuses to be called while it's already on the
+        /// call stack. Replies to the contract's own submessages are allowed.
+        #[serde(default)]
+        non_reentrant: bool,
     },
     /// Execute a contract.
     Execute {
@@ -54,6 +139,20 @@ pub enum Message {
         new_code_hash: Hash,
         msg: Json,
     },
+    /// Update the `admin` associated with a contract.
+    ///
+    /// Only the contract's current `admin` is authorized to do this.
+    UpdateAdmin {
+        contract: Addr,
+        new_admin: Addr,
+    },
+    /// Remove the `admin` associated with a contract. After this, the contract
+    /// can no longer be migrated.
+    ///
+    /// Only the contract's current `admin` is authorized to do this.
+    ClearAdmin {
+        contract: Addr,
+    },
     /// Create a new IBC light client.
     CreateClient {
         code_hash: Hash,
@@ -72,3 +171,38 @@ pub enum Message {
         misbehavior: Json,
     },
 }
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, test_case::test_case};
+
+    #[test_case(None, None, 100, 100 => false; "no expiry")]
+    #[test_case(Some(100), None, 100, 100 => false; "at expiry height")]
+    #[test_case(Some(100), None, 101, 100 => true; "after expiry height")]
+    #[test_case(None, Some(100), 101, 100 => false; "at expiry timestamp")]
+    #[test_case(None, Some(100), 100, 101 => true; "after expiry timestamp")]
+    #[test_case(Some(200), Some(100), 100, 101 => true; "either expiry is enough")]
+    fn checking_expiry(
+        expiry_height: Option<u64>,
+        expiry_seconds: Option<u64>,
+        height: u64,
+        seconds: u64,
+    ) -> bool {
+        let tx = Tx {
+            sender: Addr::mock(1),
+            msgs: vec![],
+            gas_limit: Uint64::ZERO,
+            expiry_height: expiry_height.map(Uint64::new),
+            expiry_timestamp: expiry_seconds.map(Timestamp::from_seconds),
+            credential: Binary::empty(),
+        };
+        let block = BlockInfo {
+            height:    Uint64::new(height),
+            timestamp: Timestamp::from_seconds(seconds),
+            hash:      Hash::ZERO,
+        };
+        tx.is_expired(&block)
+    }
+}
@@ -0,0 +1,23 @@
+use {
+    crate::{Binary, Uint64},
+    borsh::{BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize},
+};
+
+/// A change to the validator set, to be passed on to CometBFT.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorUpdate {
+    pub pubkey: ValidatorPubKey,
+    /// The validator's new voting power. Zero removes it from the set.
+    pub power:  Uint64,
+}
+
+/// The consensus public key of a validator. Only key types that CometBFT
+/// accepts for validators are supported.
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub enum ValidatorPubKey {
+    Ed25519(Binary),
+    Secp256k1(Binary),
+}
@@ -0,0 +1,67 @@
+use {
+    crate::{Binary, Uint64},
+    serde::{Deserialize, Serialize},
+};
+
+/// Data a validator attached to its precommit vote, via the vote extension
+/// handler contract's `extend_vote` entry point.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct VoteExtension {
+    /// The validator's consensus address. Not to be confused with an account
+    /// address.
+    pub validator: Binary,
+    /// The validator's voting power.
+    pub power:     Uint64,
+    pub extension: Binary,
+}
+
+/// Sent to the vote extension handler contract's `verify_vote_extension` entry
+/// point when a precommit from another validator is received.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct VerifyVoteExtensionMsg {
+    /// The consensus address of the validator that sent the vote.
+    pub validator: Binary,
+    pub extension: Binary,
+}
+
+/// How a validator voted for the previous block, as recorded in CometBFT's
+/// commit.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockIdFlag {
+    /// The validator's vote wasn't received.
+    Absent,
+    /// The validator voted for the block.
+    Commit,
+    /// The validator voted for nil.
+    Nil,
+}
+
+/// A validator's precommit vote for the previous block, along with its vote
+/// extension and the validator's signature over it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ExtendedVote {
+    /// The validator's consensus address.
+    pub validator:           Binary,
+    /// The validator's voting power.
+    pub power:               Uint64,
+    pub block_id_flag:       BlockIdFlag,
+    /// Empty unless the validator voted for the block.
+    pub extension:           Binary,
+    /// Empty unless the validator voted for the block.
+    pub extension_signature: Binary,
+}
+
+/// The votes for the previous block, as CometBFT provides them to the block
+/// proposer. The proposer includes them in the block, so that other validators
+/// can verify the vote extensions and deliver them in FinalizeBlock.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ExtendedCommitInfo {
+    /// The round in which the previous block was committed.
+    pub round: u32,
+    pub votes: Vec<ExtendedVote>,
+}
@@ -1,13 +1,20 @@
 use {
-    crate::{Contract, ExecuteFn, InstantiateFn, MigrateFn, QueryFn, ReceiveFn, ReplyFn},
+    crate::{
+        AfterBlockFn, AfterTxFn, BeforeBlockFn, BeforeTxFn, Contract, CronExecuteFn, ExecuteFn,
+        ExtendVoteFn, HandleFeeFn, InitValidatorsFn, InstantiateFn, MigrateFn, PrepareProposalFn,
+        ProcessProposalFn, QueryFn, ReceiveFn, ReceiveVoteExtensionsFn, ReplyFn,
+        ValidatorUpdatesFn, VerifyVoteExtensionFn,
+    },
     borsh::{BorshDeserialize, BorshSerialize},
     grug_types::{
-        from_json_value, Api, Context, GenericResult, Json, Querier, Response, Storage,
-        SubMsgResult,
+        from_json_value, Api, Binary, Context, GasReport, GenericResult, Json, PrepareProposalMsg,
+        ProcessProposalMsg, Querier, Response, Storage, SubMsgResult, Tx, ValidatorUpdate,
+        VerifyVoteExtensionMsg, VoteExtension,
     },
     grug_wasm::{
-        make_immutable_ctx, make_mutable_ctx, make_sudo_ctx, return_into_generic_result,
-        unwrap_into_generic_result, ImmutableCtx, MutableCtx, SudoCtx,
+        make_auth_ctx, make_immutable_ctx, make_mutable_ctx, make_sudo_ctx,
+        return_into_generic_result, unwrap_into_generic_result, AuthCtx, ImmutableCtx, MutableCtx,
+        SudoCtx,
     },
     elsa::sync::FrozenVec,
     serde::de::DeserializeOwned,
@@ -16,6 +23,13 @@ use {
 
 pub(crate) static CONTRACTS: OnceLock<FrozenVec<Box<dyn Contract + Send + Sync>>> = OnceLock::new();
 
+// An entry point with its message type and error type erased, so that a
+// contract can be assembled out of any combination of entry points. `P` is the
+// input parameter(s) other than the context.
+type Mutating<P, R = Response> = Box<dyn Fn(Context, &mut dyn Storage, &dyn Api, &dyn Querier, P) -> GenericResult<R> + Send + Sync>;
+
+type Viewing<P, R> = Box<dyn Fn(Context, &dyn Storage, &dyn Api, &dyn Querier, P) -> GenericResult<R> + Send + Sync>;
+
 // ---------------------------------- wrapper ----------------------------------
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,68 +37,296 @@ pub struct ContractWrapper {
     pub(crate) index: usize,
 }
 
-impl ContractWrapper {
-    pub fn new<M1, M2, M3, M5, M6, E1, E2, E3, E4, E5, E6>(
-        instantiate_fn: InstantiateFn<M1, E1>,
-        execute_fn: Option<ExecuteFn<M2, E2>>,
-        migrate_fn: Option<MigrateFn<M3, E3>>,
-        receive_fn: Option<ReceiveFn<E4>>,
-        reply_fn: Option<ReplyFn<M5, E5>>,
-        query_fn: Option<QueryFn<M6, E6>>,
-    ) -> Self
-    where
-        M1: DeserializeOwned + 'static,
-        M2: DeserializeOwned + 'static,
-        M3: DeserializeOwned + 'static,
-        M5: DeserializeOwned + 'static,
-        M6: DeserializeOwned + 'static,
-        E1: ToString + 'static,
-        E2: ToString + 'static,
-        E3: ToString + 'static,
-        E4: ToString + 'static,
-        E5: ToString + 'static,
-        E6: ToString + 'static,
+// ---------------------------------- builder ----------------------------------
+
+/// Assembles a Rust contract out of its entry points. Only `instantiate` is
+/// required; calling an entry point that isn't provided panics.
+pub struct ContractBuilder {
+    contract: ContractImpl,
+}
+
+impl ContractBuilder {
+    pub fn new<M, E>(instantiate_fn: InstantiateFn<M, E>) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: ToString + 'static,
     {
+        Self {
+            contract: ContractImpl {
+                instantiate_fn: Box::new(move |ctx, storage, api, querier, msg| {
+                    let mutable_ctx = make_mutable_ctx!(ctx, storage, api, querier);
+                    let msg = unwrap_into_generic_result!(from_json_value(msg));
+                    return_into_generic_result!(instantiate_fn(mutable_ctx, msg))
+                }),
+                execute_fn: None,
+                migrate_fn: None,
+                receive_fn: None,
+                reply_fn: None,
+                query_fn: None,
+                prepare_proposal_fn: None,
+                process_proposal_fn: None,
+                extend_vote_fn: None,
+                verify_vote_extension_fn: None,
+                receive_vote_extensions_fn: None,
+                before_tx_fn: None,
+                after_tx_fn: None,
+                handle_fee_fn: None,
+                before_block_fn: None,
+                after_block_fn: None,
+                cron_execute_fn: None,
+                init_validators_fn: None,
+                validator_updates_fn: None,
+            },
+        }
+    }
+
+    pub fn with_execute<M, E>(mut self, execute_fn: ExecuteFn<M, E>) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: ToString + 'static,
+    {
+        self.contract.execute_fn = Some(Box::new(move |ctx, storage, api, querier, msg| {
+            let mutable_ctx = make_mutable_ctx!(ctx, storage, api, querier);
+            let msg = unwrap_into_generic_result!(from_json_value(msg));
+            return_into_generic_result!(execute_fn(mutable_ctx, msg))
+        }));
+        self
+    }
+
+    pub fn with_migrate<M, E>(mut self, migrate_fn: MigrateFn<M, E>) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: ToString + 'static,
+    {
+        self.contract.migrate_fn = Some(Box::new(move |ctx, storage, api, querier, msg| {
+            let mutable_ctx = make_mutable_ctx!(ctx, storage, api, querier);
+            let msg = unwrap_into_generic_result!(from_json_value(msg));
+            return_into_generic_result!(migrate_fn(mutable_ctx, msg))
+        }));
+        self
+    }
+
+    pub fn with_receive<E>(mut self, receive_fn: ReceiveFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.receive_fn = Some(Box::new(move |ctx, storage, api, querier, ()| {
+            let mutable_ctx = make_mutable_ctx!(ctx, storage, api, querier);
+            return_into_generic_result!(receive_fn(mutable_ctx))
+        }));
+        self
+    }
+
+    pub fn with_reply<M, E>(mut self, reply_fn: ReplyFn<M, E>) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: ToString + 'static,
+    {
+        self.contract.reply_fn =
+            Some(Box::new(move |ctx, storage, api, querier, (msg, submsg_res)| {
+                let sudo_ctx = make_sudo_ctx!(ctx, storage, api, querier);
+                let msg = unwrap_into_generic_result!(from_json_value(msg));
+                return_into_generic_result!(reply_fn(sudo_ctx, msg, submsg_res))
+            }));
+        self
+    }
+
+    pub fn with_query<M, E>(mut self, query_fn: QueryFn<M, E>) -> Self
+    where
+        M: DeserializeOwned + 'static,
+        E: ToString + 'static,
+    {
+        self.contract.query_fn = Some(Box::new(move |ctx, storage, api, querier, msg| {
+            let immutable_ctx = make_immutable_ctx!(ctx, storage, api, querier);
+            let msg = unwrap_into_generic_result!(from_json_value(msg));
+            return_into_generic_result!(query_fn(immutable_ctx, msg))
+        }));
+        self
+    }
+
+    pub fn with_prepare_proposal<E>(mut self, prepare_fn: PrepareProposalFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.prepare_proposal_fn =
+            Some(Box::new(move |ctx, storage, api, querier, msg| {
+                let immutable_ctx = make_immutable_ctx!(ctx, storage, api, querier);
+                return_into_generic_result!(prepare_fn(immutable_ctx, msg))
+            }));
+        self
+    }
+
+    pub fn with_process_proposal<E>(mut self, process_fn: ProcessProposalFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.process_proposal_fn =
+            Some(Box::new(move |ctx, storage, api, querier, msg| {
+                let immutable_ctx = make_immutable_ctx!(ctx, storage, api, querier);
+                return_into_generic_result!(process_fn(immutable_ctx, msg))
+            }));
+        self
+    }
+
+    pub fn with_extend_vote<E>(mut self, extend_vote_fn: ExtendVoteFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.extend_vote_fn = Some(Box::new(move |ctx, storage, api, querier, ()| {
+            let immutable_ctx = make_immutable_ctx!(ctx, storage, api, querier);
+            return_into_generic_result!(extend_vote_fn(immutable_ctx))
+        }));
+        self
+    }
+
+    pub fn with_verify_vote_extension<E>(mut self, verify_fn: VerifyVoteExtensionFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.verify_vote_extension_fn =
+            Some(Box::new(move |ctx, storage, api, querier, msg| {
+                let immutable_ctx = make_immutable_ctx!(ctx, storage, api, querier);
+                return_into_generic_result!(verify_fn(immutable_ctx, msg))
+            }));
+        self
+    }
+
+    pub fn with_receive_vote_extensions<E>(mut self, receive_fn: ReceiveVoteExtensionsFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.receive_vote_extensions_fn =
+            Some(Box::new(move |ctx, storage, api, querier, extensions| {
+                let sudo_ctx = make_sudo_ctx!(ctx, storage, api, querier);
+                return_into_generic_result!(receive_fn(sudo_ctx, extensions))
+            }));
+        self
+    }
+
+    pub fn with_before_tx<E>(mut self, before_tx_fn: BeforeTxFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.before_tx_fn = Some(Box::new(move |ctx, storage, api, querier, tx| {
+            let auth_ctx = make_auth_ctx!(ctx, storage, api, querier);
+            return_into_generic_result!(before_tx_fn(auth_ctx, tx))
+        }));
+        self
+    }
+
+    pub fn with_after_tx<E>(mut self, after_tx_fn: AfterTxFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.after_tx_fn = Some(Box::new(move |ctx, storage, api, querier, tx| {
+            let auth_ctx = make_auth_ctx!(ctx, storage, api, querier);
+            return_into_generic_result!(after_tx_fn(auth_ctx, tx))
+        }));
+        self
+    }
+
+    pub fn with_handle_fee<E>(mut self, handle_fee_fn: HandleFeeFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.handle_fee_fn = Some(Box::new(move |ctx, storage, api, querier, report| {
+            let sudo_ctx = make_sudo_ctx!(ctx, storage, api, querier);
+            return_into_generic_result!(handle_fee_fn(sudo_ctx, report))
+        }));
+        self
+    }
+
+    pub fn with_before_block<E>(mut self, before_block_fn: BeforeBlockFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.before_block_fn = Some(Box::new(move |ctx, storage, api, querier, ()| {
+            let sudo_ctx = make_sudo_ctx!(ctx, storage, api, querier);
+            return_into_generic_result!(before_block_fn(sudo_ctx))
+        }));
+        self
+    }
+
+    pub fn with_after_block<E>(mut self, after_block_fn: AfterBlockFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.after_block_fn = Some(Box::new(move |ctx, storage, api, querier, ()| {
+            let sudo_ctx = make_sudo_ctx!(ctx, storage, api, querier);
+            return_into_generic_result!(after_block_fn(sudo_ctx))
+        }));
+        self
+    }
+
+    pub fn with_cron_execute<E>(mut self, cron_execute_fn: CronExecuteFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.cron_execute_fn = Some(Box::new(move |ctx, storage, api, querier, ()| {
+            let sudo_ctx = make_sudo_ctx!(ctx, storage, api, querier);
+            return_into_generic_result!(cron_execute_fn(sudo_ctx))
+        }));
+        self
+    }
+
+    pub fn with_init_validators<E>(mut self, init_validators_fn: InitValidatorsFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.init_validators_fn =
+            Some(Box::new(move |ctx, storage, api, querier, validators| {
+                let sudo_ctx = make_sudo_ctx!(ctx, storage, api, querier);
+                return_into_generic_result!(init_validators_fn(sudo_ctx, validators))
+            }));
+        self
+    }
+
+    pub fn with_validator_updates<E>(mut self, validator_updates_fn: ValidatorUpdatesFn<E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.contract.validator_updates_fn = Some(Box::new(move |ctx, storage, api, querier, ()| {
+            let sudo_ctx = make_sudo_ctx!(ctx, storage, api, querier);
+            return_into_generic_result!(validator_updates_fn(sudo_ctx))
+        }));
+        self
+    }
+
+    /// Register the contract, and return a wrapper that can be uploaded as the
+    /// program of a code.
+    pub fn build(self) -> ContractWrapper {
         let contracts = CONTRACTS.get_or_init(Default::default);
         let index = contracts.len();
-        contracts.push(Box::new(ContractImpl {
-            instantiate_fn,
-            execute_fn,
-            migrate_fn,
-            receive_fn,
-            reply_fn,
-            query_fn,
-        }));
-        Self { index }
+        contracts.push(Box::new(self.contract));
+        ContractWrapper { index }
     }
 }
 
 // ----------------------------------- impl ------------------------------------
 
-pub struct ContractImpl<M1, M2, M3, M5, M6, E1, E2, E3, E4, E5, E6> {
-    instantiate_fn: InstantiateFn<M1, E1>,
-    execute_fn: Option<ExecuteFn<M2, E2>>,
-    migrate_fn: Option<MigrateFn<M3, E3>>,
-    receive_fn: Option<ReceiveFn<E4>>,
-    reply_fn: Option<ReplyFn<M5, E5>>,
-    query_fn: Option<QueryFn<M6, E6>>,
+pub struct ContractImpl {
+    instantiate_fn: Mutating<Json>,
+    execute_fn: Option<Mutating<Json>>,
+    migrate_fn: Option<Mutating<Json>>,
+    receive_fn: Option<Mutating<()>>,
+    reply_fn: Option<Mutating<(Json, SubMsgResult)>>,
+    query_fn: Option<Viewing<Json, Json>>,
+    prepare_proposal_fn: Option<Viewing<PrepareProposalMsg, Vec<Binary>>>,
+    process_proposal_fn: Option<Viewing<ProcessProposalMsg, ()>>,
+    extend_vote_fn: Option<Viewing<(), Binary>>,
+    verify_vote_extension_fn: Option<Viewing<VerifyVoteExtensionMsg, ()>>,
+    receive_vote_extensions_fn: Option<Mutating<Vec<VoteExtension>>>,
+    before_tx_fn: Option<Mutating<Tx>>,
+    after_tx_fn: Option<Mutating<Tx>>,
+    handle_fee_fn: Option<Mutating<GasReport>>,
+    before_block_fn: Option<Mutating<()>>,
+    after_block_fn: Option<Mutating<()>>,
+    cron_execute_fn: Option<Mutating<()>>,
+    init_validators_fn: Option<Mutating<Vec<ValidatorUpdate>>>,
+    validator_updates_fn: Option<Mutating<(), Vec<ValidatorUpdate>>>,
 }
 
-impl<M1, M2, M3, M5, M6, E1, E2, E3, E4, E5, E6> Contract
-    for ContractImpl<M1, M2, M3, M5, M6, E1, E2, E3, E4, E5, E6>
-where
-    M1: DeserializeOwned,
-    M2: DeserializeOwned,
-    M3: DeserializeOwned,
-    M5: DeserializeOwned,
-    M6: DeserializeOwned,
-    E1: ToString,
-    E2: ToString,
-    E3: ToString,
-    E4: ToString,
-    E5: ToString,
-    E6: ToString,
-{
+impl Contract for ContractImpl {
     fn instantiate(
         &self,
         ctx: Context,
@@ -93,9 +335,7 @@ where
         querier: &dyn Querier,
         msg: Json,
     ) -> GenericResult<Response> {
-        let mutable_ctx = make_mutable_ctx!(ctx, storage, api, querier);
-        let msg = unwrap_into_generic_result!(from_json_value(msg));
-        return_into_generic_result!((self.instantiate_fn)(mutable_ctx, msg))
+        (self.instantiate_fn)(ctx, storage, api, querier, msg)
     }
 
     fn execute(
@@ -106,9 +346,7 @@ where
         querier: &dyn Querier,
         msg: Json,
     ) -> GenericResult<Response> {
-        let mutable_ctx = make_mutable_ctx!(ctx, storage, api, querier);
-        let msg = unwrap_into_generic_result!(from_json_value(msg));
-        return_into_generic_result!(self.execute_fn.as_ref().unwrap()(mutable_ctx, msg))
+        self.execute_fn.as_ref().unwrap()(ctx, storage, api, querier, msg)
     }
 
     fn migrate(
@@ -119,9 +357,7 @@ where
         querier: &dyn Querier,
         msg: Json,
     ) -> GenericResult<Response> {
-        let mutable_ctx = make_mutable_ctx!(ctx, storage, api, querier);
-        let msg = unwrap_into_generic_result!(from_json_value(msg));
-        return_into_generic_result!(self.migrate_fn.as_ref().unwrap()(mutable_ctx, msg))
+        self.migrate_fn.as_ref().unwrap()(ctx, storage, api, querier, msg)
     }
 
     fn receive(
@@ -131,8 +367,7 @@ where
         api: &dyn Api,
         querier: &dyn Querier,
     ) -> GenericResult<Response> {
-        let mutable_ctx = make_mutable_ctx!(ctx, storage, api, querier);
-        return_into_generic_result!(self.receive_fn.as_ref().unwrap()(mutable_ctx))
+        self.receive_fn.as_ref().unwrap()(ctx, storage, api, querier, ())
     }
 
     fn reply(
@@ -144,9 +379,7 @@ where
         msg: Json,
         submsg_res: SubMsgResult,
     ) -> GenericResult<Response> {
-        let sudo_ctx = make_sudo_ctx!(ctx, storage, api, querier);
-        let msg = unwrap_into_generic_result!(from_json_value(msg));
-        return_into_generic_result!(self.reply_fn.as_ref().unwrap()(sudo_ctx, msg, submsg_res))
+        self.reply_fn.as_ref().unwrap()(ctx, storage, api, querier, (msg, submsg_res))
     }
 
     fn query(
@@ -157,8 +390,144 @@ where
         querier: &dyn Querier,
         msg: Json,
     ) -> GenericResult<Json> {
-        let immutable_ctx = make_immutable_ctx!(ctx, storage, api, querier);
-        let msg = unwrap_into_generic_result!(from_json_value(msg));
-        return_into_generic_result!(self.query_fn.as_ref().unwrap()(immutable_ctx, msg))
+        self.query_fn.as_ref().unwrap()(ctx, storage, api, querier, msg)
+    }
+
+    fn prepare_proposal(
+        &self,
+        ctx: Context,
+        storage: &dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        msg: PrepareProposalMsg,
+    ) -> GenericResult<Vec<Binary>> {
+        self.prepare_proposal_fn.as_ref().unwrap()(ctx, storage, api, querier, msg)
+    }
+
+    fn process_proposal(
+        &self,
+        ctx: Context,
+        storage: &dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        msg: ProcessProposalMsg,
+    ) -> GenericResult<()> {
+        self.process_proposal_fn.as_ref().unwrap()(ctx, storage, api, querier, msg)
+    }
+
+    fn extend_vote(
+        &self,
+        ctx: Context,
+        storage: &dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Binary> {
+        self.extend_vote_fn.as_ref().unwrap()(ctx, storage, api, querier, ())
+    }
+
+    fn verify_vote_extension(
+        &self,
+        ctx: Context,
+        storage: &dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        msg: VerifyVoteExtensionMsg,
+    ) -> GenericResult<()> {
+        self.verify_vote_extension_fn.as_ref().unwrap()(ctx, storage, api, querier, msg)
+    }
+
+    fn receive_vote_extensions(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        extensions: Vec<VoteExtension>,
+    ) -> GenericResult<Response> {
+        self.receive_vote_extensions_fn.as_ref().unwrap()(ctx, storage, api, querier, extensions)
+    }
+
+    fn before_tx(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        tx: Tx,
+    ) -> GenericResult<Response> {
+        self.before_tx_fn.as_ref().unwrap()(ctx, storage, api, querier, tx)
+    }
+
+    fn after_tx(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        tx: Tx,
+    ) -> GenericResult<Response> {
+        self.after_tx_fn.as_ref().unwrap()(ctx, storage, api, querier, tx)
+    }
+
+    fn handle_fee(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        report: GasReport,
+    ) -> GenericResult<Response> {
+        self.handle_fee_fn.as_ref().unwrap()(ctx, storage, api, querier, report)
+    }
+
+    fn before_block(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Response> {
+        self.before_block_fn.as_ref().unwrap()(ctx, storage, api, querier, ())
+    }
+
+    fn after_block(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Response> {
+        self.after_block_fn.as_ref().unwrap()(ctx, storage, api, querier, ())
+    }
+
+    fn cron_execute(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Response> {
+        self.cron_execute_fn.as_ref().unwrap()(ctx, storage, api, querier, ())
+    }
+
+    fn init_validators(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        validators: Vec<ValidatorUpdate>,
+    ) -> GenericResult<Response> {
+        self.init_validators_fn.as_ref().unwrap()(ctx, storage, api, querier, validators)
+    }
+
+    fn validator_updates(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Vec<ValidatorUpdate>> {
+        self.validator_updates_fn.as_ref().unwrap()(ctx, storage, api, querier, ())
     }
 }
@@ -12,10 +12,62 @@ pub enum VmError {
     },
 }
 
+impl VmError {
+    /// The ABCI codespace under which Rust VM errors are reported.
+    pub const CODESPACE: &'static str = "rust_vm";
+
+    /// Return the ABCI error code of this error. Each variant has a unique code
+    /// that must not change, as clients rely on them to identify errors.
+    pub fn code(&self) -> u32 {
+        match self {
+            VmError::Std(_) => 1,
+            VmError::IncorrectNumberOfInputs { .. } => 2,
+        }
+    }
+}
+
 impl From<VmError> for AppError {
     fn from(err: VmError) -> Self {
-        AppError::Vm(err.to_string())
+        match err {
+            // standard errors are converted to the dedicated AppError variant,
+            // so that they are reported with their own error codes. among
+            // others, this allows out of gas errors to be distinguished.
+            VmError::Std(err) => err.into(),
+            err => AppError::vm(VmError::CODESPACE, err.code(), err),
+        }
     }
 }
 
 pub type VmResult<T> = std::result::Result<T, VmError>;
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // clients rely on the codes to identify errors, so they must not change
+    #[test]
+    fn codes_are_stable() {
+        let incorrect_inputs = || VmError::IncorrectNumberOfInputs {
+            name: "execute".into(),
+            num:  3,
+        };
+
+        for (err, code) in
+            [(VmError::Std(StdError::VerificationFailed), 1), (incorrect_inputs(), 2)]
+        {
+            assert_eq!(err.code(), code, "code of {err:?}");
+        }
+
+        // VM errors are reported under the VM's codespace, while standard
+        // errors keep their own
+        let err = AppError::from(incorrect_inputs());
+        assert_eq!(err.codespace(), "rust_vm");
+        assert_eq!(err.code(), 2);
+
+        let err = AppError::from(VmError::Std(StdError::VerificationFailed));
+        assert_eq!(err.codespace(), "std");
+        assert_eq!(err.code(), 4);
+    }
+}
@@ -0,0 +1,184 @@
+use {
+    grug_app::GasTracker,
+    grug_types::{
+        to_json_vec, Addr, Api, GasCosts, Order, Querier, QueryRequest, QueryResponse, Record,
+        StdResult, Storage,
+    },
+};
+
+/// Wraps a storage, an API, or a querier, charging gas for each call according
+/// to the cost table, the same way the Wasm VM charges for host function calls.
+///
+/// The `Storage` trait methods are infallible, so if the tracker runs out of
+/// gas during a storage access, the error can't be returned to the contract.
+/// Instead, the VM checks whether this has happened after the contract call
+/// returns.
+#[derive(Clone)]
+pub struct Metered<T> {
+    inner:       T,
+    gas_tracker: GasTracker,
+    gas_costs:   GasCosts,
+}
+
+impl<T> Metered<T> {
+    pub fn new(inner: T, gas_tracker: GasTracker, gas_costs: GasCosts) -> Self {
+        Self {
+            inner,
+            gas_tracker,
+            gas_costs,
+        }
+    }
+}
+
+impl<T: Storage + Clone> Storage for Metered<T> {
+    fn read(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let maybe_value = self.inner.read(key);
+        let bytes = key.len() + maybe_value.as_ref().map_or(0, |value| value.len());
+        self.gas_tracker.consume(self.gas_costs.db_read.cost(bytes), "db_read").ok();
+        maybe_value
+    }
+
+    fn scan<'a>(
+        &'a self,
+        min: Option<&[u8]>,
+        max: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'a> {
+        let bytes = min.map_or(0, |min| min.len()) + max.map_or(0, |max| max.len());
+        self.gas_tracker.consume(self.gas_costs.db_scan.cost(bytes), "db_scan").ok();
+        Box::new(self.inner.scan(min, max, order).map(|(k, v)| {
+            let cost = self.gas_costs.db_next.cost(k.len() + v.len());
+            self.gas_tracker.consume(cost, "db_next").ok();
+            (k, v)
+        }))
+    }
+
+    fn write(&mut self, key: &[u8], value: &[u8]) {
+        let cost = self.gas_costs.db_write.cost(key.len() + value.len());
+        self.gas_tracker.consume(cost, "db_write").ok();
+        self.inner.write(key, value);
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.gas_tracker.consume(self.gas_costs.db_remove.cost(key.len()), "db_remove").ok();
+        self.inner.remove(key);
+    }
+
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        let bytes = min.map_or(0, |min| min.len()) + max.map_or(0, |max| max.len());
+        let cost = self.gas_costs.db_remove_range.cost(bytes);
+        self.gas_tracker.consume(cost, "db_remove_range").ok();
+
+        // charge for each record as if it were removed individually. stop as
+        // soon as the gas runs out, so that the host doesn't scan an unbounded
+        // range for a call that fails anyway.
+        for (key, _) in self.inner.scan(min, max, Order::Ascending) {
+            let cost = self.gas_costs.db_remove.cost(key.len());
+            if self.gas_tracker.consume(cost, "db_remove_range").is_err() {
+                return;
+            }
+        }
+
+        self.inner.remove_range(min, max);
+    }
+}
+
+impl<T: Api> Api for Metered<T> {
+    fn debug(&self, addr: &Addr, msg: &str) {
+        self.inner.debug(addr, msg)
+    }
+
+    fn secp256k1_verify(&self, msg_hash: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()> {
+        let cost = self.gas_costs.secp256k1_verify.cost(msg_hash.len() + sig.len() + pk.len());
+        self.gas_tracker.consume(cost, "secp256k1_verify")?;
+        self.inner.secp256k1_verify(msg_hash, sig, pk)
+    }
+
+    fn secp256r1_verify(&self, msg_hash: &[u8], sig: &[u8], pk: &[u8]) -> StdResult<()> {
+        let cost = self.gas_costs.secp256r1_verify.cost(msg_hash.len() + sig.len() + pk.len());
+        self.gas_tracker.consume(cost, "secp256r1_verify")?;
+        self.inner.secp256r1_verify(msg_hash, sig, pk)
+    }
+}
+
+impl<T: Querier> Querier for Metered<T> {
+    fn query_chain(&self, req: QueryRequest) -> StdResult<QueryResponse> {
+        // the Wasm VM charges by the size of the JSON-encoded request and
+        // response, so we do the same here.
+        let req_len = to_json_vec(&req)?.len();
+        let res = self.inner.query_chain(req)?;
+        let res_len = to_json_vec(&res)?.len();
+        let cost = self.gas_costs.query_chain.cost(req_len + res_len);
+        self.gas_tracker.consume(cost, "query_chain")?;
+        Ok(res)
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        grug_types::{GasCost, MockApi, MockStorage},
+    };
+
+    // each host function has a different cost, so that charging the wrong
+    // entry of the table is caught
+    const GAS_COSTS: GasCosts = GasCosts {
+        db_read:          GasCost::new(10, 1),
+        db_scan:          GasCost::new(20, 2),
+        db_next:          GasCost::new(30, 3),
+        db_write:         GasCost::new(40, 4),
+        db_remove:        GasCost::new(50, 5),
+        db_remove_range:  GasCost::new(60, 6),
+        query_chain:      GasCost::new(70, 7),
+        secp256k1_verify: GasCost::new(80, 8),
+        secp256r1_verify: GasCost::new(90, 9),
+    };
+
+    #[test]
+    fn host_functions_charge_their_gas_costs() {
+        let gas_tracker = GasTracker::new_limitless();
+        let mut storage = Metered::new(MockStorage::new(), gas_tracker.clone(), GAS_COSTS);
+        let api = Metered::new(MockApi, gas_tracker.clone(), GAS_COSTS);
+
+        // return the gas consumed since the last time this was called
+        let mut last_used = 0;
+        let mut charged = || {
+            let used = gas_tracker.used();
+            let charged = used - last_used;
+            last_used = used;
+            charged
+        };
+
+        storage.write(b"key", b"value");
+        assert_eq!(charged(), GAS_COSTS.db_write.cost(8));
+
+        storage.read(b"key");
+        assert_eq!(charged(), GAS_COSTS.db_read.cost(8));
+
+        storage.read(b"nope");
+        assert_eq!(charged(), GAS_COSTS.db_read.cost(4));
+
+        storage.scan(Some(b"k"), None, Order::Ascending).for_each(drop);
+        assert_eq!(charged(), GAS_COSTS.db_scan.cost(1) + GAS_COSTS.db_next.cost(8));
+
+        storage.remove(b"key");
+        assert_eq!(charged(), GAS_COSTS.db_remove.cost(3));
+
+        // each record in the range is charged as if it were removed on its own
+        storage.write(b"a", b"1");
+        storage.write(b"b", b"2");
+        charged();
+        storage.remove_range(Some(b"a"), Some(b"c"));
+        assert_eq!(charged(), GAS_COSTS.db_remove_range.cost(2) + 2 * GAS_COSTS.db_remove.cost(1));
+
+        // verification fails, but the gas is charged regardless
+        api.secp256k1_verify(&[0; 32], &[0; 64], &[0; 33]).unwrap_err();
+        assert_eq!(charged(), GAS_COSTS.secp256k1_verify.cost(129));
+
+        api.secp256r1_verify(&[0; 32], &[0; 64], &[0; 33]).unwrap_err();
+        assert_eq!(charged(), GAS_COSTS.secp256r1_verify.cost(129));
+    }
+}
@@ -1,6 +1,7 @@
 mod contract;
 mod error;
+mod gas;
 mod traits;
 mod vm;
 
-pub use {contract::*, error::*, traits::*, vm::*};
+pub use {contract::*, error::*, gas::*, traits::*, vm::*};
@@ -5,8 +5,9 @@
 
 use {
     grug_types::{
-        Api, BankQueryMsg, BankQueryResponse, Context, Empty, GenericResult, Json, Querier,
-        Response, StdError, Storage, SubMsgResult, TransferMsg, Tx,
+        Api, BankQueryMsg, BankQueryResponse, Binary, Context, Empty, GasReport, GenericResult,
+        Json, PrepareProposalMsg, ProcessProposalMsg, Querier, Response, StdError, Storage,
+        SubMsgResult, TransferMsg, Tx, ValidatorUpdate, VerifyVoteExtensionMsg, VoteExtension,
     },
     grug_wasm::{AuthCtx, ImmutableCtx, MutableCtx, SudoCtx},
 };
@@ -65,6 +66,118 @@ pub trait Contract {
         querier: &dyn Querier,
         msg: Json,
     ) -> GenericResult<Json>;
+
+    fn prepare_proposal(
+        &self,
+        ctx: Context,
+        storage: &dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        msg: PrepareProposalMsg,
+    ) -> GenericResult<Vec<Binary>>;
+
+    fn process_proposal(
+        &self,
+        ctx: Context,
+        storage: &dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        msg: ProcessProposalMsg,
+    ) -> GenericResult<()>;
+
+    fn extend_vote(
+        &self,
+        ctx: Context,
+        storage: &dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Binary>;
+
+    fn verify_vote_extension(
+        &self,
+        ctx: Context,
+        storage: &dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        msg: VerifyVoteExtensionMsg,
+    ) -> GenericResult<()>;
+
+    fn receive_vote_extensions(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        extensions: Vec<VoteExtension>,
+    ) -> GenericResult<Response>;
+
+    fn before_tx(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        tx: Tx,
+    ) -> GenericResult<Response>;
+
+    fn after_tx(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        tx: Tx,
+    ) -> GenericResult<Response>;
+
+    fn handle_fee(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        report: GasReport,
+    ) -> GenericResult<Response>;
+
+    fn before_block(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Response>;
+
+    fn after_block(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Response>;
+
+    fn cron_execute(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Response>;
+
+    fn init_validators(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+        validators: Vec<ValidatorUpdate>,
+    ) -> GenericResult<Response>;
+
+    fn validator_updates(
+        &self,
+        ctx: Context,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        querier: &dyn Querier,
+    ) -> GenericResult<Vec<ValidatorUpdate>>;
 }
 
 pub type InstantiateFn<M = Empty, E = StdError> = Box<dyn Fn(MutableCtx, M) -> Result<Response, E> + Send + Sync>;
@@ -87,6 +200,24 @@ pub type BeforeBlockFn<E = StdError> = Box<dyn Fn(SudoCtx) -> Result<Response, E
 
 pub type AfterBlockFn<E = StdError> = Box<dyn Fn(SudoCtx) -> Result<Response, E> + Send + Sync>;
 
+pub type CronExecuteFn<E = StdError> = Box<dyn Fn(SudoCtx) -> Result<Response, E> + Send + Sync>;
+
+pub type InitValidatorsFn<E = StdError> = Box<dyn Fn(SudoCtx, Vec<ValidatorUpdate>) -> Result<Response, E> + Send + Sync>;
+
+pub type ValidatorUpdatesFn<E = StdError> = Box<dyn Fn(SudoCtx) -> Result<Vec<ValidatorUpdate>, E> + Send + Sync>;
+
+pub type HandleFeeFn<E = StdError> = Box<dyn Fn(SudoCtx, GasReport) -> Result<Response, E> + Send + Sync>;
+
 pub type BankTransferFn<E = StdError> = Box<dyn Fn(SudoCtx, TransferMsg) -> Result<Response, E> + Send + Sync>;
 
 pub type BankQueryFn<E = StdError> = Box<dyn Fn(ImmutableCtx, BankQueryMsg) -> Result<BankQueryResponse, E> + Send + Sync>;
+
+pub type PrepareProposalFn<E = StdError> = Box<dyn Fn(ImmutableCtx, PrepareProposalMsg) -> Result<Vec<Binary>, E> + Send + Sync>;
+
+pub type ProcessProposalFn<E = StdError> = Box<dyn Fn(ImmutableCtx, ProcessProposalMsg) -> Result<(), E> + Send + Sync>;
+
+pub type ExtendVoteFn<E = StdError> = Box<dyn Fn(ImmutableCtx) -> Result<Binary, E> + Send + Sync>;
+
+pub type VerifyVoteExtensionFn<E = StdError> = Box<dyn Fn(ImmutableCtx, VerifyVoteExtensionMsg) -> Result<(), E> + Send + Sync>;
+
+pub type ReceiveVoteExtensionsFn<E = StdError> = Box<dyn Fn(SudoCtx, Vec<VoteExtension>) -> Result<Response, E> + Send + Sync>;
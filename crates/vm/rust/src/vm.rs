@@ -1,7 +1,7 @@
 use {
-    crate::{ContractWrapper, VmError, VmResult, CONTRACTS},
-    grug_app::{PrefixStore, QueryProvider, Vm},
-    grug_types::{from_json_slice, to_json_vec, Context, MockApi},
+    crate::{ContractWrapper, Metered, VmError, VmResult, CONTRACTS},
+    grug_app::{GasTracker, PrefixStore, QueryProvider, Vm},
+    grug_types::{from_json_slice, to_json_vec, Context, GasCosts, MockApi},
 };
 
 macro_rules! get_contract {
@@ -13,27 +13,51 @@ macro_rules! get_contract {
 }
 
 pub struct RustVm {
-    storage: PrefixStore,
-    querier: QueryProvider<Self>,
-    program: ContractWrapper,
+    storage:     Metered<PrefixStore>,
+    api:         Metered<MockApi>,
+    querier:     Metered<QueryProvider<Self>>,
+    gas_tracker: GasTracker,
+    program:     ContractWrapper,
+}
+
+impl RustVm {
+    // check whether the tracker has run out of gas during the contract call
+    // (see the docs of `Metered` for why this is necessary). once the tracker
+    // has run out of gas, consuming any amount errors, including zero.
+    fn check_gas(&self) -> VmResult<()> {
+        self.gas_tracker.consume(0, "rust").map_err(Into::into)
+    }
 }
 
 impl Vm for RustVm {
     type Error = VmError;
     type Program = ContractWrapper;
 
+    // Rust contracts are run natively, so there's no way to meter the number
+    // of instructions being executed. only calls to the storage, API, and
+    // querier are charged, according to the gas cost table.
     fn build_instance(
-        storage: PrefixStore,
-        querier: QueryProvider<Self>,
-        program: Self::Program,
+        storage:     PrefixStore,
+        querier:     QueryProvider<Self>,
+        gas_tracker: GasTracker,
+        gas_costs:   GasCosts,
+        program:     Self::Program,
     ) -> VmResult<Self> {
         Ok(Self {
-            storage,
-            querier,
+            storage: Metered::new(storage, gas_tracker.clone(), gas_costs.clone()),
+            api: Metered::new(MockApi, gas_tracker.clone(), gas_costs.clone()),
+            querier: Metered::new(querier, gas_tracker.clone(), gas_costs),
+            gas_tracker,
             program,
         })
     }
 
+    // Rust contracts are compiled together with the host, so there's nothing
+    // to validate.
+    fn validate_program(_program: &Self::Program) -> VmResult<()> {
+        Ok(())
+    }
+
     fn call_in_0_out_1(mut self, name: &str, ctx: &Context) -> VmResult<Vec<u8>> {
         let contract = get_contract!(self.program.index);
         let out = match name {
@@ -41,7 +65,52 @@ impl Vm for RustVm {
                 let res = contract.receive(
                     ctx.clone(),
                     &mut self.storage,
-                    &MockApi,
+                    &self.api,
+                    &self.querier,
+                );
+                to_json_vec(&res)?
+            },
+            "extend_vote" => {
+                let res = contract.extend_vote(
+                    ctx.clone(),
+                    &self.storage,
+                    &self.api,
+                    &self.querier,
+                );
+                to_json_vec(&res)?
+            },
+            "before_block" => {
+                let res = contract.before_block(
+                    ctx.clone(),
+                    &mut self.storage,
+                    &self.api,
+                    &self.querier,
+                );
+                to_json_vec(&res)?
+            },
+            "after_block" => {
+                let res = contract.after_block(
+                    ctx.clone(),
+                    &mut self.storage,
+                    &self.api,
+                    &self.querier,
+                );
+                to_json_vec(&res)?
+            },
+            "cron_execute" => {
+                let res = contract.cron_execute(
+                    ctx.clone(),
+                    &mut self.storage,
+                    &self.api,
+                    &self.querier,
+                );
+                to_json_vec(&res)?
+            },
+            "validator_updates" => {
+                let res = contract.validator_updates(
+                    ctx.clone(),
+                    &mut self.storage,
+                    &self.api,
                     &self.querier,
                 );
                 to_json_vec(&res)?
@@ -53,6 +122,7 @@ impl Vm for RustVm {
                 })
             },
         };
+        self.check_gas()?;
         Ok(out)
     }
 
@@ -69,7 +139,7 @@ impl Vm for RustVm {
                 let res = contract.instantiate(
                     ctx.clone(),
                     &mut self.storage,
-                    &MockApi,
+                    &self.api,
                     &self.querier,
                     msg,
                 );
@@ -80,7 +150,7 @@ impl Vm for RustVm {
                 let res = contract.execute(
                     ctx.clone(),
                     &mut self.storage,
-                    &MockApi,
+                    &self.api,
                     &self.querier,
                     msg,
                 );
@@ -91,7 +161,7 @@ impl Vm for RustVm {
                 let res = contract.migrate(
                     ctx.clone(),
                     &mut self.storage,
-                    &MockApi,
+                    &self.api,
                     &self.querier,
                     msg,
                 );
@@ -102,12 +172,100 @@ impl Vm for RustVm {
                 let res = contract.query(
                     ctx.clone(),
                     &self.storage,
-                    &MockApi,
+                    &self.api,
                     &self.querier,
                     msg,
                 );
                 to_json_vec(&res)?
             },
+            "prepare_proposal" => {
+                let msg = from_json_slice(param1)?;
+                let res = contract.prepare_proposal(
+                    ctx.clone(),
+                    &self.storage,
+                    &self.api,
+                    &self.querier,
+                    msg,
+                );
+                to_json_vec(&res)?
+            },
+            "process_proposal" => {
+                let msg = from_json_slice(param1)?;
+                let res = contract.process_proposal(
+                    ctx.clone(),
+                    &self.storage,
+                    &self.api,
+                    &self.querier,
+                    msg,
+                );
+                to_json_vec(&res)?
+            },
+            "verify_vote_extension" => {
+                let msg = from_json_slice(param1)?;
+                let res = contract.verify_vote_extension(
+                    ctx.clone(),
+                    &self.storage,
+                    &self.api,
+                    &self.querier,
+                    msg,
+                );
+                to_json_vec(&res)?
+            },
+            "receive_vote_extensions" => {
+                let extensions = from_json_slice(param1)?;
+                let res = contract.receive_vote_extensions(
+                    ctx.clone(),
+                    &mut self.storage,
+                    &self.api,
+                    &self.querier,
+                    extensions,
+                );
+                to_json_vec(&res)?
+            },
+            "before_tx" => {
+                let tx = from_json_slice(param1)?;
+                let res = contract.before_tx(
+                    ctx.clone(),
+                    &mut self.storage,
+                    &self.api,
+                    &self.querier,
+                    tx,
+                );
+                to_json_vec(&res)?
+            },
+            "after_tx" => {
+                let tx = from_json_slice(param1)?;
+                let res = contract.after_tx(
+                    ctx.clone(),
+                    &mut self.storage,
+                    &self.api,
+                    &self.querier,
+                    tx,
+                );
+                to_json_vec(&res)?
+            },
+            "handle_fee" => {
+                let report = from_json_slice(param1)?;
+                let res = contract.handle_fee(
+                    ctx.clone(),
+                    &mut self.storage,
+                    &self.api,
+                    &self.querier,
+                    report,
+                );
+                to_json_vec(&res)?
+            },
+            "init_validators" => {
+                let validators = from_json_slice(param1)?;
+                let res = contract.init_validators(
+                    ctx.clone(),
+                    &mut self.storage,
+                    &self.api,
+                    &self.querier,
+                    validators,
+                );
+                to_json_vec(&res)?
+            },
             _ => {
                 return Err(VmError::IncorrectNumberOfInputs {
                     name: name.into(),
@@ -115,6 +273,7 @@ impl Vm for RustVm {
                 })
             },
         };
+        self.check_gas()?;
         Ok(out)
     }
 
@@ -133,7 +292,7 @@ impl Vm for RustVm {
                 let res = contract.reply(
                     ctx.clone(),
                     &mut self.storage,
-                    &MockApi,
+                    &self.api,
                     &self.querier,
                     msg,
                     submsg_res,
@@ -147,6 +306,7 @@ impl Vm for RustVm {
                 })
             },
         };
+        self.check_gas()?;
         Ok(out)
     }
 }
@@ -0,0 +1,353 @@
+use {
+    crate::{compile, compiler_settings_hash, VmError, VmResult},
+    grug_types::{hash, Hash},
+    std::{
+        collections::{HashMap, VecDeque},
+        fs,
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, MutexGuard, OnceLock,
+        },
+    },
+    tracing::{debug, info, warn},
+    wasmer::{Engine, Module, NativeEngineExt},
+};
+
+/// Number of compiled modules kept in memory, if the cache isn't explicitly
+/// initialized with `init_module_cache`.
+pub const DEFAULT_MODULE_CACHE_CAPACITY: usize = 100;
+
+static MODULE_CACHE: OnceLock<ModuleCache> = OnceLock::new();
+
+/// Initialize the global module cache. Must be called before the first Wasm
+/// instance is built; otherwise, a cache without on-disk storage and with the
+/// default capacity has already been created, and an error is returned.
+pub fn init_module_cache(capacity: usize, dir: Option<PathBuf>) -> VmResult<&'static ModuleCache> {
+    let cache = ModuleCache::new(capacity, dir)?;
+    MODULE_CACHE.set(cache).map_err(|_| VmError::ModuleCacheAlreadyInitialized)?;
+    Ok(module_cache())
+}
+
+/// Return the global module cache, creating one with the default settings if
+/// it hasn't been initialized.
+pub fn module_cache() -> &'static ModuleCache {
+    MODULE_CACHE.get_or_init(|| {
+        // without a directory, creating the cache can't fail
+        ModuleCache::new(DEFAULT_MODULE_CACHE_CAPACITY, None).unwrap()
+    })
+}
+
+/// Hit/miss counters of the module cache, since the process started.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The module was found in memory.
+    pub memory_hits: u64,
+    /// The module wasn't in memory, but its artifact was found on disk.
+    pub disk_hits: u64,
+    /// The module had to be compiled from the Wasm byte code.
+    pub misses: u64,
+}
+
+/// A compiled module, and the engine it was created with. Wasmer requires a
+/// module to be instantiated in a store using the same engine.
+#[derive(Clone)]
+pub(crate) struct CachedModule {
+    pub engine: Engine,
+    pub module: Module,
+}
+
+struct Lru<T> {
+    modules: HashMap<Hash, T>,
+    // least recently used first
+    order: VecDeque<Hash>,
+}
+
+impl<T> Default for Lru<T> {
+    fn default() -> Self {
+        Self {
+            modules: HashMap::new(),
+            order:   VecDeque::new(),
+        }
+    }
+}
+
+impl<T: Clone> Lru<T> {
+    fn get(&mut self, key: &Hash) -> Option<T> {
+        let cached = self.modules.get(key)?.clone();
+        self.touch(key);
+        Some(cached)
+    }
+
+    fn insert(&mut self, key: Hash, cached: T, capacity: usize) {
+        if self.modules.insert(key.clone(), cached).is_some() {
+            self.touch(&key);
+            return;
+        }
+
+        self.order.push_back(key);
+
+        while self.order.len() > capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.modules.remove(&evicted);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &Hash) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            let key = self.order.remove(pos).unwrap();
+            self.order.push_back(key);
+        }
+    }
+}
+
+/// An LRU cache of compiled Wasm modules, keyed by the hash of the Wasm byte
+/// code, optionally backed by compiled artifacts stored on disk.
+///
+/// Compiling a module is by far the most expensive step in creating a Wasm
+/// instance, so without a cache, block processing would be dominated by it.
+pub struct ModuleCache {
+    capacity:    usize,
+    dir:         Option<PathBuf>,
+    modules:     Mutex<Lru<CachedModule>>,
+    memory_hits: AtomicU64,
+    disk_hits:   AtomicU64,
+    misses:      AtomicU64,
+}
+
+impl ModuleCache {
+    /// Create a new cache that keeps up to `capacity` modules in memory. If a
+    /// directory is provided, compiled artifacts are also saved there, so that
+    /// they don't need to be compiled again after the node restarts.
+    ///
+    /// Artifacts are stored under a subdirectory named after the hash of the
+    /// compiler settings, as the metering and NaN canonicalization are baked
+    /// into them. Artifacts compiled with other settings are never loaded.
+    pub fn new(capacity: usize, dir: Option<PathBuf>) -> VmResult<Self> {
+        let dir = dir.map(|dir| dir.join(compiler_settings_hash().to_string()));
+
+        if let Some(dir) = &dir {
+            fs::create_dir_all(dir).map_err(|err| VmError::ModuleCache(err.to_string()))?;
+        }
+
+        Ok(Self {
+            capacity:    capacity.max(1),
+            dir,
+            modules:     Default::default(),
+            memory_hits: AtomicU64::new(0),
+            disk_hits:   AtomicU64::new(0),
+            misses:      AtomicU64::new(0),
+        })
+    }
+
+    /// Return the hit/miss counters. They're also logged each time a module
+    /// needs to be compiled.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            memory_hits: self.memory_hits.load(Ordering::Relaxed),
+            disk_hits:   self.disk_hits.load(Ordering::Relaxed),
+            misses:      self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Load the given programs into memory, from disk if their artifacts are
+    /// available or otherwise by compiling them, so that they're ready by the
+    /// time the first block is processed. Stops once the cache is full.
+    ///
+    /// Doesn't affect the hit/miss statistics. Returns the number of modules
+    /// that were loaded.
+    pub fn warm_up<I, P>(&self, programs: I) -> VmResult<usize>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let mut count = 0;
+
+        for program in programs.into_iter().take(self.capacity) {
+            let key = hash(program.as_ref());
+            if self.lock().modules.contains_key(&key) {
+                continue;
+            }
+
+            let cached = match self.load_from_disk(&key) {
+                Some(cached) => cached,
+                None => self.compile_and_save(&key, program.as_ref())?,
+            };
+
+            self.lock().insert(key, cached, self.capacity);
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    pub(crate) fn get_or_compile(&self, program: &[u8]) -> VmResult<CachedModule> {
+        let key = hash(program);
+
+        if let Some(cached) = self.lock().get(&key) {
+            self.memory_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(cached);
+        }
+
+        // note: the lock isn't held while loading or compiling the module. if
+        // the same module is requested concurrently, it may be compiled twice,
+        // which is harmless.
+        let cached = match self.load_from_disk(&key) {
+            Some(cached) => {
+                self.disk_hits.fetch_add(1, Ordering::Relaxed);
+                cached
+            },
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                let cached = self.compile_and_save(&key, program)?;
+                let stats = self.stats();
+                info!(
+                    key = key.to_string(),
+                    memory_hits = stats.memory_hits,
+                    disk_hits = stats.disk_hits,
+                    misses = stats.misses,
+                    "Compiled Wasm module"
+                );
+                cached
+            },
+        };
+
+        self.lock().insert(key, cached.clone(), self.capacity);
+
+        Ok(cached)
+    }
+
+    fn lock(&self) -> MutexGuard<Lru<CachedModule>> {
+        self.modules.lock().unwrap_or_else(|err| {
+            panic!("poisoned lock: {err:?}")
+        })
+    }
+
+    fn artifact_path(&self, key: &Hash) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(key.to_string()))
+    }
+
+    // failing to read or write an artifact isn't fatal: we can always fall
+    // back to compiling the module. we log a warning and move on.
+    fn load_from_disk(&self, key: &Hash) -> Option<CachedModule> {
+        let path = self.artifact_path(key)?;
+        let bytes = fs::read(&path).ok()?;
+
+        // the artifact doesn't need to be compiled, so the engine doesn't need
+        // a compiler. the metering middleware's effect is already baked in.
+        let engine = Engine::headless();
+
+        // safety: the artifact was created by `Module::serialize` in this node,
+        // and is stored in the node's home directory. wasmer also checks that
+        // the artifact was created by a compatible version before loading it.
+        match unsafe { Module::deserialize(&engine, bytes) } {
+            Ok(module) => {
+                debug!(key = key.to_string(), "Loaded Wasm module from disk");
+                Some(CachedModule { engine, module })
+            },
+            Err(err) => {
+                warn!(
+                    path = path.display().to_string(),
+                    err = err.to_string(),
+                    "Failed to load Wasm artifact"
+                );
+                None
+            },
+        }
+    }
+
+    fn compile_and_save(&self, key: &Hash, program: &[u8]) -> VmResult<CachedModule> {
+        let cached = compile(program)?;
+
+        if let Some(path) = self.artifact_path(key) {
+            let res = cached
+                .module
+                .serialize()
+                .map_err(|err| err.to_string())
+                .and_then(|bytes| fs::write(&path, bytes).map_err(|err| err.to_string()));
+            if let Err(err) = res {
+                warn!(path = path.display().to_string(), err, "Failed to save Wasm artifact");
+            }
+        }
+
+        Ok(cached)
+    }
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, wasmer::wat2wasm};
+
+    fn key(i: u8) -> Hash {
+        hash([i])
+    }
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let mut lru = Lru::default();
+        lru.insert(key(1), 1, 2);
+        lru.insert(key(2), 2, 2);
+
+        // using 1 makes 2 the least recently used, so it's evicted
+        assert_eq!(lru.get(&key(1)), Some(1));
+        lru.insert(key(3), 3, 2);
+        assert_eq!(lru.get(&key(2)), None);
+        assert_eq!(lru.get(&key(1)), Some(1));
+        assert_eq!(lru.get(&key(3)), Some(3));
+
+        // inserting an existing key replaces its value without evicting
+        lru.insert(key(1), 10, 2);
+        assert_eq!(lru.modules.len(), 2);
+        assert_eq!(lru.get(&key(3)), Some(3));
+        assert_eq!(lru.get(&key(1)), Some(10));
+    }
+
+    // modules that differ only by the name of an exported function, so that
+    // they have different hashes
+    fn program(name: &str) -> Vec<u8> {
+        let wat = format!(r#"(module (func (export "{name}")))"#);
+        wat2wasm(wat.as_bytes()).unwrap().into_owned()
+    }
+
+    #[test]
+    fn cache_works() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ModuleCache::new(1, Some(dir.path().to_path_buf())).unwrap();
+
+        // artifacts are stored under the hash of the compiler settings
+        let artifact_dir = dir.path().join(compiler_settings_hash().to_string());
+        assert!(artifact_dir.is_dir());
+
+        cache.get_or_compile(&program("foo")).unwrap();
+        cache.get_or_compile(&program("foo")).unwrap();
+        assert_eq!(cache.stats(), CacheStats {
+            memory_hits: 1,
+            disk_hits:   0,
+            misses:      1,
+        });
+        assert!(artifact_dir.join(hash(program("foo")).to_string()).is_file());
+
+        // compiling bar evicts foo from memory, but it's still on disk
+        cache.get_or_compile(&program("bar")).unwrap();
+        cache.get_or_compile(&program("foo")).unwrap();
+        assert_eq!(cache.stats(), CacheStats {
+            memory_hits: 1,
+            disk_hits:   1,
+            misses:      2,
+        });
+
+        // without a directory, evicted modules have to be compiled again
+        let cache = ModuleCache::new(1, None).unwrap();
+        cache.get_or_compile(&program("foo")).unwrap();
+        cache.get_or_compile(&program("bar")).unwrap();
+        cache.get_or_compile(&program("foo")).unwrap();
+        assert_eq!(cache.stats(), CacheStats {
+            memory_hits: 0,
+            disk_hits:   0,
+            misses:      3,
+        });
+    }
+}
@@ -1,6 +1,7 @@
 use {
     crate::{Iterator, VmError, VmResult, WasmVm},
-    grug_app::{PrefixStore, QueryProvider},
+    grug_app::{GasTracker, PrefixStore, QueryProvider},
+    grug_types::GasCosts,
     std::{
         borrow::{Borrow, BorrowMut},
         collections::HashMap,
@@ -8,6 +9,7 @@ use {
         sync::{Arc, RwLock},
     },
     wasmer::{AsStoreMut, AsStoreRef, Instance, Memory, MemoryView, Value},
+    wasmer_middlewares::metering::{get_remaining_points, set_remaining_points, MeteringPoints},
 };
 
 // TODO: add explaination on why wasm_instance field needs to be Options
@@ -17,6 +19,10 @@ pub struct ContextData {
     pub querier: QueryProvider<WasmVm>,
     pub iterators: HashMap<i32, Iterator>,
     pub next_iterator_id: i32,
+    pub gas_tracker: GasTracker,
+    /// The Wasm module's remaining metering points, as of the last time the
+    /// consumed gas was reported to the gas tracker.
+    pub gas_checkpoint: u64,
     /// A non-owning link to the wasmer instance. Need this for doing function
     /// calls (see Environment::call_function).
     wasmer_instance: Option<NonNull<Instance>>,
@@ -32,10 +38,16 @@ unsafe impl Sync for ContextData {}
 pub struct Environment {
     memory: Option<Memory>,
     data: Arc<RwLock<ContextData>>,
+    gas_costs: GasCosts,
 }
 
 impl Environment {
-    pub fn new(store: PrefixStore, querier: QueryProvider<WasmVm>) -> Self {
+    pub fn new(
+        store: PrefixStore,
+        querier: QueryProvider<WasmVm>,
+        gas_tracker: GasTracker,
+        gas_costs: GasCosts,
+    ) -> Self {
         Self {
             memory: None,
             data: Arc::new(RwLock::new(ContextData {
@@ -43,11 +55,18 @@ impl Environment {
                 querier,
                 iterators: HashMap::new(),
                 next_iterator_id: 0,
+                gas_tracker,
+                gas_checkpoint: 0,
                 wasmer_instance: None,
             })),
+            gas_costs,
         }
     }
 
+    pub fn gas_costs(&self) -> &GasCosts {
+        &self.gas_costs
+    }
+
     pub fn memory<'a>(&self, wasm_store: &'a impl AsStoreRef) -> VmResult<MemoryView<'a>> {
         self.memory.as_ref().ok_or(VmError::MemoryNotSet).map(|mem| mem.view(wasm_store))
     }
@@ -95,6 +114,67 @@ impl Environment {
         })
     }
 
+    /// Report the gas consumed by the Wasm module since the last checkpoint to
+    /// the gas tracker. Error if the tracker runs out of gas.
+    pub fn consume_gas(&mut self, wasm_store: &mut impl AsStoreMut) -> VmResult<()> {
+        let points = self.with_wasm_instance(|wasm_instance| -> VmResult<_> {
+            Ok(get_remaining_points(wasm_store, wasm_instance))
+        })?;
+
+        self.with_context_data_mut(|ctx| -> VmResult<_> {
+            let (consumed, remaining) = match points {
+                MeteringPoints::Remaining(remaining) => {
+                    (ctx.gas_checkpoint.saturating_sub(remaining), remaining)
+                },
+                // the module attempted to use more points than it had left. we
+                // can't tell exactly how many, so we consume one more than the
+                // amount that was available, which is sufficient to make the
+                // tracker run out of gas.
+                MeteringPoints::Exhausted => (ctx.gas_checkpoint.saturating_add(1), 0),
+            };
+
+            ctx.gas_checkpoint = remaining;
+            ctx.gas_tracker.consume(consumed, "wasm")?;
+
+            Ok(())
+        })
+    }
+
+    /// Set the Wasm module's remaining metering points to the amount of gas
+    /// left in the gas tracker.
+    ///
+    /// This needs to be called when the instance is created, as well as every
+    /// time after the host has consumed gas on the module's behalf, e.g. in a
+    /// nested contract call.
+    pub fn refresh_gas(&mut self, wasm_store: &mut impl AsStoreMut) -> VmResult<()> {
+        let remaining = self.with_context_data_mut(|ctx| -> VmResult<_> {
+            // if the tracker doesn't have a gas limit, we still need to give
+            // the module some points to work with. u64::MAX is practically
+            // unlimited.
+            ctx.gas_checkpoint = ctx.gas_tracker.remaining().unwrap_or(u64::MAX);
+            Ok(ctx.gas_checkpoint)
+        })?;
+
+        self.with_wasm_instance(|wasm_instance| -> VmResult<_> {
+            set_remaining_points(wasm_store, wasm_instance, remaining);
+            Ok(())
+        })
+    }
+
+    /// Charge the given amount of gas for a host function call, in addition to
+    /// the gas consumed by the Wasm module itself. Error if the tracker runs
+    /// out of gas.
+    pub fn charge_gas(
+        &mut self,
+        wasm_store: &mut impl AsStoreMut,
+        amount: u64,
+        comment: &'static str,
+    ) -> VmResult<()> {
+        self.consume_gas(wasm_store)?;
+        self.with_context_data(|ctx| ctx.gas_tracker.consume(amount, comment))?;
+        self.refresh_gas(wasm_store)
+    }
+
     pub fn call_function1(
         &self,
         wasm_store: &mut impl AsStoreMut,
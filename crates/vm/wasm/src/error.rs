@@ -65,6 +65,18 @@ pub enum VmError {
 
     #[error("Unexpected return type: {0}")]
     ReturnType(&'static str),
+
+    #[error("Invalid Wasm program: {0}")]
+    InvalidProgram(String),
+
+    #[error("Floating-point numbers aren't allowed, but the program uses {0}")]
+    FloatingPoint(String),
+
+    #[error("Module cache error: {0}")]
+    ModuleCache(String),
+
+    #[error("Module cache has already been initialized")]
+    ModuleCacheAlreadyInitialized,
 }
 
 impl From<CompileError> for VmError {
@@ -86,10 +98,111 @@ impl From<VmError> for RuntimeError {
     }
 }
 
+impl VmError {
+    /// The ABCI codespace under which Wasm VM errors are reported.
+    pub const CODESPACE: &'static str = "wasm_vm";
+
+    /// Return the ABCI error code of this error. Each variant has a unique code
+    /// that must not change, as clients rely on them to identify errors.
+    pub fn code(&self) -> u32 {
+        match self {
+            VmError::Std(_) => 1,
+            VmError::FromUtf8(_) => 2,
+            VmError::Export(_) => 3,
+            VmError::MemoryAccess(_) => 4,
+            VmError::Runtime(_) => 5,
+            VmError::Instantiation(_) => 6,
+            VmError::FailedReadLock => 7,
+            VmError::FailedWriteLock => 8,
+            VmError::MemoryNotSet => 9,
+            VmError::StoreNotSet => 10,
+            VmError::WasmerInstanceNotSet => 11,
+            VmError::IteratorNotFound { .. } => 12,
+            VmError::RegionTooSmall { .. } => 13,
+            VmError::ReturnCount { .. } => 14,
+            VmError::ReturnType(_) => 15,
+            VmError::InvalidProgram(_) => 16,
+            VmError::ModuleCache(_) => 17,
+            VmError::ModuleCacheAlreadyInitialized => 18,
+            VmError::FloatingPoint(_) => 19,
+        }
+    }
+}
+
 impl From<VmError> for AppError {
     fn from(err: VmError) -> Self {
-        AppError::Vm(err.to_string())
+        match err {
+            // standard errors are converted to the dedicated AppError variant,
+            // so that they are reported with their own error codes. among
+            // others, this allows out of gas errors to be distinguished.
+            VmError::Std(err) => err.into(),
+            err => AppError::vm(VmError::CODESPACE, err.code(), err),
+        }
     }
 }
 
 pub type VmResult<T> = std::result::Result<T, VmError>;
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // clients rely on the codes to identify errors, so they must not change
+    #[test]
+    fn codes_are_stable() {
+        for (err, code) in [
+            (VmError::Std(StdError::VerificationFailed), 1),
+            (String::from_utf8(vec![0xff]).unwrap_err().into(), 2),
+            (ExportError::Missing("execute".into()).into(), 3),
+            (MemoryAccessError::HeapOutOfBounds.into(), 4),
+            (RuntimeError::new("trap").into(), 5),
+            (VmError::Instantiation("oops".into()), 6),
+            (VmError::FailedReadLock, 7),
+            (VmError::FailedWriteLock, 8),
+            (VmError::MemoryNotSet, 9),
+            (VmError::StoreNotSet, 10),
+            (VmError::WasmerInstanceNotSet, 11),
+            (
+                VmError::IteratorNotFound {
+                    iterator_id: 1,
+                },
+                12,
+            ),
+            (
+                VmError::RegionTooSmall {
+                    offset: 0,
+                    capacity: 1,
+                    data: "oops".into(),
+                },
+                13,
+            ),
+            (
+                VmError::ReturnCount {
+                    name: "execute".into(),
+                    expect: 1,
+                    actual: 0,
+                },
+                14,
+            ),
+            (VmError::ReturnType("u32"), 15),
+            (VmError::InvalidProgram("oops".into()), 16),
+            (VmError::ModuleCache("oops".into()), 17),
+            (VmError::ModuleCacheAlreadyInitialized, 18),
+            (VmError::FloatingPoint("oops".into()), 19),
+        ] {
+            assert_eq!(err.code(), code, "code of {err:?}");
+        }
+
+        // VM errors are reported under the VM's codespace, while standard
+        // errors keep their own
+        let err = AppError::from(VmError::MemoryNotSet);
+        assert_eq!(err.codespace(), "wasm_vm");
+        assert_eq!(err.code(), 9);
+
+        let err = AppError::from(VmError::Std(StdError::VerificationFailed));
+        assert_eq!(err.codespace(), "std");
+        assert_eq!(err.code(), 4);
+    }
+}
@@ -1,6 +1,8 @@
 use {
     crate::{read_from_memory, write_to_memory, Environment, Iterator, VmError, VmResult},
-    grug_types::{from_json_slice, to_json_vec, Addr, Querier, QueryRequest, Record, Storage},
+    grug_types::{
+        from_json_slice, to_json_vec, Addr, Order, Querier, QueryRequest, Record, Storage,
+    },
     tracing::info,
     wasmer::FunctionEnvMut,
 };
@@ -14,6 +16,10 @@ pub fn db_read(mut fe: FunctionEnvMut<Environment>, key_ptr: u32) -> VmResult<u3
         Ok(ctx.store.read(&key))
     })?;
 
+    let bytes = key.len() + maybe_value.as_ref().map_or(0, |value| value.len());
+    let cost = env.gas_costs().db_read.cost(bytes);
+    env.charge_gas(&mut wasm_store, cost, "db_read")?;
+
     // if the record doesn't exist, we return a zero pointer
     let Some(value) = maybe_value else {
         return Ok(0);
@@ -28,7 +34,7 @@ pub fn db_scan(
     max_ptr: u32,
     order: i32,
 ) -> VmResult<i32> {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
 
     // parse iteration parameters provided by the module and create iterator
     let min = if min_ptr != 0 {
@@ -42,6 +48,11 @@ pub fn db_scan(
         None
     };
     let order = order.try_into()?;
+
+    let bytes = min.as_ref().map_or(0, |min| min.len()) + max.as_ref().map_or(0, |max| max.len());
+    let cost = env.gas_costs().db_scan.cost(bytes);
+    env.charge_gas(&mut wasm_store, cost, "db_scan")?;
+
     let iterator = Iterator::new(min, max, order);
 
     // insert the iterator into the ContextData, incrementing the next ID
@@ -61,6 +72,10 @@ pub fn db_next(mut fe: FunctionEnvMut<Environment>, iterator_id: i32) -> VmResul
         Ok(iterator.next(&ctx.store))
     })?;
 
+    let bytes = maybe_record.as_ref().map_or(0, |(k, v)| k.len() + v.len());
+    let cost = env.gas_costs().db_next.cost(bytes);
+    env.charge_gas(&mut wasm_store, cost, "db_next")?;
+
     // if the iterator has reached its end, return a zero pointer
     let Some(record) = maybe_record else {
         return Ok(0);
@@ -81,11 +96,14 @@ fn encode_record((mut k, v): Record) -> Vec<u8> {
 }
 
 pub fn db_write(mut fe: FunctionEnvMut<Environment>, key_ptr: u32, value_ptr: u32) -> VmResult<()> {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
 
     let key = read_from_memory(env, &wasm_store, key_ptr)?;
     let value = read_from_memory(env, &wasm_store, value_ptr)?;
 
+    let cost = env.gas_costs().db_write.cost(key.len() + value.len());
+    env.charge_gas(&mut wasm_store, cost, "db_write")?;
+
     env.with_context_data_mut(|ctx| -> VmResult<_> {
         ctx.store.write(&key, &value);
         Ok(())
@@ -93,16 +111,65 @@ pub fn db_write(mut fe: FunctionEnvMut<Environment>, key_ptr: u32, value_ptr: u3
 }
 
 pub fn db_remove(mut fe: FunctionEnvMut<Environment>, key_ptr: u32) -> VmResult<()> {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
 
     let key = read_from_memory(env, &wasm_store, key_ptr)?;
 
+    let cost = env.gas_costs().db_remove.cost(key.len());
+    env.charge_gas(&mut wasm_store, cost, "db_remove")?;
+
     env.with_context_data_mut(|ctx| -> VmResult<_> {
         ctx.store.remove(&key);
         Ok(())
     })
 }
 
+pub fn db_remove_range(
+    mut fe: FunctionEnvMut<Environment>,
+    min_ptr: u32,
+    max_ptr: u32,
+) -> VmResult<()> {
+    let (env, mut wasm_store) = fe.data_and_store_mut();
+
+    // same as in `db_scan`, a zero pointer means the bound is None
+    let min = if min_ptr != 0 {
+        Some(read_from_memory(env, &wasm_store, min_ptr)?)
+    } else {
+        None
+    };
+    let max = if max_ptr != 0 {
+        Some(read_from_memory(env, &wasm_store, max_ptr)?)
+    } else {
+        None
+    };
+
+    let bytes = min.as_ref().map_or(0, |min| min.len()) + max.as_ref().map_or(0, |max| max.len());
+    let cost = env.gas_costs().db_remove_range.cost(bytes);
+    env.charge_gas(&mut wasm_store, cost, "db_remove_range")?;
+
+    // charge for each record as if it were removed individually. stop counting
+    // once the cost exceeds the gas left, so that the host doesn't scan an
+    // unbounded range for a call that fails anyway.
+    let db_remove = env.gas_costs().db_remove;
+    let cost = env.with_context_data(|ctx| -> VmResult<_> {
+        let remaining = ctx.gas_tracker.remaining().unwrap_or(u64::MAX);
+        let mut cost = 0_u64;
+        for (key, _) in ctx.store.scan(min.as_deref(), max.as_deref(), Order::Ascending) {
+            cost = cost.saturating_add(db_remove.cost(key.len()));
+            if cost > remaining {
+                break;
+            }
+        }
+        Ok(cost)
+    })?;
+    env.charge_gas(&mut wasm_store, cost, "db_remove_range")?;
+
+    env.with_context_data_mut(|ctx| -> VmResult<_> {
+        ctx.store.remove_range(min.as_deref(), max.as_deref());
+        Ok(())
+    })
+}
+
 pub fn debug(mut fe: FunctionEnvMut<Environment>, addr_ptr: u32, msg_ptr: u32) -> VmResult<()> {
     let (env, wasm_store) = fe.data_and_store_mut();
 
@@ -120,11 +187,19 @@ pub fn query_chain(mut fe: FunctionEnvMut<Environment>, req_ptr: u32) -> VmResul
     let (env, mut wasm_store) = fe.data_and_store_mut();
 
     let req_bytes = read_from_memory(env, &wasm_store, req_ptr)?;
-    let req: QueryRequest = from_json_slice(req_bytes)?;
+    let req: QueryRequest = from_json_slice(&req_bytes)?;
 
+    // report the gas consumed so far to the tracker before making the query,
+    // so that the nested call (if any) knows how much gas is available; then
+    // charge the cost of the query itself, which also updates the remaining
+    // points to account for what the nested call has consumed.
+    env.consume_gas(&mut wasm_store)?;
     let res = env.with_context_data(|ctx| ctx.querier.query_chain(req))?;
     let res_bytes = to_json_vec(&res)?;
 
+    let cost = env.gas_costs().query_chain.cost(req_bytes.len() + res_bytes.len());
+    env.charge_gas(&mut wasm_store, cost, "query_chain")?;
+
     write_to_memory(env, &mut wasm_store, &res_bytes)
 }
 
@@ -134,12 +209,15 @@ pub fn secp256k1_verify(
     sig_ptr: u32,
     pk_ptr: u32,
 ) -> VmResult<i32> {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
 
     let msg_hash = read_from_memory(env, &wasm_store, msg_hash_ptr)?;
     let sig = read_from_memory(env, &wasm_store, sig_ptr)?;
     let pk = read_from_memory(env, &wasm_store, pk_ptr)?;
 
+    let cost = env.gas_costs().secp256k1_verify.cost(msg_hash.len() + sig.len() + pk.len());
+    env.charge_gas(&mut wasm_store, cost, "secp256k1_verify")?;
+
     match grug_crypto::secp256k1_verify(&msg_hash, &sig, &pk) {
         Ok(()) => Ok(0),
         Err(_) => Ok(1),
@@ -152,12 +230,15 @@ pub fn secp256r1_verify(
     sig_ptr: u32,
     pk_ptr: u32,
 ) -> VmResult<i32> {
-    let (env, wasm_store) = fe.data_and_store_mut();
+    let (env, mut wasm_store) = fe.data_and_store_mut();
 
     let msg_hash = read_from_memory(env, &wasm_store, msg_hash_ptr)?;
     let sig = read_from_memory(env, &wasm_store, sig_ptr)?;
     let pk = read_from_memory(env, &wasm_store, pk_ptr)?;
 
+    let cost = env.gas_costs().secp256r1_verify.cost(msg_hash.len() + sig.len() + pk.len());
+    env.charge_gas(&mut wasm_store, cost, "secp256r1_verify")?;
+
     match grug_crypto::secp256r1_verify(&msg_hash, &sig, &pk) {
         Ok(()) => Ok(0),
         Err(_) => Ok(1),
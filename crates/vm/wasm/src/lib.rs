@@ -1,9 +1,14 @@
+mod cache;
 mod environment;
 mod error;
 mod imports;
 mod iterator;
 mod memory;
 mod region;
+mod validation;
 mod vm;
 
-pub use {environment::*, error::*, imports::*, iterator::*, memory::*, region::*, vm::*};
+pub use {
+    cache::*, environment::*, error::*, imports::*, iterator::*, memory::*, region::*,
+    validation::*, vm::*,
+};
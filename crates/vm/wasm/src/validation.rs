@@ -0,0 +1,361 @@
+use {
+    crate::{VmError, VmResult},
+    wasmer::wasmparser::{CompositeType, Operator, Parser, Payload, TypeRef, ValType, Validator},
+};
+
+/// Functions that a contract must export in order to be used by the host.
+const REQUIRED_EXPORTS: &[&str] = &["allocate", "deallocate"];
+
+/// Functions through which a contract can be created. A contract must export
+/// at least one of them: regular contracts are instantiated, while IBC light
+/// clients are created via `ibc_client_create`.
+///
+/// Other entry points are optional, and calling one that isn't exported fails
+/// at call time.
+const CREATION_EXPORTS: &[&str] = &["instantiate", "ibc_client_create"];
+
+/// Functions that the host provides to contracts, under the `env` module.
+/// Contracts may not import anything else.
+const KNOWN_IMPORTS: &[&str] = &[
+    "db_read",
+    "db_scan",
+    "db_next",
+    "db_write",
+    "db_remove",
+    "db_remove_range",
+    "debug",
+    "query_chain",
+    "secp256k1_verify",
+    "secp256r1_verify",
+];
+
+/// The maximum number of 64 KiB pages a contract's memory can start with, or
+/// be declared to grow to. 512 pages is 32 MiB.
+const MAX_MEMORY_PAGES: u64 = 512;
+
+/// Statically validate a Wasm module, so that invalid code is rejected when
+/// it's uploaded, rather than when it's first instantiated.
+///
+/// The module must:
+/// - be a well-formed Wasm module;
+/// - export the functions that the host requires, and at least one through
+///   which it can be created;
+/// - import only the host functions that are known to exist;
+/// - not declare a memory larger than `MAX_MEMORY_PAGES`;
+/// - not use floating-point instructions or types, whose results may differ
+///   across platforms.
+pub fn validate_wasm(wasm: &[u8]) -> VmResult<()> {
+    Validator::new().validate_all(wasm).map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+
+    let mut exports = vec![];
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.map_err(|err| VmError::InvalidProgram(err.to_string()))? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+                    if import.module != "env" || !KNOWN_IMPORTS.contains(&import.name) {
+                        return Err(VmError::InvalidProgram(format!(
+                            "unknown import `{}::{}`",
+                            import.module, import.name
+                        )));
+                    }
+                    if let TypeRef::Global(global) = import.ty {
+                        ensure_not_float(global.content_type)?;
+                    }
+                }
+            },
+            Payload::TypeSection(reader) => {
+                for rec_group in reader {
+                    let rec_group =
+                        rec_group.map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+                    for sub_type in rec_group.types() {
+                        if let CompositeType::Func(func_type) = &sub_type.composite_type {
+                            for ty in func_type.params().iter().chain(func_type.results()) {
+                                ensure_not_float(*ty)?;
+                            }
+                        }
+                    }
+                }
+            },
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    let global = global.map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+                    ensure_not_float(global.ty.content_type)?;
+                }
+            },
+            Payload::CodeSectionEntry(body) => {
+                let locals = body
+                    .get_locals_reader()
+                    .map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+                for local in locals {
+                    let (_, ty) = local.map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+                    ensure_not_float(ty)?;
+                }
+
+                let operators = body
+                    .get_operators_reader()
+                    .map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+                for operator in operators {
+                    let operator =
+                        operator.map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+                    ensure_not_float_operator(&operator)?;
+                }
+            },
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+                    exports.push(export.name);
+                }
+            },
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    let memory = memory.map_err(|err| VmError::InvalidProgram(err.to_string()))?;
+                    let pages = memory.maximum.unwrap_or(memory.initial).max(memory.initial);
+                    if pages > MAX_MEMORY_PAGES {
+                        return Err(VmError::InvalidProgram(format!(
+                            "memory too large: {pages} pages > {MAX_MEMORY_PAGES} pages"
+                        )));
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+
+    for name in REQUIRED_EXPORTS {
+        if !exports.contains(name) {
+            return Err(VmError::InvalidProgram(format!("missing export `{name}`")));
+        }
+    }
+
+    if !CREATION_EXPORTS.iter().any(|name| exports.contains(name)) {
+        return Err(VmError::InvalidProgram(format!(
+            "missing export: one of {}",
+            CREATION_EXPORTS.iter().map(|name| format!("`{name}`")).collect::<Vec<_>>().join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+fn ensure_not_float(ty: ValType) -> VmResult<()> {
+    if matches!(ty, ValType::F32 | ValType::F64) {
+        return Err(VmError::FloatingPoint(format!("type `{ty}`")));
+    }
+
+    Ok(())
+}
+
+// the names of all floating-point operators contain `F32` or `F64`, including
+// conversions between integers and floats, and the SIMD ones. so do the debug
+// strings of blocks and selects typed with a float.
+fn ensure_not_float_operator(operator: &Operator) -> VmResult<()> {
+    let operator = format!("{operator:?}");
+    if operator.contains("F32") || operator.contains("F64") {
+        return Err(VmError::FloatingPoint(format!("operator `{operator}`")));
+    }
+
+    Ok(())
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {super::*, wasmer::wat2wasm};
+
+    const MINIMAL_EXPORTS: &str = r#"
+        (func (export "allocate") (param i32) (result i32) i32.const 0)
+        (func (export "deallocate") (param i32))
+        (func (export "instantiate") (param i32 i32) (result i32) i32.const 0)
+    "#;
+
+    fn validate_wat(body: &str) -> VmResult<()> {
+        let wat = format!("(module {body})");
+        validate_wasm(&wat2wasm(wat.as_bytes()).unwrap())
+    }
+
+    fn assert_invalid(res: VmResult<()>, msg: &str) {
+        match res {
+            Err(VmError::InvalidProgram(err)) => assert!(err.contains(msg), "{err}"),
+            res => panic!("expected the program to be invalid, got: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn valid_module_passes() {
+        let body = format!(
+            r#"
+            (import "env" "db_read" (func (param i32) (result i32)))
+            (memory (export "memory") 17 512)
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        validate_wat(&body).unwrap();
+    }
+
+    // the exports of the solomachine light client, which is created via
+    // `ibc_client_create` rather than instantiated
+    #[test]
+    fn ibc_client_passes() {
+        let body = r#"
+            (memory (export "memory") 17)
+            (func (export "allocate") (param i32) (result i32) i32.const 0)
+            (func (export "deallocate") (param i32))
+            (func (export "ibc_client_create") (param i32 i32 i32) (result i32) i32.const 0)
+            (func (export "ibc_client_update") (param i32 i32) (result i32) i32.const 0)
+            (func (export "ibc_client_verify") (param i32 i32) (result i32) i32.const 0)
+        "#;
+        validate_wat(body).unwrap();
+    }
+
+    #[test]
+    fn missing_export_fails() {
+        let body = r#"
+            (func (export "deallocate") (param i32))
+            (func (export "instantiate") (param i32 i32) (result i32) i32.const 0)
+        "#;
+        assert_invalid(validate_wat(body), "missing export `allocate`");
+
+        let body = r#"
+            (func (export "allocate") (param i32) (result i32) i32.const 0)
+            (func (export "deallocate") (param i32))
+            (func (export "execute") (param i32 i32) (result i32) i32.const 0)
+        "#;
+        assert_invalid(
+            validate_wat(body),
+            "missing export: one of `instantiate`, `ibc_client_create`",
+        );
+    }
+
+    #[test]
+    fn unknown_import_fails() {
+        let body = format!(
+            r#"
+            (import "env" "do_something_naughty" (func))
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_invalid(validate_wat(&body), "unknown import `env::do_something_naughty`");
+
+        let body = format!(
+            r#"
+            (import "wasi_snapshot_preview1" "db_read" (func (param i32) (result i32)))
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_invalid(validate_wat(&body), "unknown import `wasi_snapshot_preview1::db_read`");
+    }
+
+    #[test]
+    fn too_much_memory_fails() {
+        let body = format!(
+            r#"
+            (memory 1 513)
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_invalid(validate_wat(&body), "memory too large");
+
+        let body = format!(
+            r#"
+            (memory 1000)
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_invalid(validate_wat(&body), "memory too large");
+    }
+
+    fn assert_float(res: VmResult<()>, msg: &str) {
+        match res {
+            Err(VmError::FloatingPoint(err)) => assert!(err.contains(msg), "{err}"),
+            res => panic!("expected the program to use floats, got: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn floats_fail() {
+        // a floating-point operator
+        let body = format!(
+            r#"
+            (func (param i32) (result i32)
+                f32.const 1
+                f32.const 2
+                f32.add
+                drop
+                local.get 0)
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_float(validate_wat(&body), "operator `F32Const");
+
+        // a conversion between an integer and a float
+        let body = format!(
+            r#"
+            (func (param i32) (result i32)
+                local.get 0
+                f64.convert_i32_s
+                i32.trunc_f64_s)
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_float(validate_wat(&body), "operator `F64ConvertI32S`");
+
+        // a block typed with a float
+        let body = format!(
+            r#"
+            (func (param i32) (result i32)
+                (block (result f32) unreachable)
+                drop
+                local.get 0)
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_float(validate_wat(&body), "operator `Block");
+
+        // a function signature
+        let body = format!(
+            r#"
+            (func (param f64) (result i32) i32.const 0)
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_float(validate_wat(&body), "type `f64`");
+
+        // a local
+        let body = format!(
+            r#"
+            (func (local f32))
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_float(validate_wat(&body), "type `f32`");
+
+        // a global
+        let body = format!(
+            r#"
+            (global f64 (f64.const 0))
+            {MINIMAL_EXPORTS}
+            "#
+        );
+        assert_float(validate_wat(&body), "type `f64`");
+    }
+
+    // the contracts must be compiled with `just optimize` first, which places
+    // them in the `artifacts` directory at the root of the repository
+    #[test]
+    #[ignore = "requires the contracts to be built with `just optimize`"]
+    fn built_contracts_pass() {
+        let artifacts = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../artifacts");
+        for name in ["grug_account", "grug_bank", "grug_ibc_solomachine", "grug_taxman"] {
+            let path = format!("{artifacts}/{name}.wasm");
+            let wasm = std::fs::read(&path).unwrap_or_else(|err| {
+                panic!("failed to read `{path}`: {err}");
+            });
+            validate_wasm(&wasm).unwrap_or_else(|err| {
+                panic!("`{name}` failed validation: {err}");
+            });
+        }
+    }
+}
@@ -1,15 +1,61 @@
 use {
     crate::{
-        db_next, db_read, db_remove, db_scan, db_write, debug, query_chain, read_then_wipe,
-        secp256k1_verify, secp256r1_verify, write_to_memory, Environment, VmError, VmResult,
+        db_next, db_read, db_remove, db_remove_range, db_scan, db_write, debug, module_cache,
+        query_chain, read_then_wipe, secp256k1_verify, secp256r1_verify, validate_wasm,
+        write_to_memory, CachedModule, Environment, VmError, VmResult,
     },
-    grug_app::{PrefixStore, QueryProvider, Vm},
-    grug_types::{to_borsh_vec, Context},
+    grug_app::{GasTracker, PrefixStore, QueryProvider, Vm},
+    grug_types::{hash, to_borsh_vec, Context, GasCosts, Hash},
+    std::sync::Arc,
     wasmer::{
-        imports, Function, FunctionEnv, Instance as WasmerInstance, Module, Singlepass, Store,
+        imports, wasmparser::Operator, AsStoreMut, CompilerConfig, Engine, Function, FunctionEnv,
+        Instance as WasmerInstance, Module, Singlepass, Store, Value,
     },
+    wasmer_middlewares::Metering,
 };
 
+/// Gas cost of executing each Wasm operator.
+///
+/// For now, all operators cost the same. We may want to differentiate them
+/// based on benchmark results in the future.
+const OPERATOR_COST: u64 = 1;
+
+/// Whether NaNs produced by floating-point instructions are canonicalized.
+const CANONICALIZE_NANS: bool = true;
+
+fn cost_function(_operator: &Operator) -> u64 {
+    OPERATOR_COST
+}
+
+/// A hash of the settings that `compile` uses, which affect the compiled code.
+///
+/// A compiled artifact is only valid for the settings it was compiled with, so
+/// the module cache stores artifacts under a directory named after this hash.
+/// Any change to `compile` or `cost_function` must be reflected here.
+pub fn compiler_settings_hash() -> Hash {
+    hash(format!(
+        "compiler=singlepass;canonicalize_nans={CANONICALIZE_NANS};operator_cost={OPERATOR_COST}"
+    ))
+}
+
+/// Compile Wasm byte code into a module.
+///
+/// For now we use the singlepass compiler, with the metering middleware which
+/// injects gas accounting into the compiled code. A new engine is created for
+/// each module, as a metering middleware can't be used for more than one.
+///
+/// NaNs produced by floating-point instructions are canonicalized, since their
+/// bit patterns may otherwise differ across platforms and break consensus.
+pub(crate) fn compile(program: &[u8]) -> VmResult<CachedModule> {
+    let mut compiler = Singlepass::default();
+    compiler.canonicalize_nans(CANONICALIZE_NANS);
+    compiler.push_middleware(Arc::new(Metering::new(0, cost_function)));
+    let engine = Engine::from(compiler);
+    let module = Module::new(&engine, program)?;
+
+    Ok(CachedModule { engine, module })
+}
+
 pub struct WasmVm {
     _wasm_instance: Box<WasmerInstance>,
     wasm_store: Store,
@@ -23,18 +69,22 @@ impl Vm for WasmVm {
     fn build_instance(
         storage: PrefixStore,
         querier: QueryProvider<Self>,
+        gas_tracker: GasTracker,
+        gas_costs: GasCosts,
         program: Vec<u8>,
     ) -> Result<Self, Self::Error> {
-        // create Wasm store
-        // for now we use the singlepass compiler
-        let mut wasm_store = Store::new(Singlepass::default());
+        // load the compiled module from the cache, or compile it if not found.
+        // the initial metering points don't matter here; they are set to the
+        // gas tracker's remaining amount once the instance is created.
+        let CachedModule { engine, module } = module_cache().get_or_compile(&program)?;
 
-        // compile Wasm byte code into module
-        let module = Module::new(&wasm_store, program)?;
+        // create Wasm store, using the same engine the module was created with
+        let mut wasm_store = Store::new(engine);
 
         // create function environment and register imports
         // note: memory/store/instance in the env hasn't been set yet at this point
-        let fe = FunctionEnv::new(&mut wasm_store, Environment::new(storage, querier));
+        let env = Environment::new(storage, querier, gas_tracker, gas_costs);
+        let fe = FunctionEnv::new(&mut wasm_store, env);
         let import_obj = imports! {
             "env" => {
                 "db_read" => Function::new_typed_with_env(&mut wasm_store, &fe, db_read),
@@ -42,6 +92,7 @@ impl Vm for WasmVm {
                 "db_next" => Function::new_typed_with_env(&mut wasm_store, &fe, db_next),
                 "db_write" => Function::new_typed_with_env(&mut wasm_store, &fe, db_write),
                 "db_remove" => Function::new_typed_with_env(&mut wasm_store, &fe, db_remove),
+                "db_remove_range" => Function::new_typed_with_env(&mut wasm_store, &fe, db_remove_range),
                 "debug" => Function::new_typed_with_env(&mut wasm_store, &fe, debug),
                 "query_chain" => Function::new_typed_with_env(&mut wasm_store, &fe, query_chain),
                 "secp256k1_verify" => Function::new_typed_with_env(&mut wasm_store, &fe, secp256k1_verify),
@@ -53,10 +104,15 @@ impl Vm for WasmVm {
         let wasm_instance = WasmerInstance::new(&mut wasm_store, &module, &import_obj)?;
         let wasm_instance = Box::new(wasm_instance);
 
-        // set memory/store/instance in the env
-        let env = fe.as_mut(&mut wasm_store);
-        env.set_memory(&wasm_instance)?;
-        env.set_wasm_instance(wasm_instance.as_ref())?;
+        // set memory/store/instance in the env, then give the instance as many
+        // metering points as there is gas remaining in the tracker
+        {
+            let mut fe_mut = fe.clone().into_mut(&mut wasm_store);
+            let (env, mut wasm_store) = fe_mut.data_and_store_mut();
+            env.set_memory(&wasm_instance)?;
+            env.set_wasm_instance(wasm_instance.as_ref())?;
+            env.refresh_gas(&mut wasm_store)?;
+        }
 
         Ok(Self {
             _wasm_instance: wasm_instance,
@@ -65,60 +121,355 @@ impl Vm for WasmVm {
         })
     }
 
-    fn call_in_0_out_1(mut self, name: &str, ctx: &Context) -> VmResult<Vec<u8>> {
-        let mut fe_mut = self.fe.clone().into_mut(&mut self.wasm_store);
-        let (env, mut wasm_store) = fe_mut.data_and_store_mut();
-
-        let ctx_ptr = write_to_memory(env, &mut wasm_store, &to_borsh_vec(ctx)?)?;
-        let res_ptr: u32 = env
-            .call_function1(&mut wasm_store, name, &[ctx_ptr.into()])?
-            .try_into()
-            .map_err(VmError::ReturnType)?;
+    fn validate_program(program: &Self::Program) -> VmResult<()> {
+        validate_wasm(program)
+    }
 
-        read_then_wipe(env, &mut wasm_store, res_ptr)
+    fn call_in_0_out_1(self, name: &str, ctx: &Context) -> VmResult<Vec<u8>> {
+        self.call(name, ctx, &[])
     }
 
     fn call_in_1_out_1(
-        mut self,
+        self,
         name: &str,
         ctx: &Context,
         param1: impl AsRef<[u8]>,
     ) -> VmResult<Vec<u8>> {
-        let mut fe_mut = self.fe.clone().into_mut(&mut self.wasm_store);
-        let (env, mut wasm_store) = fe_mut.data_and_store_mut();
-
-        let ctx_ptr = write_to_memory(env, &mut wasm_store, &to_borsh_vec(ctx)?)?;
-        let param1_ptr = write_to_memory(env, &mut wasm_store, param1.as_ref())?;
-        let res_ptr: u32 = env
-            .call_function1(&mut wasm_store, name, &[ctx_ptr.into(), param1_ptr.into()])?
-            .try_into()
-            .map_err(VmError::ReturnType)?;
-
-        read_then_wipe(env, &mut wasm_store, res_ptr)
+        self.call(name, ctx, &[param1.as_ref()])
     }
 
     fn call_in_2_out_1(
-        mut self,
+        self,
         name: &str,
         ctx: &Context,
         param1: impl AsRef<[u8]>,
         param2: impl AsRef<[u8]>,
     ) -> VmResult<Vec<u8>> {
+        self.call(name, ctx, &[param1.as_ref(), param2.as_ref()])
+    }
+}
+
+impl WasmVm {
+    fn call(mut self, name: &str, ctx: &Context, params: &[&[u8]]) -> VmResult<Vec<u8>> {
         let mut fe_mut = self.fe.clone().into_mut(&mut self.wasm_store);
         let (env, mut wasm_store) = fe_mut.data_and_store_mut();
 
-        let ctx_ptr = write_to_memory(env, &mut wasm_store, &to_borsh_vec(ctx)?)?;
-        let param1_ptr = write_to_memory(env, &mut wasm_store, param1.as_ref())?;
-        let param2_ptr = write_to_memory(env, &mut wasm_store, param2.as_ref())?;
-        let res_ptr: u32 = env
-            .call_function1(
-                &mut wasm_store,
-                name,
-                &[ctx_ptr.into(), param1_ptr.into(), param2_ptr.into()],
-            )?
-            .try_into()
-            .map_err(VmError::ReturnType)?;
-
-        read_then_wipe(env, &mut wasm_store, res_ptr)
+        let res = call_function(env, &mut wasm_store, name, ctx, params);
+
+        // report the consumed gas to the tracker. if the tracker has run out of
+        // gas, either in this call or in a nested call (e.g. a query made via
+        // `query_chain`), the out of gas error takes precedence over whatever
+        // error the function call itself may have returned.
+        env.consume_gas(&mut wasm_store)?;
+
+        res
+    }
+}
+
+fn call_function(
+    env: &mut Environment,
+    wasm_store: &mut impl AsStoreMut,
+    name: &str,
+    ctx: &Context,
+    params: &[&[u8]],
+) -> VmResult<Vec<u8>> {
+    let mut args: Vec<Value> = vec![write_to_memory(env, wasm_store, &to_borsh_vec(ctx)?)?.into()];
+    for param in params {
+        args.push(write_to_memory(env, wasm_store, param)?.into());
+    }
+
+    let res_ptr: u32 = env
+        .call_function1(wasm_store, name, &args)?
+        .try_into()
+        .map_err(VmError::ReturnType)?;
+
+    read_then_wipe(env, wasm_store, res_ptr)
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        grug_app::{create_vm_instance, App, AppError, GAS_COSTS},
+        grug_db_memory::MemDb,
+        grug_types::{
+            to_json_value, Addr, Binary, BlockInfo, Coins, Config, ConsensusParams, Empty,
+            GenesisState, Message, MockStorage, Permission, Permissions, Timestamp, Tx, Uint64,
+            DEFAULT_HANDLER_GAS_LIMIT, DEFAULT_MAX_CALL_DEPTH, GENESIS_SENDER,
+        },
+        std::collections::{BTreeMap, BTreeSet},
+        wasmer::wat2wasm,
+    };
+
+    fn mock_config() -> Config {
+        Config {
+            owner: None,
+            bank: Addr::mock(1),
+            taxman: Addr::mock(2),
+            begin_blockers: vec![],
+            end_blockers: vec![],
+            blocker_failure_policies: BTreeMap::new(),
+            cronjobs: BTreeMap::new(),
+            permissions: Permissions {
+                upload: Permission::Everybody,
+                instantiate: Permission::Everybody,
+                create_client: Permission::Everybody,
+                create_connection: Permission::Everybody,
+                create_channel: Permission::Everybody,
+            },
+            allowed_clients: BTreeSet::new(),
+            gas_costs: GasCosts::default(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            validator_set: None,
+            proposal_handler: None,
+            vote_extension_handler: None,
+            handler_gas_limit: DEFAULT_HANDLER_GAS_LIMIT,
+        }
+    }
+
+    // an account whose `before_tx` never returns. `allocate` is a bump
+    // allocator, and `instantiate` returns an empty response, found at offset 0
+    // and pointed to by the region at offset 512.
+    const LOOPING_ACCOUNT: &str = r#"
+        (module
+            (memory (export "memory") 2)
+            (global $heap (mut i32) (i32.const 1024))
+            (data (i32.const 0) "{\"ok\":{\"submsgs\":[],\"attributes\":[]}}")
+            (data (i32.const 512) "\00\00\00\00\25\00\00\00\25\00\00\00")
+            (func (export "allocate") (param $size i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $heap))
+                (i32.store (local.get $ptr) (i32.add (local.get $ptr) (i32.const 12)))
+                (i32.store offset=4 (local.get $ptr) (local.get $size))
+                (i32.store offset=8 (local.get $ptr) (i32.const 0))
+                (global.set $heap
+                    (i32.add (local.get $ptr) (i32.add (local.get $size) (i32.const 12))))
+                (local.get $ptr))
+            (func (export "deallocate") (param i32))
+            (func (export "instantiate") (param i32 i32) (result i32)
+                (i32.const 512))
+            (func (export "before_tx") (param i32 i32) (result i32)
+                (loop $spin (br $spin))
+                (i32.const 0)))
+    "#;
+
+    #[test]
+    fn infinite_loop_runs_out_of_gas() {
+        // codes are stored borsh-encoded, the same way they're uploaded
+        let wasm = wat2wasm(LOOPING_ACCOUNT.as_bytes()).unwrap().into_owned();
+        let code = to_borsh_vec(&wasm).unwrap();
+        let code_hash = hash(&code);
+        let salt = Binary::from(b"account".to_vec());
+        let account = Addr::compute(&GENESIS_SENDER, &code_hash, &salt);
+
+        let app = App::<MemDb, WasmVm>::new(MemDb::new());
+        let block = |height: u64| BlockInfo {
+            height:    Uint64::new(height),
+            timestamp: Timestamp::from_seconds(height),
+            hash:      Hash::ZERO,
+        };
+        let genesis_state = GenesisState {
+            config: mock_config(),
+            msgs: vec![
+                Message::Upload {
+                    code: code.into(),
+                    instantiate_permission: None,
+                },
+                Message::Instantiate {
+                    code_hash,
+                    msg: to_json_value(&Empty {}).unwrap(),
+                    salt,
+                    funds: Coins::new_empty(),
+                    admin: None,
+                    non_reentrant: false,
+                },
+            ],
+            exported: None,
+        };
+        app.do_init_chain(
+            "dev-1".to_string(),
+            block(0),
+            genesis_state,
+            ConsensusParams::default(),
+            vec![],
+        )
+        .unwrap();
+
+        // the loop is aborted once the tx's gas limit is used up, and the gas
+        // is reported as such
+        let tx = Tx {
+            sender: account,
+            msgs: vec![],
+            gas_limit: Uint64::new(100_000),
+            expiry_height: None,
+            expiry_timestamp: None,
+            credential: Binary::empty(),
+        };
+        let outcome = app.do_finalize_block(block(1), vec![], vec![(Hash::ZERO, tx)]).unwrap();
+        let tx_outcome = &outcome.tx_results[0];
+        assert!(matches!(
+            tx_outcome.result,
+            Err(AppError::OutOfGas {
+                limit: 100_000,
+                ..
+            })
+        ));
+        assert_eq!(tx_outcome.gas_limit, 100_000);
+        assert!(tx_outcome.gas_used > tx_outcome.gas_limit);
+    }
+
+    // a contract that adds two floats in `instantiate`
+    const FLOAT_ADDER: &str = r#"
+        (module
+            (memory (export "memory") 2)
+            (func (export "allocate") (param i32) (result i32) (i32.const 0))
+            (func (export "deallocate") (param i32))
+            (func (export "instantiate") (param i32 i32) (result i32)
+                (drop (f32.add (f32.const 1) (f32.const 2)))
+                (i32.const 0)))
+    "#;
+
+    #[test]
+    fn uploading_floats_fails() {
+        let wasm = wat2wasm(FLOAT_ADDER.as_bytes()).unwrap().into_owned();
+        let code = to_borsh_vec(&wasm).unwrap();
+
+        let app = App::<MemDb, WasmVm>::new(MemDb::new());
+        let genesis_state = GenesisState {
+            config: mock_config(),
+            msgs: vec![Message::Upload {
+                code: code.into(),
+                instantiate_permission: None,
+            }],
+            exported: None,
+        };
+        let err = app
+            .do_init_chain(
+                "dev-1".to_string(),
+                BlockInfo {
+                    height:    Uint64::new(0),
+                    timestamp: Timestamp::from_seconds(0),
+                    hash:      Hash::ZERO,
+                },
+                genesis_state,
+                ConsensusParams::default(),
+                vec![],
+            )
+            .unwrap_err();
+        assert_eq!(err.codespace(), VmError::CODESPACE);
+        assert_eq!(err.code(), 19);
+    }
+
+    // a contract whose `execute` calls each of the storage and crypto host
+    // functions once, except for `db_read`, which it calls twice. the key is
+    // found at offset 0 and the value at offset 16, pointed to by the regions
+    // at offsets 256 and 268. the message hash, signature, and public key are
+    // all zeros, pointed to by the regions at offsets 280, 292, and 304. the
+    // response doesn't matter, so the key's region is returned.
+    const HOST_FUNCTION_CALLER: &str = r#"
+        (module
+            (import "env" "db_read" (func $db_read (param i32) (result i32)))
+            (import "env" "db_scan" (func $db_scan (param i32 i32 i32) (result i32)))
+            (import "env" "db_next" (func $db_next (param i32) (result i32)))
+            (import "env" "db_write" (func $db_write (param i32 i32)))
+            (import "env" "db_remove" (func $db_remove (param i32)))
+            (import "env" "db_remove_range" (func $db_remove_range (param i32 i32)))
+            (import "env" "secp256k1_verify"
+                (func $secp256k1_verify (param i32 i32 i32) (result i32)))
+            (import "env" "secp256r1_verify"
+                (func $secp256r1_verify (param i32 i32 i32) (result i32)))
+            (memory (export "memory") 2)
+            (global $heap (mut i32) (i32.const 1024))
+            (data (i32.const 0) "key")
+            (data (i32.const 16) "value")
+            (data (i32.const 256) "\00\00\00\00\03\00\00\00\03\00\00\00")
+            (data (i32.const 268) "\10\00\00\00\05\00\00\00\05\00\00\00")
+            (data (i32.const 280) "\20\00\00\00\20\00\00\00\20\00\00\00")
+            (data (i32.const 292) "\40\00\00\00\40\00\00\00\40\00\00\00")
+            (data (i32.const 304) "\80\00\00\00\21\00\00\00\21\00\00\00")
+            (func (export "allocate") (param $size i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $heap))
+                (i32.store (local.get $ptr) (i32.add (local.get $ptr) (i32.const 12)))
+                (i32.store offset=4 (local.get $ptr) (local.get $size))
+                (i32.store offset=8 (local.get $ptr) (i32.const 0))
+                (global.set $heap
+                    (i32.add (local.get $ptr) (i32.add (local.get $size) (i32.const 12))))
+                (local.get $ptr))
+            (func (export "deallocate") (param i32))
+            (func (export "execute") (param i32 i32) (result i32)
+                (call $db_write (i32.const 256) (i32.const 268))
+                (drop (call $db_read (i32.const 256)))
+                (drop (call $db_next (call $db_scan (i32.const 0) (i32.const 0) (i32.const 1))))
+                (call $db_remove (i32.const 256))
+                (drop (call $db_read (i32.const 256)))
+                (call $db_remove_range (i32.const 0) (i32.const 0))
+                (drop (call $secp256k1_verify (i32.const 280) (i32.const 292) (i32.const 304)))
+                (drop (call $secp256r1_verify (i32.const 280) (i32.const 292) (i32.const 304)))
+                (i32.const 256)))
+    "#;
+
+    fn gas_used_by_host_function_caller(gas_costs: &GasCosts) -> u64 {
+        let code = wat2wasm(HOST_FUNCTION_CALLER.as_bytes()).unwrap().into_owned();
+        let block = BlockInfo {
+            height:    Uint64::new(1),
+            timestamp: Timestamp::from_seconds(1),
+            hash:      Hash::ZERO,
+        };
+        let ctx = Context {
+            chain_id:        "dev-1".to_string(),
+            block_height:    block.height,
+            block_timestamp: block.timestamp,
+            block_hash:      block.hash.clone(),
+            contract:        Addr::mock(1),
+            sender:          None,
+            funds:           None,
+            simulate:        None,
+        };
+
+        let mut storage = MockStorage::new();
+        GAS_COSTS.save(&mut storage, gas_costs).unwrap();
+
+        let gas_tracker = GasTracker::new_limitless();
+        let instance = create_vm_instance::<WasmVm>(
+            Box::new(storage),
+            block,
+            gas_tracker.clone(),
+            &ctx.contract,
+            code,
+        )
+        .unwrap();
+        instance.call_in_1_out_1("execute", &ctx, b"{}").unwrap();
+
+        gas_tracker.used()
+    }
+
+    #[test]
+    fn host_functions_charge_their_gas_costs() {
+        const EXTRA: u64 = 1_000_000;
+
+        let baseline = gas_used_by_host_function_caller(&GasCosts::default());
+
+        // raising the flat fee of one host function raises the gas used by
+        // that much for each call to it, and nothing else
+        type Raise = fn(&mut GasCosts);
+        let cases: [(Raise, u64); 8] = [
+            (|costs| costs.db_read.flat += EXTRA, 2),
+            (|costs| costs.db_scan.flat += EXTRA, 1),
+            (|costs| costs.db_next.flat += EXTRA, 1),
+            (|costs| costs.db_write.flat += EXTRA, 1),
+            (|costs| costs.db_remove.flat += EXTRA, 1),
+            (|costs| costs.db_remove_range.flat += EXTRA, 1),
+            (|costs| costs.secp256k1_verify.flat += EXTRA, 1),
+            (|costs| costs.secp256r1_verify.flat += EXTRA, 1),
+        ];
+        for (raise, calls) in cases {
+            let mut gas_costs = GasCosts::default();
+            raise(&mut gas_costs);
+            let gas_used = gas_used_by_host_function_caller(&gas_costs);
+            assert_eq!(gas_used - baseline, EXTRA * calls);
+        }
     }
 }
@@ -1,7 +1,8 @@
 use {
     grug_types::{
-        from_json_value, to_json_value, AccountResponse, Addr, Api, Binary, Coins, Hash,
-        InfoResponse, Querier, QueryRequest, StdResult, Storage, Timestamp, Uint128, Uint64,
+        from_json_value, to_json_value, AccountResponse, Addr, Api, Binary, CodeInfoResponse, Coins,
+        CronjobResponse, Hash, InfoResponse, Querier, QueryRequest, QueryResponse, StdResult,
+        Storage, Timestamp, Uint128, Uint64,
     },
     serde::{de::DeserializeOwned, ser::Serialize},
 };
@@ -170,6 +171,29 @@ macro_rules! impl_methods {
                 .map(|res| res.as_codes())
             }
 
+            #[inline]
+            pub fn query_code_info(&self, hash: Hash) -> StdResult<CodeInfoResponse> {
+                self.querier.query_chain(QueryRequest::CodeInfo {
+                    hash,
+                })
+                .map(|res| res.as_code_info())
+            }
+
+            #[inline]
+            pub fn query_code_infos(
+                &self,
+                start_after: Option<Hash>,
+                limit: Option<u32>,
+                uploader: Option<Addr>,
+            ) -> StdResult<Vec<CodeInfoResponse>> {
+                self.querier.query_chain(QueryRequest::CodeInfos {
+                    start_after,
+                    limit,
+                    uploader,
+                })
+                .map(|res| res.as_code_infos())
+            }
+
             #[inline]
             pub fn query_account(&self, address: Addr) -> StdResult<AccountResponse> {
                 self.querier.query_chain(QueryRequest::Account {
@@ -191,6 +215,19 @@ macro_rules! impl_methods {
                 .map(|res| res.as_accounts())
             }
 
+            #[inline]
+            pub fn query_next_cronjobs(
+                &self,
+                start_after: Option<Addr>,
+                limit: Option<u32>,
+            ) -> StdResult<Vec<CronjobResponse>> {
+                self.querier.query_chain(QueryRequest::NextCronjobs {
+                    start_after,
+                    limit,
+                })
+                .map(|res| res.as_next_cronjobs())
+            }
+
             #[inline]
             pub fn query_wasm_raw(&self, contract: Addr, key: Binary) -> StdResult<Option<Binary>> {
                 self.querier.query_chain(QueryRequest::WasmRaw {
@@ -212,6 +249,11 @@ macro_rules! impl_methods {
                 })
                 .and_then(|res| from_json_value(res.as_wasm_smart().data))
             }
+
+            #[inline]
+            pub fn query_multi(&self, reqs: Vec<QueryRequest>) -> StdResult<Vec<QueryResponse>> {
+                self.querier.query_chain(QueryRequest::Multi(reqs)).map(|res| res.as_multi())
+            }
         }
     };
 }
@@ -5,9 +5,10 @@ use {
         ImmutableCtx, MutableCtx, Region, SudoCtx,
     },
     grug_types::{
-        from_borsh_slice, from_json_slice, to_json_vec, BankQueryMsg, BankQueryResponse, Context,
-        GenericResult, IbcClientUpdateMsg, IbcClientVerifyMsg, Json, Response, SubMsgResult,
-        TransferMsg, Tx,
+        from_borsh_slice, from_json_slice, to_json_vec, BankQueryMsg, BankQueryResponse, Binary,
+        Context, GasReport, GenericResult, IbcClientUpdateMsg, IbcClientVerifyMsg, Json,
+        PrepareProposalMsg, ProcessProposalMsg, Response, SubMsgResult, TransferMsg, Tx,
+        ValidatorUpdate, VerifyVoteExtensionMsg, VoteExtension,
     },
     serde::de::DeserializeOwned,
 };
@@ -306,6 +307,272 @@ where
     after_block_fn(sudo_ctx).into()
 }
 
+// ------------------------------- cron execute --------------------------------
+
+pub fn do_cron_execute<E>(
+    cron_execute_fn: &dyn Fn(SudoCtx) -> Result<Response, E>,
+    ctx_ptr: usize,
+) -> usize
+where
+    E: ToString,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+
+    let res = _do_cron_execute(cron_execute_fn, &ctx_bytes);
+    let res_bytes = to_json_vec(&res).unwrap();
+
+    Region::release_buffer(res_bytes) as usize
+}
+
+fn _do_cron_execute<E>(
+    cron_execute_fn: &dyn Fn(SudoCtx) -> Result<Response, E>,
+    ctx_bytes: &[u8],
+) -> GenericResult<Response>
+where
+    E: ToString,
+{
+    let ctx: Context = unwrap_into_generic_result!(from_borsh_slice(ctx_bytes));
+    let sudo_ctx = make_sudo_ctx!(ctx, &mut ExternalStorage, &ExternalApi, &ExternalQuerier);
+
+    cron_execute_fn(sudo_ctx).into()
+}
+
+// ------------------------------ init validators ------------------------------
+
+#[allow(clippy::type_complexity)]
+pub fn do_init_validators<E>(
+    init_validators_fn: &dyn Fn(SudoCtx, Vec<ValidatorUpdate>) -> Result<Response, E>,
+    ctx_ptr: usize,
+    validators_ptr: usize,
+) -> usize
+where
+    E: ToString,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+    let validators_bytes = unsafe { Region::consume(validators_ptr as *mut Region) };
+
+    let res = _do_init_validators(init_validators_fn, &ctx_bytes, &validators_bytes);
+    let res_bytes = to_json_vec(&res).unwrap();
+
+    Region::release_buffer(res_bytes) as usize
+}
+
+#[allow(clippy::type_complexity)]
+fn _do_init_validators<E>(
+    init_validators_fn: &dyn Fn(SudoCtx, Vec<ValidatorUpdate>) -> Result<Response, E>,
+    ctx_bytes: &[u8],
+    validators_bytes: &[u8],
+) -> GenericResult<Response>
+where
+    E: ToString,
+{
+    let ctx: Context = unwrap_into_generic_result!(from_borsh_slice(ctx_bytes));
+    let sudo_ctx = make_sudo_ctx!(ctx, &mut ExternalStorage, &ExternalApi, &ExternalQuerier);
+    let validators = unwrap_into_generic_result!(from_json_slice(validators_bytes));
+
+    init_validators_fn(sudo_ctx, validators).into()
+}
+
+// ----------------------------- validator updates -----------------------------
+
+pub fn do_validator_updates<E>(
+    validator_updates_fn: &dyn Fn(SudoCtx) -> Result<Vec<ValidatorUpdate>, E>,
+    ctx_ptr: usize,
+) -> usize
+where
+    E: ToString,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+
+    let res = _do_validator_updates(validator_updates_fn, &ctx_bytes);
+    let res_bytes = to_json_vec(&res).unwrap();
+
+    Region::release_buffer(res_bytes) as usize
+}
+
+fn _do_validator_updates<E>(
+    validator_updates_fn: &dyn Fn(SudoCtx) -> Result<Vec<ValidatorUpdate>, E>,
+    ctx_bytes: &[u8],
+) -> GenericResult<Vec<ValidatorUpdate>>
+where
+    E: ToString,
+{
+    let ctx: Context = unwrap_into_generic_result!(from_borsh_slice(ctx_bytes));
+    let sudo_ctx = make_sudo_ctx!(ctx, &mut ExternalStorage, &ExternalApi, &ExternalQuerier);
+
+    validator_updates_fn(sudo_ctx).into()
+}
+
+// ----------------------------- prepare proposal ------------------------------
+
+#[allow(clippy::type_complexity)]
+pub fn do_prepare_proposal<E>(
+    prepare_fn: &dyn Fn(ImmutableCtx, PrepareProposalMsg) -> Result<Vec<Binary>, E>,
+    ctx_ptr: usize,
+    msg_ptr: usize,
+) -> usize
+where
+    E: ToString,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+    let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
+
+    let res = _do_prepare_proposal(prepare_fn, &ctx_bytes, &msg_bytes);
+    let res_bytes = to_json_vec(&res).unwrap();
+
+    Region::release_buffer(res_bytes) as usize
+}
+
+#[allow(clippy::type_complexity)]
+fn _do_prepare_proposal<E>(
+    prepare_fn: &dyn Fn(ImmutableCtx, PrepareProposalMsg) -> Result<Vec<Binary>, E>,
+    ctx_bytes: &[u8],
+    msg_bytes: &[u8],
+) -> GenericResult<Vec<Binary>>
+where
+    E: ToString,
+{
+    let ctx: Context = unwrap_into_generic_result!(from_borsh_slice(ctx_bytes));
+    let immutable_ctx = make_immutable_ctx!(ctx, &ExternalStorage, &ExternalApi, &ExternalQuerier);
+    let msg = unwrap_into_generic_result!(from_json_slice(msg_bytes));
+
+    prepare_fn(immutable_ctx, msg).into()
+}
+
+// ----------------------------- process proposal ------------------------------
+
+pub fn do_process_proposal<E>(
+    process_fn: &dyn Fn(ImmutableCtx, ProcessProposalMsg) -> Result<(), E>,
+    ctx_ptr: usize,
+    msg_ptr: usize,
+) -> usize
+where
+    E: ToString,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+    let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
+
+    let res = _do_process_proposal(process_fn, &ctx_bytes, &msg_bytes);
+    let res_bytes = to_json_vec(&res).unwrap();
+
+    Region::release_buffer(res_bytes) as usize
+}
+
+fn _do_process_proposal<E>(
+    process_fn: &dyn Fn(ImmutableCtx, ProcessProposalMsg) -> Result<(), E>,
+    ctx_bytes: &[u8],
+    msg_bytes: &[u8],
+) -> GenericResult<()>
+where
+    E: ToString,
+{
+    let ctx: Context = unwrap_into_generic_result!(from_borsh_slice(ctx_bytes));
+    let immutable_ctx = make_immutable_ctx!(ctx, &ExternalStorage, &ExternalApi, &ExternalQuerier);
+    let msg = unwrap_into_generic_result!(from_json_slice(msg_bytes));
+
+    process_fn(immutable_ctx, msg).into()
+}
+
+// -------------------------------- extend vote --------------------------------
+
+pub fn do_extend_vote<E>(
+    extend_fn: &dyn Fn(ImmutableCtx) -> Result<Binary, E>,
+    ctx_ptr: usize,
+) -> usize
+where
+    E: ToString,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+
+    let res = _do_extend_vote(extend_fn, &ctx_bytes);
+    let res_bytes = to_json_vec(&res).unwrap();
+
+    Region::release_buffer(res_bytes) as usize
+}
+
+fn _do_extend_vote<E>(
+    extend_fn: &dyn Fn(ImmutableCtx) -> Result<Binary, E>,
+    ctx_bytes: &[u8],
+) -> GenericResult<Binary>
+where
+    E: ToString,
+{
+    let ctx: Context = unwrap_into_generic_result!(from_borsh_slice(ctx_bytes));
+    let immutable_ctx = make_immutable_ctx!(ctx, &ExternalStorage, &ExternalApi, &ExternalQuerier);
+
+    extend_fn(immutable_ctx).into()
+}
+
+// --------------------------- verify vote extension ---------------------------
+
+pub fn do_verify_vote_extension<E>(
+    verify_fn: &dyn Fn(ImmutableCtx, VerifyVoteExtensionMsg) -> Result<(), E>,
+    ctx_ptr: usize,
+    msg_ptr: usize,
+) -> usize
+where
+    E: ToString,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+    let msg_bytes = unsafe { Region::consume(msg_ptr as *mut Region) };
+
+    let res = _do_verify_vote_extension(verify_fn, &ctx_bytes, &msg_bytes);
+    let res_bytes = to_json_vec(&res).unwrap();
+
+    Region::release_buffer(res_bytes) as usize
+}
+
+fn _do_verify_vote_extension<E>(
+    verify_fn: &dyn Fn(ImmutableCtx, VerifyVoteExtensionMsg) -> Result<(), E>,
+    ctx_bytes: &[u8],
+    msg_bytes: &[u8],
+) -> GenericResult<()>
+where
+    E: ToString,
+{
+    let ctx: Context = unwrap_into_generic_result!(from_borsh_slice(ctx_bytes));
+    let immutable_ctx = make_immutable_ctx!(ctx, &ExternalStorage, &ExternalApi, &ExternalQuerier);
+    let msg = unwrap_into_generic_result!(from_json_slice(msg_bytes));
+
+    verify_fn(immutable_ctx, msg).into()
+}
+
+// -------------------------- receive vote extensions --------------------------
+
+#[allow(clippy::type_complexity)]
+pub fn do_receive_vote_extensions<E>(
+    receive_fn: &dyn Fn(SudoCtx, Vec<VoteExtension>) -> Result<Response, E>,
+    ctx_ptr: usize,
+    extensions_ptr: usize,
+) -> usize
+where
+    E: ToString,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+    let extensions_bytes = unsafe { Region::consume(extensions_ptr as *mut Region) };
+
+    let res = _do_receive_vote_extensions(receive_fn, &ctx_bytes, &extensions_bytes);
+    let res_bytes = to_json_vec(&res).unwrap();
+
+    Region::release_buffer(res_bytes) as usize
+}
+
+#[allow(clippy::type_complexity)]
+fn _do_receive_vote_extensions<E>(
+    receive_fn: &dyn Fn(SudoCtx, Vec<VoteExtension>) -> Result<Response, E>,
+    ctx_bytes: &[u8],
+    extensions_bytes: &[u8],
+) -> GenericResult<Response>
+where
+    E: ToString,
+{
+    let ctx: Context = unwrap_into_generic_result!(from_borsh_slice(ctx_bytes));
+    let sudo_ctx = make_sudo_ctx!(ctx, &mut ExternalStorage, &ExternalApi, &ExternalQuerier);
+    let extensions = unwrap_into_generic_result!(from_json_slice(extensions_bytes));
+
+    receive_fn(sudo_ctx, extensions).into()
+}
+
 // --------------------------------- before tx ---------------------------------
 
 pub fn do_before_tx<E>(
@@ -374,6 +641,40 @@ where
     after_tx_fn(auth_ctx, tx).into()
 }
 
+// -------------------------------- handle fee ---------------------------------
+
+pub fn do_handle_fee<E>(
+    handle_fee_fn: &dyn Fn(SudoCtx, GasReport) -> Result<Response, E>,
+    ctx_ptr: usize,
+    report_ptr: usize,
+) -> usize
+where
+    E: ToString,
+{
+    let ctx_bytes = unsafe { Region::consume(ctx_ptr as *mut Region) };
+    let report_bytes = unsafe { Region::consume(report_ptr as *mut Region) };
+
+    let res = _do_handle_fee(handle_fee_fn, &ctx_bytes, &report_bytes);
+    let res_bytes = to_json_vec(&res).unwrap();
+
+    Region::release_buffer(res_bytes) as usize
+}
+
+fn _do_handle_fee<E>(
+    handle_fee_fn: &dyn Fn(SudoCtx, GasReport) -> Result<Response, E>,
+    ctx_bytes: &[u8],
+    report_bytes: &[u8],
+) -> GenericResult<Response>
+where
+    E: ToString,
+{
+    let ctx: Context = unwrap_into_generic_result!(from_borsh_slice(ctx_bytes));
+    let sudo_ctx = make_sudo_ctx!(ctx, &mut ExternalStorage, &ExternalApi, &ExternalQuerier);
+    let report = unwrap_into_generic_result!(from_json_slice(report_bytes));
+
+    handle_fee_fn(sudo_ctx, report).into()
+}
+
 // ------------------------------- bank transfer -------------------------------
 
 pub fn do_bank_transfer<E>(
@@ -24,6 +24,7 @@ extern "C" {
     // write ops (mutate the state):
     fn db_write(key_ptr: usize, value_ptr: usize);
     fn db_remove(key_ptr: usize);
+    fn db_remove_range(min_ptr: usize, max_ptr: usize);
 
     // print a debug message to the client's CLI output.
     fn debug(addr_ptr: usize, msg_ptr: usize);
@@ -110,6 +111,17 @@ impl Storage for ExternalStorage {
 
         unsafe { db_remove(key_ptr as usize) }
     }
+
+    fn remove_range(&mut self, min: Option<&[u8]>, max: Option<&[u8]>) {
+        // same as in `scan`, the Regions must be kept in scope
+        let min_region = min.map(Region::build);
+        let min_ptr = get_optional_region_ptr(min_region.as_ref());
+
+        let max_region = max.map(Region::build);
+        let max_ptr = get_optional_region_ptr(max_region.as_ref());
+
+        unsafe { db_remove_range(min_ptr, max_ptr) }
+    }
 }
 
 pub struct ExternalIterator {
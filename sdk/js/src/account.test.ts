@@ -1,10 +1,15 @@
 import { expect, test } from "vitest";
 import { Message, createSignBytes, encodeHex } from ".";
 
-test("creating sign bytes", () => {
+test.each([
+  [undefined, undefined, "4bda4a203d001699a89494d17381c37fd62c763f54e5f840e5a0ca6044d415cc"],
+  ["100", undefined, "3bb41f91424d07b60957ed027271a6ab744fc7945442036709e402fad18ab5c4"],
+  ["100", "1700000000000000000", "04d33dec7439fd9091eadf2eff75803e58fbb95c550bc7b56de426c677c2cfb6"],
+])("creating sign bytes, expiry = %s, %s", (expiryHeight, expiryTimestamp, expected) => {
   const sender = "0xc3e1842184f9c0271b1dadd719c6f3d172e715ea60bf445d63489a4dbed1f6e4";
   const chainId = "dev-1";
   const sequence = 0;
+  const gasLimit = "1000000";
   const msg: Message = {
     transfer: {
       to: "0xecd3d63044b62571eb98dea97bd62142bb7b32b9ab590ccaffa2b50134b19db2",
@@ -20,6 +25,14 @@ test("creating sign bytes", () => {
       ],
     },
   };
-  const signBytes = createSignBytes([msg], sender, chainId, sequence);
-  expect(encodeHex(signBytes)).toStrictEqual("baf97f186c39cbb4d4738ee98e8914e6738685cce45c59ca702d201f3375540a");
+  const signBytes = createSignBytes(
+    [msg],
+    sender,
+    chainId,
+    sequence,
+    gasLimit,
+    expiryHeight,
+    expiryTimestamp,
+  );
+  expect(encodeHex(signBytes)).toStrictEqual(expected);
 })
@@ -1,5 +1,13 @@
 import { Sha256 } from "@cosmjs/crypto";
-import { type Message, decodeHex, encodeBigEndian32, encodeHex, encodeUtf8, serialize } from ".";
+import {
+  type Message,
+  decodeHex,
+  encodeBigEndian32,
+  encodeBigEndian64,
+  encodeHex,
+  encodeUtf8,
+  serialize,
+} from ".";
 
 /**
  * Given parameters used while instantiating a new contract, compute what the
@@ -51,17 +59,37 @@ export function createSalt(
  * Generate sign byte that the grug-account contract expects.
  *
  * Mirrors the Rust function: `grug_account::sign_bytes`.
+ *
+ * @param gasLimit The transaction's gas limit, as a string.
+ * @param expiryHeight The last block height at which the transaction can be
+ * included, if any, as a string.
+ * @param expiryTimestamp The last block timestamp at which the transaction can
+ * be included, if any, in nanoseconds since the UNIX epoch.
  */
 export function createSignBytes(
   msgs: Message[],
   sender: string,
   chainId: string,
   sequence: number,
+  gasLimit: string,
+  expiryHeight?: string,
+  expiryTimestamp?: string,
 ): Uint8Array {
   const hasher = new Sha256();
   hasher.update(serialize(msgs));
   hasher.update(decodeHex(sender.substring(2))); // strip the 0x prefix
   hasher.update(encodeUtf8(chainId));
   hasher.update(encodeBigEndian32(sequence));
+  hasher.update(encodeBigEndian64(BigInt(gasLimit)));
+  // the optional fields are prefixed with a tag, so that an absent value can't
+  // be confused with any present one
+  for (const expiry of [expiryHeight, expiryTimestamp]) {
+    if (expiry === undefined) {
+      hasher.update(new Uint8Array([0]));
+    } else {
+      hasher.update(new Uint8Array([1]));
+      hasher.update(encodeBigEndian64(BigInt(expiry)));
+    }
+  }
   return hasher.digest();
 }
@@ -290,6 +290,9 @@ export class Client {
       signOpts.sender,
       signOpts.chainId,
       signOpts.sequence,
+      signOpts.gasLimit,
+      signOpts.expiryHeight,
+      signOpts.expiryTimestamp,
     );
 
     return this.broadcastTx(tx);
@@ -406,6 +409,9 @@ export type SigningOptions = {
   sender: string;
   chainId?: string;
   sequence?: number;
+  gasLimit: string;
+  expiryHeight?: string;
+  expiryTimestamp?: string;
 };
 
 export enum AdminOptionKind {
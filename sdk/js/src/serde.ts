@@ -99,6 +99,17 @@ export function encodeBigEndian32(value: number): Uint8Array {
   return new Uint8Array(buffer);
 }
 
+/**
+ * Given a number, assume it is a non-negative integer, encode it as 64-bit big
+ * endian bytes.
+ */
+export function encodeBigEndian64(value: number | bigint): Uint8Array {
+  const buffer = new ArrayBuffer(8);
+  const view = new DataView(buffer);
+  view.setBigUint64(0, BigInt(value), false);
+  return new Uint8Array(buffer);
+}
+
 /**
  * Given a byte array, attempt to deserialize it into a number as 32-bit big
  * endian encoding. Error if the byte array isn't exactly 4 bytes in length.
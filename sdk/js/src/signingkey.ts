@@ -59,8 +59,19 @@ export class SigningKey {
     sender: string,
     chainId: string,
     sequence: number,
+    gasLimit: string,
+    expiryHeight?: string,
+    expiryTimestamp?: string,
   ): Promise<Uint8Array> {
-    const signBytes = createSignBytes(msgs, sender, chainId, sequence);
+    const signBytes = createSignBytes(
+      msgs,
+      sender,
+      chainId,
+      sequence,
+      gasLimit,
+      expiryHeight,
+      expiryTimestamp,
+    );
     return this.signHash(signBytes);
   }
 
@@ -72,11 +83,25 @@ export class SigningKey {
     sender: string,
     chainId: string,
     sequence: number,
+    gasLimit: string,
+    expiryHeight?: string,
+    expiryTimestamp?: string,
   ): Promise<Tx> {
-    const signature = await this.signTx(msgs, sender, chainId, sequence);
+    const signature = await this.signTx(
+      msgs,
+      sender,
+      chainId,
+      sequence,
+      gasLimit,
+      expiryHeight,
+      expiryTimestamp,
+    );
     return {
       sender,
       msgs,
+      gasLimit,
+      expiryHeight,
+      expiryTimestamp,
       credential: encodeBase64(signature),
     };
   }
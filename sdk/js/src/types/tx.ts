@@ -3,6 +3,12 @@ import type { Coin, Config } from ".";
 export type Tx = {
   sender: string;
   msgs: Message[];
+  /** As a string, since it may exceed the largest safe integer. */
+  gasLimit: string;
+  /** As a string, since it may exceed the largest safe integer. */
+  expiryHeight?: string;
+  /** In nanoseconds since the UNIX epoch, as a string. */
+  expiryTimestamp?: string;
   credential: string;
 };
 
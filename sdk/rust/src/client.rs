@@ -5,8 +5,9 @@ use {
     grug_jmt::Proof,
     grug::{
         from_json_slice, from_json_value, hash, to_json_value, to_json_vec, AccountResponse, Addr,
-        Binary, Coin, Coins, Config, Hash, InfoResponse, Message, QueryRequest, QueryResponse, Tx,
-        WasmRawResponse,
+        Binary, CodeInfoResponse, Coin, Coins, Config, ConsensusParams, CronjobResponse, Hash,
+        InfoResponse, Message, QueryRequest, QueryResponse, SimulateRequest, SimulateResponse,
+        Timestamp, Tx, Uint64, WasmRawResponse,
     },
     serde::{de::DeserializeOwned, ser::Serialize},
     std::any::type_name,
@@ -18,10 +19,13 @@ use {
 };
 
 pub struct SigningOptions {
-    pub signing_key: SigningKey,
-    pub sender:      Addr,
-    pub chain_id:    Option<String>,
-    pub sequence:    Option<u32>,
+    pub signing_key:      SigningKey,
+    pub sender:           Addr,
+    pub chain_id:         Option<String>,
+    pub sequence:         Option<u32>,
+    pub gas_limit:        Uint64,
+    pub expiry_height:    Option<Uint64>,
+    pub expiry_timestamp: Option<Timestamp>,
 }
 
 pub struct Client {
@@ -120,6 +124,30 @@ impl Client {
         Ok(from_json_slice(res.value)?)
     }
 
+    /// Query the app, along with the Merkle proofs of the storage keys that
+    /// the response is derived from. Only `Info`, `Account`, `Code`,
+    /// `CodeInfo`, and `WasmRaw` queries, and `Multi` queries consisting of
+    /// these, are supported.
+    pub async fn query_app_with_proof(
+        &self,
+        req: &QueryRequest,
+        height: Option<u64>,
+    ) -> anyhow::Result<(QueryResponse, Vec<(Vec<u8>, Proof)>)> {
+        let res = self.query("/app", to_json_vec(req)?.to_vec(), height, true).await?;
+        ensure!(res.proof.is_some());
+        let proofs = res
+            .proof
+            .unwrap()
+            .ops
+            .into_iter()
+            .map(|op| {
+                ensure!(op.field_type == type_name::<Proof>());
+                Ok((op.key, from_json_slice(&op.data)?))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok((from_json_slice(res.value)?, proofs))
+    }
+
     pub async fn query_info(&self, height: Option<u64>) -> anyhow::Result<InfoResponse> {
         let res = self.query_app(&QueryRequest::Info {}, height).await?;
         Ok(res.as_info())
@@ -176,6 +204,27 @@ impl Client {
         Ok(res.as_codes())
     }
 
+    pub async fn query_code_info(
+        &self,
+        hash: Hash,
+        height: Option<u64>,
+    ) -> anyhow::Result<CodeInfoResponse> {
+        let res = self.query_app(&QueryRequest::CodeInfo { hash }, height).await?;
+        Ok(res.as_code_info())
+    }
+
+    pub async fn query_code_infos(
+        &self,
+        start_after: Option<Hash>,
+        limit: Option<u32>,
+        uploader: Option<Addr>,
+        height: Option<u64>,
+    ) -> anyhow::Result<Vec<CodeInfoResponse>> {
+        let req = QueryRequest::CodeInfos { start_after, limit, uploader };
+        let res = self.query_app(&req, height).await?;
+        Ok(res.as_code_infos())
+    }
+
     pub async fn query_account(&self, address: Addr, height: Option<u64>) -> anyhow::Result<AccountResponse> {
         let res = self.query_app(&QueryRequest::Account { address }, height).await?;
         Ok(res.as_account())
@@ -191,6 +240,16 @@ impl Client {
         Ok(res.as_accounts())
     }
 
+    pub async fn query_next_cronjobs(
+        &self,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        height: Option<u64>,
+    ) -> anyhow::Result<Vec<CronjobResponse>> {
+        let res = self.query_app(&QueryRequest::NextCronjobs { start_after, limit }, height).await?;
+        Ok(res.as_next_cronjobs())
+    }
+
     pub async fn query_wasm_raw(
         &self,
         contract: Addr,
@@ -212,6 +271,17 @@ impl Client {
         Ok(from_json_value(res.as_wasm_smart().data)?)
     }
 
+    /// Perform multiple queries at once. All queries are evaluated at the same
+    /// block height.
+    pub async fn query_multi(
+        &self,
+        reqs: Vec<QueryRequest>,
+        height: Option<u64>,
+    ) -> anyhow::Result<Vec<QueryResponse>> {
+        let res = self.query_app(&QueryRequest::Multi(reqs), height).await?;
+        Ok(res.as_multi())
+    }
+
     // ------------------------------ tx methods -------------------------------
 
     /// Create, sign, and broadcast a transaction without confirmation.
@@ -233,6 +303,38 @@ impl Client {
         sign_opts: &SigningOptions,
         confirm_fn: fn(&Tx) -> anyhow::Result<bool>,
     ) -> anyhow::Result<Option<tx_sync::Response>> {
+        let tx = self.sign_tx(msgs, sign_opts).await?;
+
+        if confirm_fn(&tx)? {
+            let tx_bytes = to_json_vec(&tx)?;
+            Ok(Some(self.inner.broadcast_tx_sync(tx_bytes).await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Execute the messages in simulation mode, without broadcasting the tx.
+    /// Useful for finding out the amount of gas the tx needs.
+    pub async fn simulate(
+        &self,
+        msgs: Vec<Message>,
+        sign_opts: &SigningOptions,
+        with_written_keys: bool,
+    ) -> anyhow::Result<SimulateResponse> {
+        let tx = self.sign_tx(msgs, sign_opts).await?;
+        let req = SimulateRequest {
+            tx,
+            with_written_keys,
+        };
+        let res = self.query("/simulate", to_json_vec(&req)?, None, false).await?;
+        Ok(from_json_slice(res.value)?)
+    }
+
+    async fn sign_tx(
+        &self,
+        msgs: Vec<Message>,
+        sign_opts: &SigningOptions,
+    ) -> anyhow::Result<Tx> {
         let chain_id = match &sign_opts.chain_id {
             None => self.query_info(None).await?.chain_id,
             Some(id) => id.to_string(),
@@ -251,19 +353,15 @@ impl Client {
             Some(seq) => seq,
         };
 
-        let tx = sign_opts.signing_key.create_and_sign_tx(
+        sign_opts.signing_key.create_and_sign_tx(
             msgs,
             sign_opts.sender.clone(),
             &chain_id,
             sequence,
-        )?;
-
-        if confirm_fn(&tx)? {
-            let tx_bytes = to_json_vec(&tx)?;
-            Ok(Some(self.inner.broadcast_tx_sync(tx_bytes).await?))
-        } else {
-            Ok(None)
-        }
+            sign_opts.gas_limit,
+            sign_opts.expiry_height,
+            sign_opts.expiry_timestamp,
+        )
     }
 
     pub async fn set_config(
@@ -271,7 +369,25 @@ impl Client {
         new_cfg: Config,
         sign_opts: &SigningOptions,
     ) -> anyhow::Result<tx_sync::Response> {
-        self.send_tx(vec![Message::SetConfig { new_cfg }], sign_opts).await
+        self.send_tx(vec![Message::SetConfig { new_cfg: Box::new(new_cfg) }], sign_opts).await
+    }
+
+    pub async fn update_consensus_params(
+        &self,
+        updates: ConsensusParams,
+        sign_opts: &SigningOptions,
+    ) -> anyhow::Result<tx_sync::Response> {
+        self.send_tx(vec![Message::UpdateConsensusParams { updates }], sign_opts).await
+    }
+
+    pub async fn schedule_upgrade(
+        &self,
+        height: Uint64,
+        name: String,
+        info: Option<String>,
+        sign_opts: &SigningOptions,
+    ) -> anyhow::Result<tx_sync::Response> {
+        self.send_tx(vec![Message::ScheduleUpgrade { height, name, info }], sign_opts).await
     }
 
     pub async fn transfer(
@@ -288,7 +404,7 @@ impl Client {
         code: Binary,
         sign_opts: &SigningOptions,
     ) -> anyhow::Result<tx_sync::Response> {
-        self.send_tx(vec![Message::Upload { code }], sign_opts).await
+        self.send_tx(vec![Message::Upload { code, instantiate_permission: None }], sign_opts).await
     }
 
     pub async fn instantiate<M: Serialize>(
@@ -303,7 +419,7 @@ impl Client {
         let address = Addr::compute(&sign_opts.sender, &code_hash, &salt);
         let msg = to_json_value(msg)?;
         let admin = admin.decide(&Addr::compute(&sign_opts.sender, &code_hash, &salt));
-        let res = self.send_tx(vec![Message::Instantiate { code_hash, msg, salt, funds, admin }], sign_opts).await?;
+        let res = self.send_tx(vec![Message::Instantiate { code_hash, msg, salt, funds, admin, non_reentrant: false }], sign_opts).await?;
         Ok((address, res))
     }
 
@@ -320,8 +436,8 @@ impl Client {
         let address = Addr::compute(&sign_opts.sender, &code_hash, &salt);
         let msg = to_json_value(msg)?;
         let admin = admin.decide(&address);
-        let upload_msg = Message::Upload { code };
-        let instantiate_msg = Message::Instantiate { code_hash, msg, salt, funds, admin };
+        let upload_msg = Message::Upload { code, instantiate_permission: None };
+        let instantiate_msg = Message::Instantiate { code_hash, msg, salt, funds, admin, non_reentrant: false };
         let res = self.send_tx(vec![upload_msg, instantiate_msg], sign_opts).await?;
         Ok((address, res))
     }
@@ -348,6 +464,23 @@ impl Client {
         self.send_tx(vec![Message::Migrate { contract, new_code_hash, msg }], sign_opts).await
     }
 
+    pub async fn update_admin(
+        &self,
+        contract: Addr,
+        new_admin: Addr,
+        sign_opts: &SigningOptions,
+    ) -> anyhow::Result<tx_sync::Response> {
+        self.send_tx(vec![Message::UpdateAdmin { contract, new_admin }], sign_opts).await
+    }
+
+    pub async fn clear_admin(
+        &self,
+        contract: Addr,
+        sign_opts: &SigningOptions,
+    ) -> anyhow::Result<tx_sync::Response> {
+        self.send_tx(vec![Message::ClearAdmin { contract }], sign_opts).await
+    }
+
     pub async fn create_client<A: Serialize, B: Serialize>(
         &self,
         code_hash: Hash,
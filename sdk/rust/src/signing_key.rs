@@ -2,7 +2,7 @@ use {
     aes_gcm::{aead::Aead, AeadCore, Aes256Gcm, Key, KeyInit},
     bip32::{Mnemonic, PublicKey, XPrv},
     grug_crypto::Identity256,
-    grug::{Addr, Binary, Message, Tx},
+    grug::{Addr, Binary, Message, Timestamp, Tx, Uint64},
     k256::ecdsa::Signature,
     pbkdf2::pbkdf2_hmac,
     rand::{rngs::OsRng, Rng},
@@ -98,18 +98,33 @@ impl SigningKey {
         signature.to_vec()
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_and_sign_tx(
         &self,
-        msgs:     Vec<Message>,
-        sender:   Addr,
-        chain_id: &str,
-        sequence: u32,
+        msgs:             Vec<Message>,
+        sender:           Addr,
+        chain_id:         &str,
+        sequence:         u32,
+        gas_limit:        Uint64,
+        expiry_height:    Option<Uint64>,
+        expiry_timestamp: Option<Timestamp>,
     ) -> anyhow::Result<Tx> {
-        let sign_bytes = grug_account::sign_bytes(&msgs, &sender, chain_id, sequence)?;
+        let sign_bytes = grug_account::sign_bytes(
+            &msgs,
+            &sender,
+            chain_id,
+            sequence,
+            gas_limit,
+            expiry_height,
+            expiry_timestamp,
+        )?;
         let signature = self.sign_digest(&sign_bytes);
         Ok(Tx {
             sender,
             msgs,
+            gas_limit,
+            expiry_height,
+            expiry_timestamp,
             credential: signature.into(),
         })
     }